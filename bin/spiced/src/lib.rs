@@ -19,14 +19,11 @@ limitations under the License.
 use std::env;
 use std::net::SocketAddr;
 use std::path::PathBuf;
-use std::pin::Pin;
 use std::sync::Arc;
 
 use app::{App, AppBuilder};
 use clap::Parser;
 use flightrepl::ReplConfig;
-use futures::future::join_all;
-use futures::Future;
 use runtime::config::Config as RuntimeConfig;
 
 use runtime::podswatcher::PodsWatcher;
@@ -56,9 +53,15 @@ pub enum Error {
     #[snafu(display("Unable to create data backend: {source}"))]
     UnableToCreateBackend { source: runtime::datafusion::Error },
 
+    #[snafu(display("Unable to use the temp directory: {source}"))]
+    UnableToUseTempDir { source: runtime::datafusion::Error },
+
     #[snafu(display("Failed to start pods watcher: {source}"))]
     UnableToInitializePodsWatcher { source: runtime::NotifyError },
 
+    #[snafu(display("Unable to load components: {source}"))]
+    UnableToLoadComponents { source: runtime::Error },
+
     #[snafu(display("Generic Error: {reason}"))]
     GenericError { reason: String },
 }
@@ -112,8 +115,19 @@ pub async fn run(args: Args) -> Result<()> {
         }
     }
 
+    if let Some(max_memory) = args.runtime.max_memory {
+        runtime::datafusion::set_max_memory(max_memory);
+    }
+    if let Some(temp_dir) = &args.runtime.temp_dir {
+        runtime::datafusion::set_temp_dir(temp_dir).context(UnableToUseTempDirSnafu)?;
+    }
+
     let mut rt: Runtime = Runtime::new(app, Arc::new(extension_factories)).await;
 
+    if let Some(threshold) = args.runtime.slow_query_threshold {
+        rt.datafusion().set_slow_query_threshold(threshold);
+    }
+
     // mutable reference
     rt.with_pods_watcher(pods_watcher);
     if let Err(err) = rt
@@ -124,35 +138,38 @@ pub async fn run(args: Args) -> Result<()> {
         tracing::warn!("{err}");
     }
 
+    // Set before any query runs, so timestamps are rendered consistently.
+    rt.init_session_timezone(args.runtime.session_timezone.as_deref())
+        .await;
+    rt.init_query_parallelism(args.runtime.query_parallelism)
+        .await;
+
+    let startup_report = args.runtime.startup_report.clone();
+    let ready_timeout = args.runtime.ready_timeout;
     let cloned_rt = rt.clone();
     let server_thread =
         tokio::spawn(async move { cloned_rt.start_servers(args.runtime, args.metrics).await });
 
     rt.load_secrets().await;
 
-    let mut futures: Vec<Pin<Box<dyn Future<Output = ()>>>> = vec![
-        Box::pin(async {
-            if let Err(err) = rt.init_query_history().await {
-                tracing::warn!("Creating internal query history table: {err}");
-            };
-        }),
-        Box::pin(rt.init_results_cache()),
-        Box::pin(rt.start_extensions()),
-        Box::pin(rt.load_datasets()),
-    ];
-
-    if cfg!(feature = "models") {
-        let mut v: Vec<Pin<Box<dyn Future<Output = ()>>>> = vec![
-            Box::pin(rt.load_models()),
-            Box::pin(rt.load_llms()),
-            Box::pin(rt.load_embeddings()),
-        ];
-
-        futures.append(&mut v);
-    }
+    let load_components = async {
+        match ready_timeout {
+            Some(ready_timeout) => rt.load_components_with_timeout(ready_timeout).await,
+            None => {
+                rt.load_components().await;
+                Ok(())
+            }
+        }
+    };
 
     tokio::select! {
-        _ = join_all(futures) => {},
+        result = load_components => {
+            result.context(UnableToLoadComponentsSnafu)?;
+
+            if let Some(path) = startup_report {
+                rt.write_startup_report(&path).await;
+            }
+        },
         () = runtime::shutdown_signal() => {
             tracing::debug!("Cancelling runtime initializing!");
         },
@@ -49,6 +49,15 @@ fn main() {
         return;
     }
 
+    if !cfg!(feature = "release") {
+        // Identify pre-release builds to the services they connect to
+        util::http::set_user_agent(format!(
+            "spiced/{}-rc.{}",
+            env!("CARGO_PKG_VERSION"),
+            env!("GIT_COMMIT_HASH")
+        ));
+    }
+
     let tokio_runtime = match Runtime::new() {
         Ok(runtime) => runtime,
         Err(err) => {
@@ -84,7 +93,7 @@ fn init_tracing() -> Result<(), Box<dyn std::error::Error>> {
     let filter = if let Ok(env_log) = std::env::var("SPICED_LOG") {
         EnvFilter::new(env_log)
     } else {
-        EnvFilter::new("spiced=INFO,runtime=INFO,secrets=INFO,sql_provider_datafusion=INFO,data_components=INFO,cache=INFO,extensions=INFO,spice_cloud=INFO")
+        EnvFilter::new("spiced=INFO,runtime=INFO,secrets=INFO,sql_provider_datafusion=INFO,data_components=INFO,cache=INFO,extensions=INFO,spice_cloud=INFO,slow_query=WARN")
     };
 
     let subscriber = tracing_subscriber::FmtSubscriber::builder()
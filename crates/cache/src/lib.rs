@@ -17,6 +17,7 @@ limitations under the License.
 use std::collections::HashSet;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::SystemTime;
@@ -26,7 +27,6 @@ use arrow::array::RecordBatch;
 use arrow::datatypes::Schema;
 use async_trait::async_trait;
 use byte_unit::Byte;
-use datafusion::execution::SendableRecordBatchStream;
 use datafusion::logical_expr::LogicalPlan;
 use fundu::ParseError;
 use lru_cache::LruCache;
@@ -58,29 +58,37 @@ pub enum Error {
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
-pub struct QueryResult {
-    pub data: SendableRecordBatchStream,
-    pub from_cache: Option<bool>,
-}
-
-impl QueryResult {
-    #[must_use]
-    pub fn new(data: SendableRecordBatchStream, from_cache: Option<bool>) -> Self {
-        QueryResult { data, from_cache }
-    }
-}
-
 #[derive(Clone)]
 pub struct CachedQueryResult {
     pub records: Arc<Vec<RecordBatch>>,
     pub schema: Arc<Schema>,
     pub input_tables: Arc<HashSet<String>>,
+    pub tag: CacheEntryTag,
+}
+
+/// Identifies a cached result by the cache key of its query and the version of its cache entry, so that a result
+/// cached again after its entry expired or was invalidated gets a different tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheEntryTag {
+    key: u64,
+    version: u64,
+}
+
+impl Display for CacheEntryTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}-{:x}", self.key, self.version)
+    }
 }
 
 #[async_trait]
 pub trait QueryResultCache {
-    async fn get(&self, plan: &LogicalPlan) -> Result<Option<CachedQueryResult>>;
-    async fn put(&self, plan: &LogicalPlan, result: CachedQueryResult) -> Result<()>;
+    async fn get(&self, key: u64) -> Result<Option<CachedQueryResult>>;
+    async fn put(
+        &self,
+        key: u64,
+        result: CachedQueryResult,
+        ttl: std::time::Duration,
+    ) -> Result<()>;
     async fn invalidate_for_table(&self, table_name: &str) -> Result<()>;
     fn size_bytes(&self) -> u64;
     fn item_count(&self) -> u64;
@@ -90,7 +98,11 @@ pub struct QueryResultsCacheProvider {
     cache: Arc<dyn QueryResultCache + Send + Sync>,
     cache_max_size: u64,
     ttl: std::time::Duration,
+    key_headers: Vec<String>,
     metrics_reported_last_time: AtomicU64,
+    /// Version of the next cache entry. Starts at the time the provider is created, so that the versions of entries
+    /// cached by a previous run aren't reused.
+    next_version: AtomicU64,
 }
 
 impl QueryResultsCacheProvider {
@@ -111,10 +123,16 @@ impl QueryResultsCacheProvider {
         };
 
         let cache_provider = QueryResultsCacheProvider {
-            cache: Arc::new(LruCache::new(cache_max_size, ttl)),
+            cache: Arc::new(LruCache::new(cache_max_size)),
             cache_max_size,
             ttl,
+            key_headers: config
+                .cache_key_headers
+                .iter()
+                .map(|header| header.to_lowercase())
+                .collect(),
             metrics_reported_last_time: AtomicU64::new(0),
+            next_version: AtomicU64::new(current_time_nanos()),
         };
 
         #[allow(clippy::cast_precision_loss)]
@@ -123,12 +141,20 @@ impl QueryResultsCacheProvider {
         Ok(cache_provider)
     }
 
+    /// Returns the cached result of `plan`, if any.
+    ///
+    /// `key_components` scope the cached result, i.e. results cached with different components aren't shared.
+    ///
     /// # Errors
     ///
     /// Will return `Err` if method fails to access the cache
-    pub async fn get(&self, plan: &LogicalPlan) -> Result<Option<CachedQueryResult>> {
+    pub async fn get(
+        &self,
+        plan: &LogicalPlan,
+        key_components: &[String],
+    ) -> Result<Option<CachedQueryResult>> {
         metrics::counter!("results_cache_request_count").increment(1);
-        match self.cache.get(plan).await {
+        match self.cache.get(cache_key(plan, key_components)).await {
             Ok(Some(cached_result)) => {
                 metrics::counter!("results_cache_hit_count").increment(1);
                 Ok(Some(cached_result))
@@ -138,15 +164,39 @@ impl QueryResultsCacheProvider {
         }
     }
 
+    /// Caches `result` for `ttl`, nothing is cached if `ttl` is zero.
+    ///
     /// # Errors
     ///
     /// Will return `Err` if method fails to access the cache
-    pub async fn put(&self, plan: &LogicalPlan, result: CachedQueryResult) -> Result<()> {
-        let res = self.cache.put(plan, result).await;
+    pub async fn put(
+        &self,
+        plan: &LogicalPlan,
+        key_components: &[String],
+        result: CachedQueryResult,
+        ttl: std::time::Duration,
+    ) -> Result<()> {
+        if ttl.is_zero() {
+            return Ok(());
+        }
+
+        let res = self
+            .cache
+            .put(cache_key(plan, key_components), result, ttl)
+            .await;
         self.report_size_metrics();
         res
     }
 
+    /// Tags a result of `plan` that is about to be cached with a new version.
+    #[must_use]
+    pub fn new_entry_tag(&self, plan: &LogicalPlan, key_components: &[String]) -> CacheEntryTag {
+        CacheEntryTag {
+            key: cache_key(plan, key_components),
+            version: self.next_version.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
     fn report_size_metrics(&self) {
         let now_seconds = current_time_secs();
 
@@ -167,11 +217,23 @@ impl QueryResultsCacheProvider {
         self.cache.invalidate_for_table(table_name).await
     }
 
+    /// Names (lowercased) of the request headers whose values are added to the cache key of HTTP queries.
+    #[must_use]
+    pub fn key_headers(&self) -> &[String] {
+        &self.key_headers
+    }
+
     #[must_use]
     pub fn max_size(&self) -> u64 {
         self.cache_max_size
     }
 
+    /// How long results are cached, unless the datasets they're read from override it.
+    #[must_use]
+    pub fn ttl(&self) -> std::time::Duration {
+        self.ttl
+    }
+
     #[must_use]
     pub fn size(&self) -> u64 {
         self.cache.size_bytes()
@@ -194,9 +256,24 @@ impl Display for QueryResultsCacheProvider {
     }
 }
 
+fn cache_key(plan: &LogicalPlan, key_components: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    plan.hash(&mut hasher);
+    key_components.hash(&mut hasher);
+    hasher.finish()
+}
+
 fn current_time_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs()
 }
+
+fn current_time_nanos() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    u64::try_from(nanos).unwrap_or(u64::MAX)
+}
@@ -18,24 +18,51 @@ use crate::FailedToInvalidateCacheSnafu;
 use crate::QueryResultCache;
 use crate::Result;
 use async_trait::async_trait;
-use datafusion::logical_expr::LogicalPlan;
 use moka::future::Cache;
+use moka::Expiry;
 use snafu::ResultExt;
-use std::hash::DefaultHasher;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+struct CacheEntry {
+    result: CachedQueryResult,
+    ttl: Duration,
+}
+
+/// Expires each entry after the ttl it was cached with.
+struct EntryTtl;
+
+impl Expiry<u64, CacheEntry> for EntryTtl {
+    fn expire_after_create(
+        &self,
+        _key: &u64,
+        value: &CacheEntry,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+/// Whether `input_table`, as written in a query, may refer to `table_name`, i.e. `spice.public.t` and `public.t`
+/// refer to `t`. Errs on the side of invalidating too much, `other_schema.t` is also considered to refer to `t`.
+fn refers_to(input_table: &str, table_name: &str) -> bool {
+    input_table == table_name
+        || input_table
+            .strip_suffix(table_name)
+            .is_some_and(|qualifier| qualifier.ends_with('.'))
+}
 
 pub struct LruCache {
-    cache: Cache<u64, CachedQueryResult>,
+    cache: Cache<u64, CacheEntry>,
 }
 
 impl LruCache {
-    pub fn new(cache_max_size: u64, ttl: Duration) -> Self {
-        let cache: Cache<u64, CachedQueryResult> = Cache::builder()
-            .time_to_live(ttl)
-            .weigher(|_key, value: &CachedQueryResult| -> u32 {
+    pub fn new(cache_max_size: u64) -> Self {
+        let cache: Cache<u64, CacheEntry> = Cache::builder()
+            .expire_after(EntryTtl)
+            .weigher(|_key, value: &CacheEntry| -> u32 {
                 let val: usize = value
+                    .result
                     .records
                     .iter()
                     .map(arrow::array::RecordBatch::get_array_memory_size)
@@ -65,24 +92,28 @@ impl LruCache {
 
 #[async_trait]
 impl QueryResultCache for LruCache {
-    async fn get(&self, plan: &LogicalPlan) -> Result<Option<CachedQueryResult>> {
-        let key = key_for_logical_plan(plan);
+    async fn get(&self, key: u64) -> Result<Option<CachedQueryResult>> {
         match self.cache.get(&key).await {
-            Some(value) => Ok(Some(value)),
+            Some(value) => Ok(Some(value.result)),
             None => Ok(None),
         }
     }
 
-    async fn put(&self, plan: &LogicalPlan, result: CachedQueryResult) -> Result<()> {
-        let key = key_for_logical_plan(plan);
-        self.cache.insert(key, result).await;
+    async fn put(&self, key: u64, result: CachedQueryResult, ttl: Duration) -> Result<()> {
+        self.cache.insert(key, CacheEntry { result, ttl }).await;
         Ok(())
     }
 
     async fn invalidate_for_table(&self, table_name: &str) -> Result<()> {
         let name = table_name.to_string().to_lowercase();
         self.cache
-            .invalidate_entries_if(move |_key, value| value.input_tables.contains(&name))
+            .invalidate_entries_if(move |_key, value| {
+                value
+                    .result
+                    .input_tables
+                    .iter()
+                    .any(|input_table| refers_to(input_table, &name))
+            })
             .context(FailedToInvalidateCacheSnafu { table_name })?;
 
         Ok(())
@@ -96,9 +127,3 @@ impl QueryResultCache for LruCache {
         self.cache.entry_count()
     }
 }
-
-pub fn key_for_logical_plan(plan: &LogicalPlan) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    plan.hash(&mut hasher);
-    hasher.finish()
-}
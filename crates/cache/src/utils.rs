@@ -14,28 +14,42 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use arrow::array::RecordBatch;
 use datafusion::{
-    execution::SendableRecordBatchStream, logical_expr::LogicalPlan,
+    common::tree_node::{TreeNode, TreeNodeRecursion},
+    execution::SendableRecordBatchStream,
+    logical_expr::{
+        expr::{Exists, InSubquery},
+        Expr, LogicalPlan,
+    },
     physical_plan::stream::RecordBatchStreamAdapter,
 };
 
-use crate::{CachedQueryResult, QueryResultsCacheProvider};
+use crate::{CacheEntryTag, CachedQueryResult, QueryResultsCacheProvider};
 
 use async_stream::stream;
 
 use futures::StreamExt;
 
+/// Caches the results of `stream` for `ttl` once it has been consumed, `stream` is returned as is if `ttl` is zero.
+/// The cached result is identified by `tag`, see [`QueryResultsCacheProvider::new_entry_tag`].
 #[must_use]
 #[allow(clippy::implicit_hasher)]
 pub fn to_cached_record_batch_stream(
     cache_provider: Arc<QueryResultsCacheProvider>,
     mut stream: SendableRecordBatchStream,
     plan: LogicalPlan,
+    key_components: Vec<String>,
+    tag: CacheEntryTag,
     input_tables: Arc<HashSet<String>>,
+    ttl: Duration,
 ) -> SendableRecordBatchStream {
+    if ttl.is_zero() {
+        return stream;
+    }
+
     let schema = stream.schema();
     let schema_copy = Arc::clone(&schema);
 
@@ -60,9 +74,10 @@ pub fn to_cached_record_batch_stream(
                 records: Arc::new(records),
                 schema: schema_copy,
                 input_tables,
+                tag,
             };
 
-            if let Err(e) = cache_provider.put(&plan, cached_result).await {
+            if let Err(e) = cache_provider.put(&plan, &key_components, cached_result, ttl).await {
                 tracing::error!("Failed to cache query results: {e}");
             }
         }
@@ -74,20 +89,34 @@ pub fn to_cached_record_batch_stream(
     ))
 }
 
+/// Names (lowercased) of the tables scanned by `plan`, including those scanned by subqueries in its expressions.
 #[must_use]
 pub fn get_logical_plan_input_tables(plan: &LogicalPlan) -> HashSet<String> {
     let mut table_names: HashSet<String> = HashSet::new();
-    let mut plan_stack = vec![plan];
+    collect_input_tables(plan, &mut table_names);
+    table_names
+}
 
-    while let Some(current_plan) = plan_stack.pop() {
-        if let LogicalPlan::TableScan(source, ..) = current_plan {
-            table_names.insert(source.table_name.to_string().to_lowercase());
-        }
+fn collect_input_tables(plan: &LogicalPlan, table_names: &mut HashSet<String>) {
+    if let LogicalPlan::TableScan(source, ..) = plan {
+        table_names.insert(source.table_name.to_string().to_lowercase());
+    }
 
-        plan_stack.extend(current_plan.inputs());
+    for expr in plan.expressions() {
+        let _ = expr.apply(&mut |expr| {
+            if let Expr::ScalarSubquery(subquery)
+            | Expr::Exists(Exists { subquery, .. })
+            | Expr::InSubquery(InSubquery { subquery, .. }) = expr
+            {
+                collect_input_tables(&subquery.subquery, table_names);
+            }
+            Ok(TreeNodeRecursion::Continue)
+        });
     }
 
-    table_names
+    for input in plan.inputs() {
+        collect_input_tables(input, table_names);
+    }
 }
 
 #[must_use]
@@ -187,6 +216,21 @@ mod tests {
         assert_eq!(table_names, expected);
     }
 
+    #[tokio::test]
+    async fn test_collect_table_names_expression_subqueries() {
+        let sql = "SELECT * FROM customer WHERE id IN (SELECT customer_id FROM orders) \
+                   AND EXISTS (SELECT 1 FROM state) AND id > (SELECT min(id) FROM public.customer)";
+        let logical_plan = parse_sql_to_logical_plan(sql).await;
+
+        let table_names = get_logical_plan_input_tables(&logical_plan);
+
+        let expected: HashSet<String> = vec!["customer", "orders", "state", "public.customer"]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(table_names, expected);
+    }
+
     #[tokio::test]
     async fn test_collect_table_names_subquery() {
         let sql = "SELECT * FROM (SELECT * FROM state) AS s";
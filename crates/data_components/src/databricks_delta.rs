@@ -80,8 +80,22 @@ async fn get_delta_table(
     // Needed to be able to load the s3:// scheme
     deltalake::aws::register_handlers(None);
     deltalake::azure::register_handlers(None);
+    let storage_options = storage_options(&secret, &params)?;
     let table_uri = resolve_table_uri(table_reference, &secret, params).await?;
 
+    let delta_table = open_table_with_storage_options(table_uri, storage_options).await?;
+
+    Ok(Arc::new(delta_table) as Arc<dyn TableProvider>)
+}
+
+/// Builds the object store options for the table's storage location from the secret and dataset params.
+///
+/// `databricks_s3_endpoint` and `databricks_s3_path_style` point S3 storage at S3-compatible services such as
+/// MinIO or Cloudflare R2.
+fn storage_options(
+    secret: &Arc<Option<Secret>>,
+    params: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, Box<dyn Error + Send + Sync>> {
     let mut storage_options = HashMap::new();
     if let Some(secret) = secret.as_ref() {
         for (key, value) in secret.iter() {
@@ -93,9 +107,26 @@ async fn get_delta_table(
     };
     storage_options.insert(AWS_S3_ALLOW_UNSAFE_RENAME.to_string(), "true".to_string());
 
-    let delta_table = open_table_with_storage_options(table_uri, storage_options).await?;
+    if let Some(endpoint) = params.get("databricks_s3_endpoint") {
+        storage_options.insert("aws_endpoint".to_string(), endpoint.to_string());
+        if endpoint.starts_with("http://") {
+            storage_options.insert("aws_allow_http".to_string(), "true".to_string());
+        }
+    }
 
-    Ok(Arc::new(delta_table) as Arc<dyn TableProvider>)
+    if let Some(path_style) = params.get("databricks_s3_path_style") {
+        let path_style: bool = path_style.parse().map_err(|_| {
+            format!(
+                "databricks_s3_path_style value {path_style} is invalid, please use true or false"
+            )
+        })?;
+        storage_options.insert(
+            "aws_virtual_hosted_style_request".to_string(),
+            (!path_style).to_string(),
+        );
+    }
+
+    Ok(storage_options)
 }
 
 #[derive(Deserialize)]
@@ -128,7 +159,8 @@ pub async fn resolve_table_uri(
         table_name
     );
 
-    let client = reqwest::Client::new();
+    let tls = util::http::TlsOptions::from_params(&params)?;
+    let client = util::http::client_with_tls(&tls)?;
     let response = client.get(&url).bearer_auth(token).send().await?;
 
     if response.status().is_success() {
@@ -142,3 +174,43 @@ pub async fn resolve_table_uri(
         .into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_options_include_s3_endpoint_and_path_style() {
+        let params = HashMap::from([
+            (
+                "databricks_s3_endpoint".to_string(),
+                "http://localhost:9000".to_string(),
+            ),
+            ("databricks_s3_path_style".to_string(), "true".to_string()),
+        ]);
+
+        let options = storage_options(&Arc::new(None), &params).expect("valid storage options");
+        assert_eq!(
+            options.get("aws_endpoint").map(String::as_str),
+            Some("http://localhost:9000")
+        );
+        assert_eq!(
+            options.get("aws_allow_http").map(String::as_str),
+            Some("true")
+        );
+        assert_eq!(
+            options
+                .get("aws_virtual_hosted_style_request")
+                .map(String::as_str),
+            Some("false")
+        );
+    }
+
+    #[test]
+    fn test_storage_options_without_s3_endpoint() {
+        let options =
+            storage_options(&Arc::new(None), &HashMap::new()).expect("valid storage options");
+        assert!(!options.contains_key("aws_endpoint"));
+        assert!(!options.contains_key("aws_virtual_hosted_style_request"));
+    }
+}
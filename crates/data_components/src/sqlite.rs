@@ -26,13 +26,13 @@ use datafusion::{
 };
 use db_connection_pool::{
     dbconnection::{sqliteconn::SqliteConnection, DbConnection},
-    sqlitepool::SqliteConnectionPool,
+    sqlitepool::{JournalMode, SqliteConnectionPool},
     DbConnectionPool, Mode,
 };
 use rusqlite::{ToSql, Transaction};
 use snafu::prelude::*;
 use sql_provider_datafusion::{expr::Engine, SqlTable};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tokio_rusqlite::Connection;
 
 use crate::delete::DeletionTableProviderAdapter;
@@ -73,10 +73,20 @@ pub enum Error {
 
     #[snafu(display("There is a dangling reference to the Sqlite struct in TableProviderFactory.create. This is a bug."))]
     DanglingReferenceToSqlite,
+
+    #[snafu(display("Invalid sqlite_busy_timeout value: {source}"))]
+    InvalidBusyTimeout { source: fundu::ParseError },
+
+    #[snafu(display("Invalid sqlite_journal_mode value: {source}"))]
+    InvalidJournalMode {
+        source: db_connection_pool::sqlitepool::Error,
+    },
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_millis(5000);
+
 #[allow(clippy::module_name_repetitions)]
 pub struct SqliteTableFactory {
     db_path_param: String,
@@ -118,8 +128,25 @@ impl TableProviderFactory for SqliteTableFactory {
             .cloned()
             .unwrap_or(format!("{name}_sqlite.db"));
 
+        let busy_timeout = options
+            .get("sqlite_busy_timeout")
+            .map(String::as_str)
+            .map(fundu::parse_duration)
+            .transpose()
+            .context(InvalidBusyTimeoutSnafu)
+            .map_err(to_datafusion_error)?
+            .unwrap_or(DEFAULT_BUSY_TIMEOUT);
+
+        let journal_mode: JournalMode = options
+            .get("sqlite_journal_mode")
+            .map(|journal_mode| journal_mode.parse())
+            .transpose()
+            .context(InvalidJournalModeSnafu)
+            .map_err(to_datafusion_error)?
+            .unwrap_or_default();
+
         let pool: Arc<SqliteConnectionPool> = Arc::new(
-            SqliteConnectionPool::new(&db_path, mode)
+            SqliteConnectionPool::new(&db_path, mode, journal_mode, busy_timeout)
                 .await
                 .context(DbConnectionPoolSnafu)
                 .map_err(to_datafusion_error)?,
@@ -14,6 +14,8 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 use async_trait::async_trait;
+use std::{fmt::Display, str::FromStr, time::Duration};
+
 use snafu::{prelude::*, ResultExt};
 use tokio_rusqlite::{Connection, ToSql};
 
@@ -30,6 +32,52 @@ pub enum Error {
 
     #[snafu(display("No path provided for SQLite connection"))]
     NoPathError {},
+
+    #[snafu(display(
+        "Invalid SQLite journal mode: {mode}. Expected one of: DELETE, TRUNCATE, PERSIST, MEMORY, WAL, OFF"
+    ))]
+    InvalidJournalMode { mode: String },
+}
+
+/// The SQLite [journal mode](https://www.sqlite.org/pragma.html#pragma_journal_mode) for file connections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    #[default]
+    Wal,
+    Off,
+}
+
+impl FromStr for JournalMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "DELETE" => Ok(JournalMode::Delete),
+            "TRUNCATE" => Ok(JournalMode::Truncate),
+            "PERSIST" => Ok(JournalMode::Persist),
+            "MEMORY" => Ok(JournalMode::Memory),
+            "WAL" => Ok(JournalMode::Wal),
+            "OFF" => Ok(JournalMode::Off),
+            _ => InvalidJournalModeSnafu { mode: s }.fail(),
+        }
+    }
+}
+
+impl Display for JournalMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalMode::Delete => write!(f, "DELETE"),
+            JournalMode::Truncate => write!(f, "TRUNCATE"),
+            JournalMode::Persist => write!(f, "PERSIST"),
+            JournalMode::Memory => write!(f, "MEMORY"),
+            JournalMode::Wal => write!(f, "WAL"),
+            JournalMode::Off => write!(f, "OFF"),
+        }
+    }
 }
 
 pub struct SqliteConnectionPool {
@@ -40,6 +88,10 @@ pub struct SqliteConnectionPool {
 impl SqliteConnectionPool {
     /// Creates a new instance of `SqliteConnectionPool`.
     ///
+    /// The `busy_timeout` controls how long a connection waits on a locked database before returning
+    /// `SQLITE_BUSY`. The `journal_mode` is only applied to file connections, in-memory databases always
+    /// use the `MEMORY` journal mode.
+    ///
     /// NOTE: The `SqliteConnectionPool` currently does no connection pooling, it simply creates a new connection
     /// and clones it on each call to `connect()`.
     ///
@@ -47,7 +99,12 @@ impl SqliteConnectionPool {
     ///
     /// Returns an error if there is a problem creating the connection pool.
     #[allow(clippy::needless_pass_by_value)]
-    pub async fn new(path: &str, mode: Mode) -> Result<Self> {
+    pub async fn new(
+        path: &str,
+        mode: Mode,
+        journal_mode: JournalMode,
+        busy_timeout: Duration,
+    ) -> Result<Self> {
         let (conn, join_push_down) = match mode {
             Mode::Memory => (
                 Connection::open_in_memory()
@@ -55,14 +112,32 @@ impl SqliteConnectionPool {
                     .context(ConnectionPoolSnafu)?,
                 JoinPushDown::Disallow,
             ),
-            Mode::File => (
-                Connection::open(path.to_string())
+            Mode::File => {
+                let conn = Connection::open(path.to_string())
                     .await
-                    .context(ConnectionPoolSnafu)?,
-                JoinPushDown::AllowedFor(path.to_string()),
-            ),
+                    .context(ConnectionPoolSnafu)?;
+
+                let journal_mode = journal_mode.to_string();
+                conn.call(move |conn| {
+                    conn.pragma_update_and_check(None, "journal_mode", &journal_mode, |row| {
+                        row.get::<_, String>(0)
+                    })?;
+                    Ok(())
+                })
+                .await
+                .context(ConnectionPoolSnafu)?;
+
+                (conn, JoinPushDown::AllowedFor(path.to_string()))
+            }
         };
 
+        conn.call(move |conn| {
+            conn.busy_timeout(busy_timeout)?;
+            Ok(())
+        })
+        .await
+        .context(ConnectionPoolSnafu)?;
+
         Ok(SqliteConnectionPool {
             conn,
             join_push_down,
@@ -82,3 +157,82 @@ impl DbConnectionPool<Connection, &'static (dyn ToSql + Sync)> for SqliteConnect
         self.join_push_down.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("{name}_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_journal_mode_from_str() {
+        assert_eq!(
+            "wal".parse::<JournalMode>().expect("valid journal mode"),
+            JournalMode::Wal
+        );
+        assert_eq!(
+            "Truncate"
+                .parse::<JournalMode>()
+                .expect("valid journal mode"),
+            JournalMode::Truncate
+        );
+        assert!("journal".parse::<JournalMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_busy_timeout_waits_for_concurrent_writer() {
+        let path = temp_db_path("sqlite_busy_timeout");
+
+        let writer =
+            SqliteConnectionPool::new(&path, Mode::File, JournalMode::Wal, Duration::from_secs(5))
+                .await
+                .expect("writer pool created");
+        let other =
+            SqliteConnectionPool::new(&path, Mode::File, JournalMode::Wal, Duration::from_secs(5))
+                .await
+                .expect("other pool created");
+
+        writer
+            .conn
+            .call(|conn| {
+                conn.execute("CREATE TABLE t (id INTEGER)", [])?;
+                Ok(())
+            })
+            .await
+            .expect("table created");
+
+        // Hold the write lock for a while on the first connection.
+        let long_write = writer.conn.call(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("INSERT INTO t VALUES (1)", [])?;
+            std::thread::sleep(Duration::from_millis(500));
+            tx.commit()?;
+            Ok(())
+        });
+
+        let concurrent_write_and_read = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            other
+                .conn
+                .call(|conn| {
+                    conn.execute("INSERT INTO t VALUES (2)", [])?;
+                    let count: i64 =
+                        conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))?;
+                    Ok(count)
+                })
+                .await
+        };
+
+        let (long_write, count) = tokio::join!(long_write, concurrent_write_and_read);
+        long_write.expect("long write succeeded");
+        assert_eq!(count.expect("concurrent write waited for the lock"), 2);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(format!("{path}-wal"));
+        let _ = std::fs::remove_file(format!("{path}-shm"));
+    }
+}
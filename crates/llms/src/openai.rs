@@ -19,8 +19,8 @@ use async_openai::{
     config::OpenAIConfig,
     types::{
         ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-        ChatCompletionResponseFormat, ChatCompletionResponseFormatType,
-        CreateChatCompletionRequestArgs, EmbeddingInput as OpenAiEmbeddingInput,
+        ChatCompletionResponseFormat, ChatCompletionResponseFormatType, CompletionUsage,
+        CreateChatCompletionRequestArgs, EmbeddingInput as OpenAiEmbeddingInput, EmbeddingUsage,
     },
     Client,
 };
@@ -121,6 +121,7 @@ impl Nql for Openai {
             .map_err(|source| NqlError::FailedToRunModel { source })?;
 
         if let Some(usage) = response.usage {
+            record_completion_usage(&self.model, &usage);
             if usage.completion_tokens >= u32::from(MAX_COMPLETION_TOKENS) {
                 tracing::warn!(
                     "Completion response may have been cut off after {} tokens",
@@ -150,22 +151,34 @@ impl Embed for Openai {
             .boxed()
             .map_err(|source| EmbedError::FailedToPrepareInput { source })?;
 
-        let embedding: Vec<Vec<f32>> = self
+        let response = self
             .client
             .embeddings()
             .create(req)
             .await
             .boxed()
-            .map_err(|source| EmbedError::FailedToCreateEmbedding { source })?
-            .data
-            .iter()
-            .map(|d| d.embedding.clone())
-            .collect();
+            .map_err(|source| EmbedError::FailedToCreateEmbedding { source })?;
+        record_embedding_usage(&self.model, &response.usage);
+
+        let embedding: Vec<Vec<f32>> = response.data.into_iter().map(|d| d.embedding).collect();
 
         Ok(embedding)
     }
 }
 
+/// Tracks the tokens consumed per model, for cost monitoring.
+fn record_completion_usage(model: &str, usage: &CompletionUsage) {
+    let labels = [("model", model.to_string())];
+    metrics::counter!("llm_prompt_tokens_total", &labels).increment(u64::from(usage.prompt_tokens));
+    metrics::counter!("llm_completion_tokens_total", &labels)
+        .increment(u64::from(usage.completion_tokens));
+}
+
+fn record_embedding_usage(model: &str, usage: &EmbeddingUsage) {
+    metrics::counter!("embedding_tokens_total", "model" => model.to_string())
+        .increment(u64::from(usage.total_tokens));
+}
+
 fn to_openai_embedding_input(input: EmbeddingInput) -> OpenAiEmbeddingInput {
     match input {
         EmbeddingInput::String(s) => OpenAiEmbeddingInput::String(s),
@@ -174,3 +187,65 @@ fn to_openai_embedding_input(input: EmbeddingInput) -> OpenAiEmbeddingInput {
         EmbeddingInput::TokensBatch(tb) => OpenAiEmbeddingInput::ArrayOfIntegerArray(tb),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn test_token_usage_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            for _ in 0..2 {
+                record_completion_usage(
+                    "gpt-4o",
+                    &CompletionUsage {
+                        prompt_tokens: 120,
+                        completion_tokens: 30,
+                        total_tokens: 150,
+                    },
+                );
+            }
+            record_embedding_usage(
+                "text-embedding-3-small",
+                &EmbeddingUsage {
+                    prompt_tokens: 8,
+                    total_tokens: 8,
+                },
+            );
+        });
+
+        let counters = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .filter_map(|(key, _, _, value)| match value {
+                DebugValue::Counter(value) => {
+                    let model = key
+                        .key()
+                        .labels()
+                        .find(|label| label.key() == "model")
+                        .map(|label| label.value().to_string());
+                    Some((key.key().name().to_string(), model, value))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let counter = |name: &str, model: &str| {
+            counters
+                .iter()
+                .find(|(key, label, _)| key == name && label.as_deref() == Some(model))
+                .map(|(_, _, value)| *value)
+        };
+        assert_eq!(counter("llm_prompt_tokens_total", "gpt-4o"), Some(240));
+        assert_eq!(counter("llm_completion_tokens_total", "gpt-4o"), Some(60));
+        assert_eq!(
+            counter("embedding_tokens_total", "text-embedding-3-small"),
+            Some(8)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Measures refresh throughput (rows/sec) of full and append refreshes into the DuckDB and SQLite accelerators.
+//!
+//! A synthetic dataset is served from an in-memory federated table and loaded into an empty accelerator, once per
+//! combination of engine, refresh mode and row count. Row counts can be overridden with a comma separated
+//! `REFRESH_BENCH_ROWS` environment variable.
+//!
+//! Run with `cargo bench -p runtime --bench refresh --features duckdb,sqlite`.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use arrow::{
+    array::{AsArray, Int64Array, RecordBatch, StringArray},
+    datatypes::{DataType, Field, Int64Type, Schema, SchemaRef},
+};
+use data_components::arrow::write::MemTable;
+use datafusion::{execution::context::SessionContext, sql::TableReference};
+use runtime::{
+    accelerated_table::{refresh::Refresh, AcceleratedTable},
+    component::dataset::acceleration::{Acceleration, Engine, RefreshMode},
+    dataaccelerator::{self, create_accelerator_table},
+};
+
+const DEFAULT_ROW_COUNTS: [usize; 3] = [10_000, 100_000, 1_000_000];
+const BATCH_SIZE: usize = 8192;
+
+fn row_counts() -> Vec<usize> {
+    std::env::var("REFRESH_BENCH_ROWS")
+        .ok()
+        .map(|rows| {
+            rows.split(',')
+                .filter_map(|count| count.trim().parse().ok())
+                .collect::<Vec<_>>()
+        })
+        .filter(|counts| !counts.is_empty())
+        .unwrap_or_else(|| DEFAULT_ROW_COUNTS.to_vec())
+}
+
+fn synthetic_batches(schema: &SchemaRef, num_rows: usize) -> Result<Vec<RecordBatch>, String> {
+    (0..num_rows)
+        .step_by(BATCH_SIZE)
+        .map(|start| {
+            let end = (start + BATCH_SIZE).min(num_rows);
+            let ids = (start..end)
+                .map(|id| i64::try_from(id).unwrap_or(i64::MAX))
+                .collect::<Int64Array>();
+            let names = (start..end)
+                .map(|id| Some(format!("name_{id}")))
+                .collect::<StringArray>();
+            RecordBatch::try_new(Arc::clone(schema), vec![Arc::new(ids), Arc::new(names)])
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// Loads `num_rows` into a new, empty accelerator and returns the achieved rows/sec, once all rows are accelerated.
+async fn measure_refresh(
+    engine: Engine,
+    refresh_mode: RefreshMode,
+    num_rows: usize,
+) -> Result<f64, String> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+    let table_name = TableReference::bare(format!("refresh_bench_{engine}_{num_rows}"));
+
+    let federated = Arc::new(
+        MemTable::try_new(
+            Arc::clone(&schema),
+            vec![synthetic_batches(&schema, num_rows)?],
+        )
+        .map_err(|e| e.to_string())?,
+    );
+
+    let acceleration = Acceleration {
+        engine,
+        refresh_mode: refresh_mode.clone(),
+        ..Default::default()
+    };
+    let accelerator = create_accelerator_table(table_name.clone(), schema, &acceleration, None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ctx = SessionContext::new();
+    ctx.register_table("accelerator", Arc::clone(&accelerator))
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    let (_accelerated_table, is_ready) = AcceleratedTable::builder(
+        table_name,
+        federated,
+        accelerator,
+        Refresh::new(None, None, None, None, refresh_mode, None),
+    )
+    .build()
+    .await;
+    is_ready
+        .await
+        .map_err(|e| format!("Refresh did not complete: {e}"))?;
+    // Append refreshes are ready once the first batch is written, so wait for the rest of the rows
+    while accelerated_rows(&ctx).await? < num_rows {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    #[allow(clippy::cast_precision_loss)]
+    Ok(num_rows as f64 / elapsed)
+}
+
+async fn accelerated_rows(ctx: &SessionContext) -> Result<usize, String> {
+    let batches = ctx
+        .sql("SELECT COUNT(*) FROM accelerator")
+        .await
+        .map_err(|e| e.to_string())?
+        .collect()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let count = batches
+        .first()
+        .and_then(|batch| batch.column(0).as_primitive_opt::<Int64Type>())
+        .map_or(0, |counts| counts.value(0));
+    Ok(usize::try_from(count).unwrap_or_default())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    dataaccelerator::register_all().await;
+
+    println!(
+        "{:<8} {:<8} {:>12} {:>16}",
+        "engine", "mode", "rows", "rows/sec"
+    );
+    for engine in [Engine::DuckDB, Engine::Sqlite] {
+        for refresh_mode in [RefreshMode::Full, RefreshMode::Append] {
+            for num_rows in row_counts() {
+                let rows_per_sec = measure_refresh(engine, refresh_mode.clone(), num_rows).await?;
+                println!(
+                    "{:<8} {:<8} {num_rows:>12} {rows_per_sec:>16.0}",
+                    engine.to_string(),
+                    format!("{refresh_mode:?}").to_lowercase(),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -40,7 +40,7 @@ use snafu::prelude::*;
 use tokio::task::JoinHandle;
 use tokio::time::interval;
 
-use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::sync::{mpsc, oneshot, RwLock, Semaphore};
 
 use crate::dataconnector;
 use crate::datafusion::filter_converter::TimestampFilterConvert;
@@ -67,6 +67,16 @@ pub enum Error {
         source: tokio::sync::mpsc::error::SendError<()>,
     },
 
+    #[snafu(display("Failed to receive the result of the table refresh: {source}"))]
+    FailedToReceiveRefreshResult {
+        source: tokio::sync::oneshot::error::RecvError,
+    },
+
+    #[snafu(display("Failed to write refreshed data to the accelerator: {source}"))]
+    FailedToWriteData {
+        source: datafusion::error::DataFusionError,
+    },
+
     #[snafu(display("Manual refresh is not supported for `append` mode"))]
     ManualRefreshIsNotSupported {},
 
@@ -77,6 +87,25 @@ pub enum Error {
 
     #[snafu(display("{reason}"))]
     FailedToFindLatestTimestamp { reason: String },
+
+    #[snafu(display("Schema of the federated source changed incompatibly: {changes}"))]
+    SchemaDrift { changes: String },
+
+    #[snafu(display("Failed to drop columns added to the federated source: {source}"))]
+    FailedToDropAddedColumns { source: arrow::error::ArrowError },
+
+    #[snafu(display(
+        "Failed to conform columns whose type changed in the federated source: {source}"
+    ))]
+    FailedToCastChangedColumns { source: arrow::error::ArrowError },
+
+    #[snafu(display("Failed to deduplicate rows: {source}"))]
+    FailedToDeduplicateRows { source: arrow::error::ArrowError },
+
+    #[snafu(display("Failed to compute the rollup: {source}"))]
+    UnableToRollupData {
+        source: datafusion::error::DataFusionError,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -89,7 +118,7 @@ pub struct AcceleratedTable {
     dataset_name: TableReference,
     accelerator: Arc<dyn TableProvider>,
     federated: Arc<dyn TableProvider>,
-    refresh_trigger: Option<mpsc::Sender<()>>,
+    refresh_trigger: Option<mpsc::Sender<refresh::RefreshTrigger>>,
     handlers: Vec<JoinHandle<()>>,
     zero_results_action: ZeroResultsAction,
     refresh_params: Arc<RwLock<refresh::Refresh>>,
@@ -116,6 +145,14 @@ fn validate_refresh_data_window(
     }
 }
 
+fn validate_append_boundary_inclusive(refresh: &refresh::Refresh, dataset: &TableReference) {
+    if refresh.append_boundary_inclusive && !refresh.append_dedup_rows {
+        tracing::warn!(
+            "refresh_append_boundary_inclusive is enabled without refresh_append_dedup_rows, rows at the latest timestamp will be appended again on each refresh of dataset {dataset}"
+        );
+    }
+}
+
 pub struct Builder {
     dataset_name: TableReference,
     federated: Arc<dyn TableProvider>,
@@ -124,6 +161,8 @@ pub struct Builder {
     retention: Option<Retention>,
     zero_results_action: ZeroResultsAction,
     cache_provider: Option<Arc<QueryResultsCacheProvider>>,
+    refresh_write_semaphore: Option<Arc<Semaphore>>,
+    replication_lag: bool,
 }
 
 impl Builder {
@@ -141,6 +180,8 @@ impl Builder {
             retention: None,
             zero_results_action: ZeroResultsAction::default(),
             cache_provider: None,
+            refresh_write_semaphore: None,
+            replication_lag: false,
         }
     }
 
@@ -161,6 +202,22 @@ impl Builder {
         self.cache_provider = cache_provider;
         self
     }
+
+    /// Refreshes acquire a permit from this semaphore before writing to the accelerator.
+    pub fn refresh_write_semaphore(
+        &mut self,
+        refresh_write_semaphore: Option<Arc<Semaphore>>,
+    ) -> &mut Self {
+        self.refresh_write_semaphore = refresh_write_semaphore;
+        self
+    }
+
+    /// Records how far the accelerated data is behind the source after each refresh.
+    pub fn replication_lag(&mut self, replication_lag: bool) -> &mut Self {
+        self.replication_lag = replication_lag;
+        self
+    }
+
     pub async fn build(self) -> (AcceleratedTable, oneshot::Receiver<()>) {
         let mut refresh_trigger = None;
         let mut scheduled_refreshes_handle: Option<JoinHandle<()>> = None;
@@ -171,7 +228,7 @@ impl Builder {
                 if self.refresh.time_column.is_none() {
                     refresh::AccelerationRefreshMode::Append(None)
                 } else {
-                    let (trigger, receiver) = mpsc::channel::<()>(1);
+                    let (trigger, receiver) = mpsc::channel::<refresh::RefreshTrigger>(1);
                     refresh_trigger = Some(trigger.clone());
                     scheduled_refreshes_handle = AcceleratedTable::schedule_regular_refreshes(
                         self.refresh.check_interval,
@@ -182,7 +239,7 @@ impl Builder {
                 }
             }
             RefreshMode::Full => {
-                let (trigger, receiver) = mpsc::channel::<()>(1);
+                let (trigger, receiver) = mpsc::channel::<refresh::RefreshTrigger>(1);
                 refresh_trigger = Some(trigger.clone());
                 scheduled_refreshes_handle = AcceleratedTable::schedule_regular_refreshes(
                     self.refresh.check_interval,
@@ -194,6 +251,7 @@ impl Builder {
         };
 
         validate_refresh_data_window(&self.refresh, &self.dataset_name, &self.federated.schema());
+        validate_append_boundary_inclusive(&self.refresh, &self.dataset_name);
         let refresh_params = Arc::new(RwLock::new(self.refresh));
         let mut refresher = refresh::Refresher::new(
             self.dataset_name.clone(),
@@ -202,6 +260,8 @@ impl Builder {
             Arc::clone(&self.accelerator),
         );
         refresher.cache_provider(self.cache_provider.clone());
+        refresher.write_semaphore(self.refresh_write_semaphore.clone());
+        refresher.replication_lag(self.replication_lag);
         let refresher = Arc::new(refresher);
 
         let refresher_tokio = Arc::clone(&refresher);
@@ -259,11 +319,26 @@ impl AcceleratedTable {
     }
 
     pub async fn trigger_refresh(&self) -> Result<()> {
+        self.send_refresh_trigger(None).await
+    }
+
+    /// Triggers a refresh and waits for it to finish, returning the number of rows it loaded.
+    pub async fn refresh_and_wait(&self) -> Result<refresh::RefreshStats> {
+        let (completion, refresh_done) = oneshot::channel();
+        self.send_refresh_trigger(Some(completion)).await?;
+
+        refresh_done
+            .await
+            .context(FailedToReceiveRefreshResultSnafu)?
+    }
+
+    async fn send_refresh_trigger(&self, trigger: refresh::RefreshTrigger) -> Result<()> {
         match &self.refresh_trigger {
             Some(refresh_trigger) => {
                 refresh_trigger
-                    .send(())
+                    .send(trigger)
                     .await
+                    .map_err(|_| mpsc::error::SendError(()))
                     .context(FailedToTriggerRefreshSnafu)?;
             }
             None => {
@@ -291,7 +366,7 @@ impl AcceleratedTable {
 
     async fn schedule_regular_refreshes(
         refresh_check_interval: Option<Duration>,
-        refresh_trigger: mpsc::Sender<()>,
+        refresh_trigger: mpsc::Sender<refresh::RefreshTrigger>,
     ) -> Option<JoinHandle<()>> {
         if let Some(refresh_check_interval) = refresh_check_interval {
             let mut interval_timer = interval(refresh_check_interval);
@@ -300,14 +375,14 @@ impl AcceleratedTable {
                 loop {
                     interval_timer.tick().await;
                     // If sending fails, it means the receiver is dropped, and we should stop the task.
-                    if trigger.send(()).await.is_err() {
+                    if trigger.send(None).await.is_err() {
                         break;
                     }
                 }
             });
 
             return Some(handle);
-        } else if let Err(err) = refresh_trigger.send(()).await {
+        } else if let Err(err) = refresh_trigger.send(None).await {
             tracing::error!("Failed to trigger refresh: {err}");
         }
 
@@ -539,3 +614,58 @@ impl Retention {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+    use data_components::arrow::write::MemTable;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_refresh_and_wait_returns_after_refresh() {
+        let schema = Arc::new(Schema::new(vec![Field::new("name", DataType::Utf8, false)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(StringArray::from(vec!["a", "b", "c"]))],
+        )
+        .expect("data should be created");
+
+        let federated = Arc::new(
+            MemTable::try_new(Arc::clone(&schema), vec![vec![batch]])
+                .expect("mem table should be created"),
+        );
+        let accelerator =
+            Arc::new(MemTable::try_new(schema, vec![vec![]]).expect("mem table should be created"))
+                as Arc<dyn TableProvider>;
+
+        let (accelerated_table, _is_ready) = AcceleratedTable::builder(
+            TableReference::bare("test"),
+            federated,
+            Arc::clone(&accelerator),
+            refresh::Refresh::new(None, None, None, None, RefreshMode::Full, None),
+        )
+        .build()
+        .await;
+
+        let stats = accelerated_table
+            .refresh_and_wait()
+            .await
+            .expect("refresh should succeed");
+        assert_eq!(stats.num_rows, 3);
+
+        // The refreshed data is in the accelerator as soon as the call returns
+        let ctx = SessionContext::new();
+        let plan = accelerator
+            .scan(&ctx.state(), None, &[], None)
+            .await
+            .expect("Scan plan can be constructed");
+        let result = collect(plan, ctx.task_ctx())
+            .await
+            .expect("Query successful");
+        assert_eq!(3, result.into_iter().map(|b| b.num_rows()).sum::<usize>());
+    }
+}
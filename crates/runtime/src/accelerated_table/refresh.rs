@@ -1,7 +1,8 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::component::dataset::acceleration::RefreshMode;
+use crate::component::dataset::acceleration::{RefreshMode, Rollup};
 use crate::component::dataset::TimeFormat;
 use crate::datafusion::filter_converter::TimestampFilterConvert;
 use crate::datafusion::{schema, SPICE_RUNTIME_SCHEMA};
@@ -12,14 +13,18 @@ use crate::{
     status,
     timing::TimeMeasurement,
 };
-use arrow::array::TimestampNanosecondArray;
-use arrow::datatypes::DataType;
+use arrow::array::{BooleanArray, RecordBatch, TimestampNanosecondArray};
+use arrow::compute::{can_cast_types, cast_with_options, filter_record_batch, CastOptions};
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::row::{RowConverter, SortField};
 use async_stream::stream;
 use cache::QueryResultsCacheProvider;
 use datafusion::common::TableReference;
 use datafusion::error::DataFusionError;
 use datafusion::execution::config::SessionConfig;
-use datafusion::logical_expr::{cast, col, Expr, Operator};
+use datafusion::functions::expr_fn::to_timestamp_nanos;
+use datafusion::logical_expr::{cast, col, lit, Expr, Operator};
 use datafusion::physical_plan::{collect, ExecutionPlanProperties};
 use datafusion::prelude::DataFrame;
 use datafusion::{datasource::TableProvider, execution::context::SessionContext};
@@ -28,7 +33,8 @@ use futures::{stream::BoxStream, StreamExt};
 use snafu::prelude::*;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::oneshot;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
 
 #[derive(Clone, Debug)]
@@ -39,6 +45,10 @@ pub struct Refresh {
     pub(crate) sql: Option<String>,
     pub(crate) mode: RefreshMode,
     pub(crate) period: Option<Duration>,
+    pub(crate) initial_backfill_window: Option<Duration>,
+    pub(crate) append_dedup_rows: bool,
+    pub(crate) append_boundary_inclusive: bool,
+    pub(crate) rollup: Option<Rollup>,
 }
 
 impl Refresh {
@@ -59,6 +69,65 @@ impl Refresh {
             sql,
             mode,
             period,
+            initial_backfill_window: None,
+            append_dedup_rows: false,
+            append_boundary_inclusive: false,
+            rollup: None,
+        }
+    }
+
+    /// Bound the first `append` refresh into an empty accelerator to the most recent `window`.
+    ///
+    /// Applied only when the accelerator has no data yet. If a refresh `period` is also set, the smaller
+    /// of the two windows is used. Later refreshes append from the latest timestamp in the accelerator.
+    #[must_use]
+    pub fn initial_backfill_window(mut self, window: Option<Duration>) -> Self {
+        self.initial_backfill_window = window;
+        self
+    }
+
+    /// Drop identical rows within a single `append` refresh before they are inserted into the accelerator.
+    ///
+    /// Rows are compared by value across all columns (including nested struct/list columns). Rows already
+    /// present in the accelerator are only considered when `append_boundary_inclusive` is also set.
+    #[must_use]
+    pub fn append_dedup_rows(mut self, enabled: bool) -> Self {
+        self.append_dedup_rows = enabled;
+        self
+    }
+
+    /// Fetch rows with a timestamp equal to the latest one in the accelerator (`>=`) on `append` refreshes,
+    /// instead of only newer rows (`>`).
+    ///
+    /// Picks up rows that arrived at the source after a refresh, but share its latest timestamp. Rows at the
+    /// boundary that were already loaded are fetched again, so this requires `append_dedup_rows`, which then
+    /// skips rows already present in the accelerator at that timestamp. Without it they are inserted again.
+    #[must_use]
+    pub fn append_boundary_inclusive(mut self, inclusive: bool) -> Self {
+        self.append_boundary_inclusive = inclusive;
+        self
+    }
+
+    /// Store the rollup of the federated rows instead of the rows themselves.
+    ///
+    /// The accelerator must have the schema of the rolled-up rows, and the refresh mode must be `full`, see
+    /// [`crate::datafusion::rollup::rollup_schema`].
+    #[must_use]
+    pub fn rollup(mut self, rollup: Option<Rollup>) -> Self {
+        self.rollup = rollup;
+        self
+    }
+
+    /// The window of data to load when there is no latest timestamp to append from.
+    fn initial_load_window(&self) -> Option<Duration> {
+        let initial_backfill_window = match self.mode {
+            RefreshMode::Append => self.initial_backfill_window,
+            RefreshMode::Full => None,
+        };
+
+        match (self.period, initial_backfill_window) {
+            (Some(period), Some(window)) => Some(period.min(window)),
+            (period, window) => period.or(window),
         }
     }
 }
@@ -72,21 +141,46 @@ impl Default for Refresh {
             sql: None,
             mode: RefreshMode::Full,
             period: None,
+            initial_backfill_window: None,
+            append_dedup_rows: false,
+            append_boundary_inclusive: false,
+            rollup: None,
         }
     }
 }
 
+/// Outcome of a refresh that was waited on, see [`super::AcceleratedTable::refresh_and_wait`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshStats {
+    /// Number of rows written to the accelerator by the refresh.
+    pub num_rows: usize,
+}
+
+/// Notified with the outcome once the refresh it was sent with has finished.
+pub(crate) type RefreshCompletion = oneshot::Sender<super::Result<RefreshStats>>;
+
+/// Sent on the refresh trigger channel. Carries a completion sender when the caller waits on the refresh.
+pub(crate) type RefreshTrigger = Option<RefreshCompletion>;
+
+type RefreshUpdate = (
+    RefreshTrigger,
+    super::Result<(Option<SystemTime>, DataUpdate)>,
+);
+
 pub(crate) enum AccelerationRefreshMode {
-    Full(Receiver<()>),
-    Append(Option<Receiver<()>>),
+    Full(Receiver<RefreshTrigger>),
+    Append(Option<Receiver<RefreshTrigger>>),
 }
 
+#[derive(Clone)]
 pub struct Refresher {
     dataset_name: TableReference,
     federated: Arc<dyn TableProvider>,
     refresh: Arc<RwLock<Refresh>>,
     accelerator: Arc<dyn TableProvider>,
     cache_provider: Option<Arc<QueryResultsCacheProvider>>,
+    write_semaphore: Option<Arc<Semaphore>>,
+    replication_lag: bool,
 }
 
 impl Refresher {
@@ -102,6 +196,8 @@ impl Refresher {
             refresh,
             accelerator,
             cache_provider: None,
+            write_semaphore: None,
+            replication_lag: false,
         }
     }
 
@@ -113,6 +209,18 @@ impl Refresher {
         self
     }
 
+    /// Sets a semaphore shared with other refreshers writing to the same accelerator, limiting how many of them write at once.
+    pub fn write_semaphore(&mut self, write_semaphore: Option<Arc<Semaphore>>) -> &mut Self {
+        self.write_semaphore = write_semaphore;
+        self
+    }
+
+    /// Records the `replication_lag_seconds` gauge after each refresh, for datasets replicated to their source.
+    pub fn replication_lag(&mut self, replication_lag: bool) -> &mut Self {
+        self.replication_lag = replication_lag;
+        self
+    }
+
     pub(crate) async fn start(
         &self,
         acceleration_refresh_mode: AccelerationRefreshMode,
@@ -124,14 +232,19 @@ impl Refresher {
         let ctx = SessionContext::new();
 
         let mut ready_sender = Some(ready_sender);
+        let mut replication_lag_task: Option<JoinHandle<()>> = None;
 
         loop {
             let future_result = stream.next().await;
 
             match future_result {
-                Some(result) => {
-                    let Ok((start_time, data_update)) = result else {
-                        continue;
+                Some((completion, result)) => {
+                    let (start_time, data_update) = match result {
+                        Ok(update) => update,
+                        Err(e) => {
+                            notify_completion(completion, Err(e));
+                            continue;
+                        }
                     };
 
                     if data_update.data.is_empty()
@@ -154,9 +267,32 @@ impl Refresher {
                             }
                         }
                         self.notify_refresh_done(&mut ready_sender, status::ComponentStatus::Ready);
+                        notify_completion(completion, Ok(RefreshStats { num_rows: 0 }));
                         continue;
                     };
 
+                    let data_update =
+                        match conform_to_schema(&self.accelerator.schema(), data_update) {
+                            Ok(data_update) => data_update,
+                            Err(e) => {
+                                tracing::error!("Error refreshing data for {dataset_name}: {e}");
+                                self.mark_dataset_status(status::ComponentStatus::Error);
+                                notify_completion(completion, Err(e));
+                                continue;
+                            }
+                        };
+
+                    // Held until this update is written, so refreshes sharing the accelerator don't write concurrently
+                    let _write_permit = match &self.write_semaphore {
+                        Some(semaphore) => semaphore.acquire().await.ok(),
+                        None => None,
+                    };
+
+                    let num_rows = data_update
+                        .data
+                        .iter()
+                        .map(RecordBatch::num_rows)
+                        .sum::<usize>();
                     let overwrite = data_update.update_type == UpdateType::Overwrite;
                     match self
                         .accelerator
@@ -171,15 +307,12 @@ impl Refresher {
                             if let Err(e) = collect(plan, ctx.task_ctx()).await {
                                 tracing::error!("Error adding data for {dataset_name}: {e}");
                                 self.mark_dataset_status(status::ComponentStatus::Error);
+                                notify_completion(
+                                    completion,
+                                    Err(super::Error::FailedToWriteData { source: e }),
+                                );
                             } else {
                                 if let Some(start_time) = start_time {
-                                    let num_rows = data_update
-                                        .clone()
-                                        .data
-                                        .into_iter()
-                                        .map(|x| x.num_rows())
-                                        .sum::<usize>();
-
                                     let memory_size = util::human_readable_bytes(
                                         data_update
                                             .data
@@ -196,14 +329,15 @@ impl Refresher {
                                             tracing::info!("Loaded {num_rows} rows ({memory_size}) for dataset {dataset_name} in {elapse}.");
                                         }
                                     }
+                                }
 
-                                    if let Some(cache_provider) = &self.cache_provider {
-                                        if let Err(e) = cache_provider
-                                            .invalidate_for_table(&dataset_name.to_string())
-                                            .await
-                                        {
-                                            tracing::error!("Failed to invalidate cached results for dataset {}: {e}", &dataset_name.to_string());
-                                        }
+                                // Also invalidates cached results of queries joining this dataset with others
+                                if let Some(cache_provider) = &self.cache_provider {
+                                    if let Err(e) = cache_provider
+                                        .invalidate_for_table(&dataset_name.to_string())
+                                        .await
+                                    {
+                                        tracing::error!("Failed to invalidate cached results for dataset {}: {e}", &dataset_name.to_string());
                                     }
                                 }
 
@@ -211,11 +345,29 @@ impl Refresher {
                                     &mut ready_sender,
                                     status::ComponentStatus::Ready,
                                 );
+                                notify_completion(completion, Ok(RefreshStats { num_rows }));
+
+                                // Queries the source, so it runs off the refresh path and is skipped while the
+                                // previous one is still running
+                                if self.replication_lag
+                                    && replication_lag_task
+                                        .as_ref()
+                                        .map_or(true, JoinHandle::is_finished)
+                                {
+                                    let refresher = self.clone();
+                                    replication_lag_task = Some(tokio::spawn(async move {
+                                        refresher.record_replication_lag().await;
+                                    }));
+                                }
                             };
                         }
                         Err(e) => {
                             self.mark_dataset_status(status::ComponentStatus::Error);
                             tracing::error!("Error adding data for {dataset_name}: {e}");
+                            notify_completion(
+                                completion,
+                                Err(super::Error::FailedToWriteData { source: e }),
+                            );
                         }
                     }
                 }
@@ -227,7 +379,7 @@ impl Refresher {
     async fn stream_updates(
         &self,
         acceleration_refresh_mode: AccelerationRefreshMode,
-    ) -> BoxStream<'_, super::Result<(Option<SystemTime>, DataUpdate)>> {
+    ) -> BoxStream<'_, RefreshUpdate> {
         let time_column = self.refresh.read().await.time_column.clone();
 
         match acceleration_refresh_mode {
@@ -244,18 +396,19 @@ impl Refresher {
         }
     }
 
-    fn get_append_stream(
-        &self,
-    ) -> impl Stream<Item = super::Result<(Option<SystemTime>, DataUpdate)>> {
+    fn get_append_stream(&self) -> impl Stream<Item = RefreshUpdate> {
         let ctx = self.get_refresh_df_context();
         let federated = Arc::clone(&self.federated);
         let dataset_name = self.dataset_name.clone();
 
         stream! {
-            let plan = federated
-                .scan(&ctx.state(), None, &[], None)
-                .await
-                .context(super::UnableToScanTableProviderSnafu {})?;
+            let plan = match federated.scan(&ctx.state(), None, &[], None).await {
+                Ok(plan) => plan,
+                Err(e) => {
+                    yield (None, Err(super::Error::UnableToScanTableProvider { source: e }));
+                    return;
+                }
+            };
 
             if plan.output_partitioning().partition_count() > 1 {
                 tracing::error!(
@@ -266,21 +419,25 @@ impl Refresher {
 
             let schema = federated.schema();
 
-            let mut stream = plan
-                .execute(0, ctx.task_ctx())
-                .context(super::UnableToScanTableProviderSnafu {})?;
+            let mut stream = match plan.execute(0, ctx.task_ctx()) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    yield (None, Err(super::Error::UnableToScanTableProvider { source: e }));
+                    return;
+                }
+            };
             loop {
                 match stream.next().await {
                     Some(Ok(batch)) => {
-                        yield Ok((None, DataUpdate {
+                        yield (None, Ok((None, DataUpdate {
                             schema: Arc::clone(&schema),
                             data: vec![batch],
                             update_type: UpdateType::Append,
-                        }));
+                        })));
                     }
                     Some(Err(e)) => {
                         tracing::error!("Error reading data for dataset {dataset_name}: {e}");
-                        yield Err(super::Error::UnableToScanTableProvider { source: e });
+                        yield (None, Err(super::Error::UnableToScanTableProvider { source: e }));
                     }
                     None => break,
                 }
@@ -290,21 +447,21 @@ impl Refresher {
 
     fn get_full_update_stream(
         &self,
-        receiver: Receiver<()>,
-    ) -> impl Stream<Item = super::Result<(Option<SystemTime>, DataUpdate)>> + '_ {
+        receiver: Receiver<RefreshTrigger>,
+    ) -> impl Stream<Item = RefreshUpdate> + '_ {
         let dataset_name = self.dataset_name.clone();
 
         let mut refresh_stream = ReceiverStream::new(receiver);
         stream! {
-            while refresh_stream.next().await.is_some() {
+            while let Some(completion) = refresh_stream.next().await {
                 let timer = TimeMeasurement::new(
                     "load_dataset_duration_ms",
                     vec![("dataset", dataset_name.to_string())],
                 );
                 let start = SystemTime::now();
                 match self.get_full_or_incremental_append_update(None).await {
-                    Ok(data) => yield Ok((Some(start), data)),
-                    Err(e) => yield Err(e),
+                    Ok(data) => yield (completion, Ok((Some(start), data))),
+                    Err(e) => yield (completion, Err(e)),
                 };
                 drop(timer);
             }
@@ -313,13 +470,13 @@ impl Refresher {
 
     fn get_incremental_append_update_stream(
         &self,
-        receiver: Receiver<()>,
-    ) -> impl Stream<Item = super::Result<(Option<SystemTime>, DataUpdate)>> + '_ {
+        receiver: Receiver<RefreshTrigger>,
+    ) -> impl Stream<Item = RefreshUpdate> + '_ {
         let dataset_name = self.dataset_name.clone();
 
         let mut refresh_stream = ReceiverStream::new(receiver);
         stream! {
-            while refresh_stream.next().await.is_some() {
+            while let Some(completion) = refresh_stream.next().await {
                 let timer = TimeMeasurement::new(
                     "append_dataset_duration_ms",
                     vec![("dataset", dataset_name.to_string())],
@@ -328,13 +485,14 @@ impl Refresher {
                     Ok(timestamp) => {
                         let start = SystemTime::now();
                         match self.get_full_or_incremental_append_update(timestamp).await {
-                            Ok(data) => yield Ok((Some(start), data)),
-                            Err(e) => yield Err(e),
+                            Ok(data) => yield (completion, Ok((Some(start), data))),
+                            Err(e) => yield (completion, Err(e)),
                         }
 
                     }
                     Err(e) => {
                         tracing::error!("No latest timestamp is found: {e}");
+                        yield (completion, Err(e));
                     }
                 }
                 drop(timer);
@@ -342,8 +500,17 @@ impl Refresher {
         }
     }
 
-    #[allow(clippy::cast_sign_loss)]
     async fn get_latest_timestamp(&self) -> super::Result<Option<u128>> {
+        self.get_latest_timestamp_of(Arc::clone(&self.accelerator))
+            .await
+    }
+
+    /// Latest value of the time column in `table`, in nanoseconds since the epoch.
+    #[allow(clippy::cast_sign_loss)]
+    async fn get_latest_timestamp_of(
+        &self,
+        table: Arc<dyn TableProvider>,
+    ) -> super::Result<Option<u128>> {
         let ctx = self.get_refresh_df_context();
         let refresh = self.refresh.read().await;
 
@@ -355,7 +522,12 @@ impl Refresher {
                     reason: "Failed to get latest timestamp due to time column not specified",
                 })?;
         let df = self
-            .get_df(ctx, &column)
+            .get_df(
+                ctx,
+                Arc::clone(&table),
+                &column,
+                refresh.time_format.as_ref(),
+            )
             .context(super::UnableToScanTableProviderSnafu)?;
         let result = &df
             .collect()
@@ -379,7 +551,7 @@ impl Refresher {
 
         let mut value = array.value(0) as u128;
 
-        let schema = &self.accelerator.schema();
+        let schema = &table.schema();
         let Ok(accelerated_field) = schema.field_with_name(&column) else {
             return Err(super::Error::FailedToFindLatestTimestamp {
                 reason: "Failed to get latest timestamp due to time column not specified"
@@ -411,18 +583,68 @@ impl Refresher {
     }
 
     #[allow(clippy::needless_pass_by_value)]
-    fn get_df(&self, ctx: SessionContext, column: &str) -> Result<DataFrame, DataFusionError> {
-        let expr = cast(
-            col(column),
-            DataType::Timestamp(arrow::datatypes::TimeUnit::Nanosecond, None),
-        )
+    fn get_df(
+        &self,
+        ctx: SessionContext,
+        table: Arc<dyn TableProvider>,
+        column: &str,
+        time_format: Option<&TimeFormat>,
+    ) -> Result<DataFrame, DataFusionError> {
+        let is_string_column = table
+            .schema()
+            .field_with_name(column)
+            .is_ok_and(|field| matches!(field.data_type(), DataType::Utf8 | DataType::LargeUtf8));
+        let expr = match time_format {
+            Some(TimeFormat::Custom(pattern)) if is_string_column => {
+                to_timestamp_nanos(vec![col(column), lit(pattern.as_str())])
+            }
+            _ => cast(
+                col(column),
+                DataType::Timestamp(arrow::datatypes::TimeUnit::Nanosecond, None),
+            ),
+        }
         .alias("a");
-        ctx.read_table(Arc::clone(&self.accelerator))?
+        ctx.read_table(table)?
             .select(vec![expr])?
             .sort(vec![col("a").sort(false, false)])?
             .limit(0, Some(1))
     }
 
+    /// How far the accelerated data is behind the source, as the difference of their latest time column values.
+    /// `None` if there is no time column, or either table has no rows to compare.
+    async fn get_replication_lag(&self) -> super::Result<Option<Duration>> {
+        if self.refresh.read().await.time_column.is_none() {
+            return Ok(None);
+        }
+
+        let Some(source) = self
+            .get_latest_timestamp_of(Arc::clone(&self.federated))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(replicated) = self.get_latest_timestamp().await? else {
+            return Ok(None);
+        };
+
+        let lag_nanos = u64::try_from(source.saturating_sub(replicated)).unwrap_or(u64::MAX);
+        Ok(Some(Duration::from_nanos(lag_nanos)))
+    }
+
+    async fn record_replication_lag(&self) {
+        match self.get_replication_lag().await {
+            Ok(Some(lag)) => {
+                metrics::gauge!("replication_lag_seconds", "dataset" => self.dataset_name.to_string())
+                    .set(lag.as_secs_f64());
+            }
+            Ok(None) => {}
+            Err(e) => tracing::debug!(
+                "Unable to determine the replication lag of {}: {e}",
+                self.dataset_name
+            ),
+        }
+    }
+
     pub async fn get_full_or_incremental_append_update(
         &self,
         overwrite_timestamp_in_nano: Option<u128>,
@@ -439,17 +661,24 @@ impl Refresher {
         status::update_dataset(&dataset_name, status::ComponentStatus::Refreshing);
         let refresh = refresh.clone();
         let mut filters = vec![];
+        let mut boundary_filter = None;
         if let Some(converter) = filter_converter.as_ref() {
             if let Some(timestamp) = overwrite_timestamp_in_nano {
-                filters.push(converter.convert(timestamp, Operator::Gt));
-            } else if let Some(period) = refresh.period {
+                if refresh.append_boundary_inclusive {
+                    let filter = converter.convert(timestamp, Operator::Eq);
+                    boundary_filter = Some(filter);
+                    filters.push(converter.convert(timestamp, Operator::GtEq));
+                } else {
+                    filters.push(converter.convert(timestamp, Operator::Gt));
+                }
+            } else if let Some(window) = refresh.initial_load_window() {
                 filters.push(
-                    converter.convert(get_timestamp(SystemTime::now() - period), Operator::Gt),
+                    converter.convert(get_timestamp(SystemTime::now() - window), Operator::Gt),
                 );
             }
         };
 
-        match self.get_data_update(filters).await {
+        match self.get_data_update(filters, boundary_filter).await {
             Ok(data) => Ok(data),
             Err(e) => {
                 tracing::error!("Failed to load data for dataset {dataset_name}: {e}");
@@ -458,12 +687,33 @@ impl Refresher {
         }
     }
 
-    async fn get_data_update(&self, filters: Vec<Expr>) -> super::Result<DataUpdate> {
+    /// Loads the data to refresh from the federated table.
+    ///
+    /// When deduplicating appended rows, rows in the accelerator matching `boundary_filter` are treated as
+    /// already loaded.
+    async fn get_data_update(
+        &self,
+        filters: Vec<Expr>,
+        boundary_filter: Option<Expr>,
+    ) -> super::Result<DataUpdate> {
         let refresh = self.refresh.read().await;
         let update_type = match refresh.mode {
             RefreshMode::Full => UpdateType::Overwrite,
             RefreshMode::Append => UpdateType::Append,
         };
+        if let Some(rollup) = &refresh.rollup {
+            let data = self
+                .get_rollup_data(rollup, filters)
+                .await
+                .context(super::UnableToRollupDataSnafu)?;
+            return Ok(DataUpdate {
+                schema: self.accelerator.schema(),
+                data,
+                update_type,
+            });
+        }
+
+        let dedup_append_rows = refresh.append_dedup_rows && update_type == UpdateType::Append;
         let mut ctx = self.get_refresh_df_context();
         let federated = Arc::clone(&self.federated);
         let dataset_name = self.dataset_name.clone();
@@ -480,11 +730,66 @@ impl Refresher {
             data: data.1,
             update_type,
         }) {
+            Ok(data) if dedup_append_rows => {
+                let existing = match boundary_filter {
+                    Some(filter) => {
+                        get_data(
+                            &mut self.get_refresh_df_context(),
+                            dataset_name.clone(),
+                            Arc::clone(&self.accelerator),
+                            None,
+                            vec![filter],
+                        )
+                        .await
+                        .context(super::UnableToGetDataFromConnectorSnafu)?
+                        .1
+                    }
+                    None => vec![],
+                };
+
+                let num_rows: usize = data.data.iter().map(RecordBatch::num_rows).sum();
+                let deduped = dedup_rows(&data.schema, &existing, data.data)
+                    .context(super::FailedToDeduplicateRowsSnafu)?;
+                let num_deduped: usize = deduped.iter().map(RecordBatch::num_rows).sum();
+                if num_deduped < num_rows {
+                    tracing::debug!(
+                        "Dropped {} duplicate rows for dataset {dataset_name}",
+                        num_rows - num_deduped
+                    );
+                }
+
+                Ok(DataUpdate {
+                    schema: data.schema,
+                    data: deduped,
+                    update_type: data.update_type,
+                })
+            }
             Ok(data) => Ok(data),
             Err(e) => Err(super::Error::UnableToGetDataFromConnector { source: e }),
         }
     }
 
+    /// Aggregates the federated rows matching `filters` into the rows of the rollup.
+    async fn get_rollup_data(
+        &self,
+        rollup: &Rollup,
+        filters: Vec<Expr>,
+    ) -> Result<Vec<RecordBatch>, DataFusionError> {
+        let ctx = self.get_refresh_df_context();
+        let mut rows = ctx.read_table(Arc::clone(&self.federated))?;
+        for filter in filters {
+            rows = rows.filter(filter)?;
+        }
+
+        // Aggregate only the filtered rows, by querying them under the dataset's name
+        ctx.deregister_table(self.dataset_name.clone())?;
+        ctx.register_table(self.dataset_name.clone(), rows.into_view())?;
+        ctx.sql(&rollup.to_sql(&self.dataset_name))
+            .await?
+            .collect()
+            .await
+    }
+
     fn get_refresh_df_context(&self) -> SessionContext {
         let ctx = SessionContext::new_with_config_rt(
             SessionConfig::new().set_bool(
@@ -532,7 +837,11 @@ impl Refresher {
         let column = refresh.time_column.as_deref().unwrap_or_default();
         let field = schema.column_with_name(column).map(|(_, f)| f).cloned();
 
-        TimestampFilterConvert::create(field, refresh.time_column.clone(), refresh.time_format)
+        TimestampFilterConvert::create(
+            field,
+            refresh.time_column.clone(),
+            refresh.time_format.clone(),
+        )
     }
 
     fn notify_refresh_done(
@@ -551,6 +860,158 @@ impl Refresher {
     }
 }
 
+fn notify_completion(completion: RefreshTrigger, result: super::Result<RefreshStats>) {
+    if let Some(completion) = completion {
+        // The caller may have stopped waiting for the refresh to finish
+        completion.send(result).ok();
+    }
+}
+
+/// Checks data from the federated source against the accelerator's schema before it is written.
+///
+/// Nullable columns added to the source are dropped from the update, and columns whose type changed are cast back to
+/// the accelerated type. Any other change, i.e. a removed column, a type that can't be cast to the accelerated type or
+/// a new non-nullable column, fails naming the changed columns.
+fn conform_to_schema(expected: &SchemaRef, update: DataUpdate) -> super::Result<DataUpdate> {
+    let incoming = Arc::clone(&update.schema);
+    let mut changes = vec![];
+    let mut indices = Vec::with_capacity(expected.fields().len());
+
+    for field in expected.fields() {
+        let Ok(index) = incoming.index_of(field.name()) else {
+            changes.push(format!("column `{}` was removed", field.name()));
+            continue;
+        };
+
+        let incoming_type = incoming.field(index).data_type();
+        if incoming_type != field.data_type() && !can_cast_types(incoming_type, field.data_type()) {
+            changes.push(format!(
+                "column `{}` changed type from {} to {incoming_type}",
+                field.name(),
+                field.data_type()
+            ));
+        }
+        indices.push(index);
+    }
+
+    for field in incoming.fields() {
+        if expected.field_with_name(field.name()).is_err() && !field.is_nullable() {
+            changes.push(format!("non-nullable column `{}` was added", field.name()));
+        }
+    }
+
+    if !changes.is_empty() {
+        return super::SchemaDriftSnafu {
+            changes: changes.join(", "),
+        }
+        .fail();
+    }
+
+    let types_changed = expected
+        .fields()
+        .iter()
+        .zip(&indices)
+        .any(|(field, index)| incoming.field(*index).data_type() != field.data_type());
+
+    if !types_changed {
+        if indices.len() == incoming.fields().len() {
+            return Ok(update);
+        }
+
+        let schema = Arc::new(
+            incoming
+                .project(&indices)
+                .context(super::FailedToDropAddedColumnsSnafu)?,
+        );
+        let data = update
+            .data
+            .iter()
+            .map(|batch| batch.project(&indices))
+            .collect::<Result<Vec<_>, _>>()
+            .context(super::FailedToDropAddedColumnsSnafu)?;
+
+        return Ok(DataUpdate {
+            schema,
+            data,
+            update_type: update.update_type,
+        });
+    }
+
+    // A safe cast would write values that don't fit the accelerated type as NULLs, so casts must not lose values
+    let cast_options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    let mut data = Vec::with_capacity(update.data.len());
+    for batch in &update.data {
+        let mut columns = Vec::with_capacity(indices.len());
+        for (field, index) in expected.fields().iter().zip(&indices) {
+            let column = cast_with_options(batch.column(*index), field.data_type(), &cast_options)
+                .map_err(|e| {
+                    super::SchemaDriftSnafu {
+                        changes: format!(
+                            "column `{}` changed type from {} to {} and has values that can't be cast: {e}",
+                            field.name(),
+                            field.data_type(),
+                            incoming.field(*index).data_type()
+                        ),
+                    }
+                    .build()
+                })?;
+            columns.push(column);
+        }
+        data.push(
+            RecordBatch::try_new(Arc::clone(expected), columns)
+                .context(super::FailedToCastChangedColumnsSnafu)?,
+        );
+    }
+
+    Ok(DataUpdate {
+        schema: Arc::clone(expected),
+        data,
+        update_type: update.update_type,
+    })
+}
+
+/// Removes rows that are identical across all columns, keeping the first occurrence.
+///
+/// Rows that are also in `existing` are removed entirely.
+fn dedup_rows(
+    schema: &SchemaRef,
+    existing: &[RecordBatch],
+    batches: Vec<RecordBatch>,
+) -> Result<Vec<RecordBatch>, ArrowError> {
+    let converter = RowConverter::new(
+        schema
+            .fields()
+            .iter()
+            .map(|field| SortField::new(field.data_type().clone()))
+            .collect(),
+    )?;
+
+    let mut seen = HashSet::new();
+    for batch in existing {
+        let rows = converter.convert_columns(batch.columns())?;
+        seen.extend(rows.iter().map(arrow::row::Row::owned));
+    }
+
+    let mut deduped = Vec::with_capacity(batches.len());
+    for batch in batches {
+        let rows = converter.convert_columns(batch.columns())?;
+        let keep: BooleanArray = rows
+            .iter()
+            .map(|row| Some(seen.insert(row.owned())))
+            .collect();
+
+        let batch = filter_record_batch(&batch, &keep)?;
+        if batch.num_rows() > 0 {
+            deduped.push(batch);
+        }
+    }
+
+    Ok(deduped)
+}
+
 pub(crate) fn get_timestamp(time: SystemTime) -> u128 {
     time.duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -562,7 +1023,7 @@ mod tests {
     use std::thread::sleep;
 
     use arrow::{
-        array::{ArrowNativeTypeOp, RecordBatch, StringArray, UInt64Array},
+        array::{ArrayRef, ArrowNativeTypeOp, RecordBatch, StringArray, StructArray, UInt64Array},
         datatypes::{DataType, Schema},
     };
     use data_components::arrow::write::MemTable;
@@ -609,7 +1070,7 @@ mod tests {
             Arc::clone(&accelerator),
         );
 
-        let (trigger, receiver) = mpsc::channel::<()>(1);
+        let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
         let (ready_sender, is_ready) = oneshot::channel::<()>();
         let acceleration_refresh_mode = AccelerationRefreshMode::Full(receiver);
         let refresh_handle = tokio::spawn(async move {
@@ -619,7 +1080,7 @@ mod tests {
         });
 
         trigger
-            .send(())
+            .send(None)
             .await
             .expect("trigger sent correctly to refresh");
 
@@ -678,6 +1139,118 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_refresh_invalidates_cached_join_results() {
+        let schema = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+            "time_in_string",
+            DataType::Utf8,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(StringArray::from(vec!["1970-01-01"]))],
+        )
+        .expect("data should be created");
+        let federated = Arc::new(
+            MemTable::try_new(Arc::clone(&schema), vec![vec![batch.clone()]])
+                .expect("mem table should be created"),
+        );
+        let accelerator = Arc::new(
+            MemTable::try_new(Arc::clone(&schema), vec![vec![batch.clone()]])
+                .expect("mem table should be created"),
+        ) as Arc<dyn TableProvider>;
+
+        let cache_provider = Arc::new(
+            QueryResultsCacheProvider::new(&spicepod::component::runtime::ResultsCache {
+                item_ttl: Some("60s".to_string()),
+                ..Default::default()
+            })
+            .expect("cache provider should be created"),
+        );
+        let ctx = SessionContext::new();
+        ctx.register_table("test", Arc::clone(&accelerator))
+            .expect("table should be registered");
+        ctx.register_table(
+            "other",
+            Arc::new(
+                MemTable::try_new(Arc::clone(&schema), vec![vec![batch.clone()]])
+                    .expect("mem table should be created"),
+            ),
+        )
+        .expect("table should be registered");
+
+        let mut join_plans = vec![];
+        for sql in [
+            "SELECT o.time_in_string FROM other o JOIN test t ON o.time_in_string = t.time_in_string",
+            "SELECT * FROM other WHERE time_in_string IN (SELECT time_in_string FROM public.test)",
+        ] {
+            let plan = ctx
+                .state()
+                .create_logical_plan(sql)
+                .await
+                .expect("query planned");
+            cache_provider
+                .put(
+                    &plan,
+                    &[],
+                    cache::CachedQueryResult {
+                        records: Arc::new(vec![batch.clone()]),
+                        schema: Arc::clone(&schema),
+                        input_tables: Arc::new(cache::get_logical_plan_input_tables(&plan)),
+                        tag: cache_provider.new_entry_tag(&plan, &[]),
+                    },
+                    Duration::from_secs(60),
+                )
+                .await
+                .expect("results cached");
+            assert!(cache_provider
+                .get(&plan, &[])
+                .await
+                .expect("cache accessible")
+                .is_some());
+            join_plans.push(plan);
+        }
+
+        let refresh = Refresh::new(None, None, None, None, RefreshMode::Full, None);
+        let mut refresher = Refresher::new(
+            TableReference::bare("test"),
+            federated,
+            Arc::new(RwLock::new(refresh)),
+            accelerator,
+        );
+        refresher.cache_provider(Some(Arc::clone(&cache_provider)));
+
+        let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+        let (ready_sender, is_ready) = oneshot::channel::<()>();
+        let refresh_handle = tokio::spawn(async move {
+            refresher
+                .start(AccelerationRefreshMode::Full(receiver), ready_sender)
+                .await;
+        });
+        trigger
+            .send(None)
+            .await
+            .expect("trigger sent correctly to refresh");
+        timeout(Duration::from_secs(2), async move {
+            is_ready.await.expect("data is received");
+        })
+        .await
+        .expect("finish before the timeout");
+
+        for plan in &join_plans {
+            assert!(
+                cache_provider
+                    .get(plan, &[])
+                    .await
+                    .expect("cache accessible")
+                    .is_none(),
+                "cached results joining the refreshed dataset are invalidated"
+            );
+        }
+
+        drop(refresh_handle);
+    }
+
     #[tokio::test]
     async fn test_refresh_status_change_to_ready() {
         fn wait_until_ready_status(
@@ -788,7 +1361,7 @@ mod tests {
                 Arc::clone(&accelerator),
             );
 
-            let (trigger, receiver) = mpsc::channel::<()>(1);
+            let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
             let (ready_sender, is_ready) = oneshot::channel::<()>();
             let acceleration_refresh_mode = AccelerationRefreshMode::Append(Some(receiver));
             let refresh_handle = tokio::spawn(async move {
@@ -797,7 +1370,7 @@ mod tests {
                     .await;
             });
             trigger
-                .send(())
+                .send(None)
                 .await
                 .expect("trigger sent correctly to refresh");
 
@@ -887,6 +1460,136 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_refresh_append_boundary_inclusive() {
+        async fn test(inclusive: bool, dedup: bool, expected: Vec<&str>, message: &str) {
+            let schema = Arc::new(Schema::new(vec![
+                arrow::datatypes::Field::new("time_in_string", DataType::Utf8, false),
+                arrow::datatypes::Field::new("value", DataType::Utf8, false),
+            ]));
+            let batch = |rows: Vec<(&str, &str)>| {
+                let (times, values): (Vec<_>, Vec<_>) = rows.into_iter().unzip();
+                RecordBatch::try_new(
+                    Arc::clone(&schema),
+                    vec![
+                        Arc::new(StringArray::from(times)),
+                        Arc::new(StringArray::from(values)),
+                    ],
+                )
+                .expect("data should be created")
+            };
+
+            let federated = Arc::new(
+                MemTable::try_new(
+                    Arc::clone(&schema),
+                    vec![vec![batch(vec![
+                        ("2012-12-01T11:11:15Z", "b"),
+                        ("2012-12-01T11:11:15Z", "c"),
+                        ("2012-12-01T11:11:16Z", "d"),
+                    ])]],
+                )
+                .expect("mem table should be created"),
+            );
+            let accelerator = Arc::new(
+                MemTable::try_new(
+                    Arc::clone(&schema),
+                    vec![vec![batch(vec![
+                        ("2012-12-01T11:11:11Z", "a"),
+                        ("2012-12-01T11:11:15Z", "b"),
+                    ])]],
+                )
+                .expect("mem table should be created"),
+            ) as Arc<dyn TableProvider>;
+
+            let refresh = Refresh::new(
+                Some("time_in_string".to_string()),
+                None,
+                None,
+                None,
+                RefreshMode::Append,
+                None,
+            )
+            .append_dedup_rows(dedup)
+            .append_boundary_inclusive(inclusive);
+
+            let refresher = Refresher::new(
+                TableReference::bare("test"),
+                federated,
+                Arc::new(RwLock::new(refresh)),
+                Arc::clone(&accelerator),
+            );
+
+            let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+            let (ready_sender, _is_ready) = oneshot::channel::<()>();
+            let acceleration_refresh_mode = AccelerationRefreshMode::Append(Some(receiver));
+            let refresh_handle = tokio::spawn(async move {
+                refresher
+                    .start(acceleration_refresh_mode, ready_sender)
+                    .await;
+            });
+
+            let (completion, refresh_done) = oneshot::channel();
+            trigger
+                .send(Some(completion))
+                .await
+                .expect("trigger sent correctly to refresh");
+            timeout(Duration::from_secs(2), refresh_done)
+                .await
+                .expect("finish before the timeout")
+                .expect("refresh result is received")
+                .expect("refresh should succeed");
+
+            let ctx = SessionContext::new();
+            let plan = accelerator
+                .scan(&ctx.state(), None, &[], None)
+                .await
+                .expect("Scan plan can be constructed");
+            let result = collect(plan, ctx.task_ctx())
+                .await
+                .expect("Query successful");
+
+            let mut values = result
+                .iter()
+                .flat_map(|batch| {
+                    batch
+                        .column(1)
+                        .as_any()
+                        .downcast_ref::<StringArray>()
+                        .expect("value is a StringArray")
+                        .iter()
+                        .map(|value| value.expect("value is not null").to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            values.sort();
+            assert_eq!(values, expected, "{message}");
+
+            drop(refresh_handle);
+        }
+
+        test(
+            false,
+            true,
+            vec!["a", "b", "d"],
+            "should skip rows at the latest timestamp when exclusive",
+        )
+        .await;
+        test(
+            true,
+            true,
+            vec!["a", "b", "c", "d"],
+            "should pick up new rows at the latest timestamp without duplicating loaded ones",
+        )
+        .await;
+        test(
+            true,
+            false,
+            vec!["a", "b", "b", "c", "d"],
+            "should append loaded rows at the latest timestamp again without dedup",
+        )
+        .await;
+    }
+
     #[allow(clippy::too_many_lines)]
     #[tokio::test]
     async fn test_refresh_append_batch_for_timestamp() {
@@ -936,7 +1639,7 @@ mod tests {
                 Arc::clone(&accelerator),
             );
 
-            let (trigger, receiver) = mpsc::channel::<()>(1);
+            let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
             let (ready_sender, is_ready) = oneshot::channel::<()>();
             let acceleration_refresh_mode = AccelerationRefreshMode::Append(Some(receiver));
             let refresh_handle = tokio::spawn(async move {
@@ -945,7 +1648,7 @@ mod tests {
                     .await;
             });
             trigger
-                .send(())
+                .send(None)
                 .await
                 .expect("trigger sent correctly to refresh");
 
@@ -1014,4 +1717,737 @@ mod tests {
         )
         .await;
     }
+
+    #[allow(clippy::too_many_lines)]
+    #[tokio::test]
+    async fn test_refresh_append_dedup_rows_with_struct_column() {
+        async fn test(append_dedup_rows: bool, expected_size: usize, message: &str) {
+            let struct_field = Arc::new(arrow::datatypes::Field::new("a", DataType::Utf8, false));
+            let schema = Arc::new(Schema::new(vec![
+                arrow::datatypes::Field::new("time", DataType::UInt64, false),
+                arrow::datatypes::Field::new(
+                    "payload",
+                    DataType::Struct(vec![Arc::clone(&struct_field)].into()),
+                    false,
+                ),
+            ]));
+
+            let make_batch = |time: Vec<u64>, payload: Vec<&str>| {
+                let payload = StructArray::from(vec![(
+                    Arc::clone(&struct_field),
+                    Arc::new(StringArray::from(payload)) as ArrayRef,
+                )]);
+                RecordBatch::try_new(
+                    Arc::clone(&schema),
+                    vec![Arc::new(UInt64Array::from(time)), Arc::new(payload)],
+                )
+                .expect("data should be created")
+            };
+
+            // The same rows arrive twice within one refresh
+            let incoming = make_batch(vec![5, 6], vec!["e", "f"]);
+            let federated = Arc::new(
+                MemTable::try_new(Arc::clone(&schema), vec![vec![incoming.clone(), incoming]])
+                    .expect("mem table should be created"),
+            );
+
+            let existing = make_batch(vec![1, 2, 3, 4], vec!["a", "b", "c", "d"]);
+            let accelerator = Arc::new(
+                MemTable::try_new(Arc::clone(&schema), vec![vec![existing]])
+                    .expect("mem table should be created"),
+            ) as Arc<dyn TableProvider>;
+
+            let refresh = Refresh::new(
+                Some("time".to_string()),
+                Some(TimeFormat::UnixSeconds),
+                None,
+                None,
+                RefreshMode::Append,
+                None,
+            )
+            .append_dedup_rows(append_dedup_rows);
+
+            let refresher = Refresher::new(
+                TableReference::bare("test"),
+                federated,
+                Arc::new(RwLock::new(refresh)),
+                Arc::clone(&accelerator),
+            );
+
+            let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+            let (ready_sender, is_ready) = oneshot::channel::<()>();
+            let acceleration_refresh_mode = AccelerationRefreshMode::Append(Some(receiver));
+            let refresh_handle = tokio::spawn(async move {
+                refresher
+                    .start(acceleration_refresh_mode, ready_sender)
+                    .await;
+            });
+            trigger
+                .send(None)
+                .await
+                .expect("trigger sent correctly to refresh");
+
+            timeout(Duration::from_secs(2), async move {
+                is_ready.await.expect("data is received");
+            })
+            .await
+            .expect("finish before the timeout");
+
+            let ctx = SessionContext::new();
+            let plan = accelerator
+                .scan(&ctx.state(), None, &[], None)
+                .await
+                .expect("Scan plan can be constructed");
+
+            let result = collect(plan, ctx.task_ctx())
+                .await
+                .expect("Query successful");
+
+            assert_eq!(
+                expected_size,
+                result.into_iter().map(|f| f.num_rows()).sum::<usize>(),
+                "{message}"
+            );
+
+            drop(refresh_handle);
+        }
+
+        test(
+            false,
+            8,
+            "should insert duplicated incoming rows without row dedup",
+        )
+        .await;
+        test(
+            true,
+            6,
+            "should drop duplicated incoming rows with row dedup",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_refresh_append_initial_backfill_window() {
+        async fn test(
+            source_data: Vec<u64>,
+            existing_data: Vec<u64>,
+            expected_size: usize,
+            message: &str,
+        ) {
+            let schema = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+                "time",
+                DataType::UInt64,
+                false,
+            )]));
+
+            let batch = RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![Arc::new(UInt64Array::from(source_data))],
+            )
+            .expect("data should be created");
+            let federated = Arc::new(
+                MemTable::try_new(Arc::clone(&schema), vec![vec![batch]])
+                    .expect("mem table should be created"),
+            );
+
+            let batch = RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![Arc::new(UInt64Array::from(existing_data))],
+            )
+            .expect("data should be created");
+            let accelerator = Arc::new(
+                MemTable::try_new(schema, vec![vec![batch]]).expect("mem table should be created"),
+            ) as Arc<dyn TableProvider>;
+
+            let refresh = Refresh::new(
+                Some("time".to_string()),
+                Some(TimeFormat::UnixSeconds),
+                None,
+                None,
+                RefreshMode::Append,
+                None,
+            )
+            .initial_backfill_window(Some(Duration::from_secs(7 * DAY)));
+
+            let refresher = Refresher::new(
+                TableReference::bare("test"),
+                federated,
+                Arc::new(RwLock::new(refresh)),
+                Arc::clone(&accelerator),
+            );
+
+            let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+            let (ready_sender, is_ready) = oneshot::channel::<()>();
+            let acceleration_refresh_mode = AccelerationRefreshMode::Append(Some(receiver));
+            let refresh_handle = tokio::spawn(async move {
+                refresher
+                    .start(acceleration_refresh_mode, ready_sender)
+                    .await;
+            });
+            trigger
+                .send(None)
+                .await
+                .expect("trigger sent correctly to refresh");
+
+            timeout(Duration::from_secs(2), async move {
+                is_ready.await.expect("data is received");
+            })
+            .await
+            .expect("finish before the timeout");
+
+            let ctx = SessionContext::new();
+            let plan = accelerator
+                .scan(&ctx.state(), None, &[], None)
+                .await
+                .expect("Scan plan can be constructed");
+
+            let result = collect(plan, ctx.task_ctx())
+                .await
+                .expect("Query successful");
+
+            assert_eq!(
+                expected_size,
+                result.into_iter().map(|f| f.num_rows()).sum::<usize>(),
+                "{message}"
+            );
+
+            drop(refresh_handle);
+        }
+
+        const DAY: u64 = 24 * 60 * 60;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time is after the epoch")
+            .as_secs();
+
+        test(
+            vec![now - 30 * DAY, now - 2 * DAY, now - 60 * 60],
+            vec![],
+            2,
+            "should only backfill data within the initial window into an empty accelerator",
+        )
+        .await;
+        test(
+            vec![now - 30 * DAY, now - 20 * DAY],
+            vec![now - 25 * DAY],
+            2,
+            "should append from the latest timestamp once the accelerator has data",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_refresh_writes_serialized_for_shared_accelerator_file() {
+        let acceleration = crate::component::dataset::acceleration::Acceleration {
+            mode: crate::component::dataset::acceleration::Mode::File,
+            engine: crate::component::dataset::acceleration::Engine::DuckDB,
+            params: std::collections::HashMap::from([(
+                "duckdb_file".to_string(),
+                "accelerated_duckdb.db".to_string(),
+            )]),
+            ..Default::default()
+        };
+
+        let semaphore_a = crate::dataaccelerator::refresh_write_semaphore(
+            &TableReference::bare("dataset_a"),
+            &acceleration,
+        )
+        .await
+        .expect("file accelerators have a write semaphore");
+        let semaphore_b = crate::dataaccelerator::refresh_write_semaphore(
+            &TableReference::bare("dataset_b"),
+            &acceleration,
+        )
+        .await
+        .expect("file accelerators have a write semaphore");
+        assert!(
+            Arc::ptr_eq(&semaphore_a, &semaphore_b),
+            "datasets on the same accelerator file should share a write semaphore"
+        );
+
+        let schema = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+            "time_in_string",
+            DataType::Utf8,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(StringArray::from(vec!["a", "b", "c"]))],
+        )
+        .expect("data should be created");
+        let federated = Arc::new(
+            MemTable::try_new(Arc::clone(&schema), vec![vec![batch]])
+                .expect("mem table should be created"),
+        );
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(StringArray::from(Vec::<&str>::new()))],
+        )
+        .expect("data should be created");
+        let accelerator = Arc::new(
+            MemTable::try_new(schema, vec![vec![batch]]).expect("mem table should be created"),
+        ) as Arc<dyn TableProvider>;
+
+        let refresh = Refresh::new(None, None, None, None, RefreshMode::Full, None);
+        // Named like the other tests' datasets, so only one dataset status metric is ever reported
+        let mut refresher = Refresher::new(
+            TableReference::bare("test"),
+            federated,
+            Arc::new(RwLock::new(refresh)),
+            Arc::clone(&accelerator),
+        );
+        refresher.write_semaphore(Some(semaphore_b));
+
+        // dataset_a is writing to the shared file
+        let permit = semaphore_a
+            .acquire()
+            .await
+            .expect("semaphore should not be closed");
+
+        let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+        let (ready_sender, mut is_ready) = oneshot::channel::<()>();
+        let acceleration_refresh_mode = AccelerationRefreshMode::Full(receiver);
+        let refresh_handle = tokio::spawn(async move {
+            refresher
+                .start(acceleration_refresh_mode, ready_sender)
+                .await;
+        });
+        trigger
+            .send(None)
+            .await
+            .expect("trigger sent correctly to refresh");
+
+        assert!(
+            timeout(Duration::from_millis(500), &mut is_ready)
+                .await
+                .is_err(),
+            "refresh should wait for the other write to the same file to finish"
+        );
+
+        drop(permit);
+
+        timeout(Duration::from_secs(2), async move {
+            is_ready.await.expect("data is received");
+        })
+        .await
+        .expect("finish before the timeout");
+
+        let ctx = SessionContext::new();
+        let plan = accelerator
+            .scan(&ctx.state(), None, &[], None)
+            .await
+            .expect("Scan plan can be constructed");
+        let result = collect(plan, ctx.task_ctx())
+            .await
+            .expect("Query successful");
+        assert_eq!(3, result.into_iter().map(|f| f.num_rows()).sum::<usize>());
+
+        drop(refresh_handle);
+    }
+
+    /// A federated source whose table can be replaced between refreshes, e.g. to change its schema.
+    struct SwappableTable {
+        table: std::sync::RwLock<Arc<MemTable>>,
+    }
+
+    impl SwappableTable {
+        fn current(&self) -> Arc<MemTable> {
+            Arc::clone(&self.table.read().expect("lock should not be poisoned"))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TableProvider for SwappableTable {
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.current().schema()
+        }
+
+        fn table_type(&self) -> datafusion::datasource::TableType {
+            datafusion::datasource::TableType::Base
+        }
+
+        async fn scan(
+            &self,
+            state: &datafusion::execution::context::SessionState,
+            projection: Option<&Vec<usize>>,
+            filters: &[Expr],
+            limit: Option<usize>,
+        ) -> datafusion::error::Result<Arc<dyn datafusion::physical_plan::ExecutionPlan>> {
+            self.current().scan(state, projection, filters, limit).await
+        }
+    }
+
+    #[test]
+    fn test_conform_to_schema_casts_changed_types() {
+        let expected = Arc::new(Schema::new(vec![
+            arrow::datatypes::Field::new("count", DataType::Int64, false),
+            arrow::datatypes::Field::new("code", DataType::Utf8, true),
+        ]));
+        let incoming = Arc::new(Schema::new(vec![
+            arrow::datatypes::Field::new("count", DataType::Int32, false),
+            arrow::datatypes::Field::new("code", DataType::Int64, true),
+            arrow::datatypes::Field::new("extra", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&incoming),
+            vec![
+                Arc::new(arrow::array::Int32Array::from(vec![1, 2])),
+                Arc::new(arrow::array::Int64Array::from(vec![Some(404), None])),
+                Arc::new(StringArray::from(vec![Some("x"), None])),
+            ],
+        )
+        .expect("data should be created");
+
+        let update = conform_to_schema(
+            &expected,
+            DataUpdate {
+                schema: incoming,
+                data: vec![batch],
+                update_type: UpdateType::Overwrite,
+            },
+        )
+        .expect("castable type changes should be conformed");
+
+        assert_eq!(update.schema, expected);
+        let batch = &update.data[0];
+        assert_eq!(batch.schema(), expected);
+        let counts = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow::array::Int64Array>()
+            .expect("count is cast to Int64");
+        assert_eq!(counts.values(), &[1, 2]);
+        let codes = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .expect("code is cast to Utf8");
+        assert_eq!(codes.iter().collect::<Vec<_>>(), vec![Some("404"), None]);
+    }
+
+    #[test]
+    fn test_conform_to_schema_rejects_values_that_cant_be_cast() {
+        let conform = |expected: DataType, incoming: DataType, column: ArrayRef| {
+            let expected = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+                "value", expected, true,
+            )]));
+            let incoming = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+                "value", incoming, true,
+            )]));
+            let batch = RecordBatch::try_new(Arc::clone(&incoming), vec![column])
+                .expect("data should be created");
+            conform_to_schema(
+                &expected,
+                DataUpdate {
+                    schema: incoming,
+                    data: vec![batch],
+                    update_type: UpdateType::Append,
+                },
+            )
+        };
+
+        let overflowing = conform(
+            DataType::Int32,
+            DataType::Int64,
+            Arc::new(arrow::array::Int64Array::from(vec![1, i64::MAX])),
+        );
+        let unparseable = conform(
+            DataType::Int64,
+            DataType::Utf8,
+            Arc::new(StringArray::from(vec!["1", "not a number"])),
+        );
+        for result in [overflowing, unparseable] {
+            match result {
+                Err(crate::accelerated_table::Error::SchemaDrift { changes }) => {
+                    assert!(changes.contains("column `value`"), "{changes}");
+                }
+                Err(e) => panic!("expected schema drift, got {e}"),
+                Ok(_) => panic!("values that can't be cast must not be written as NULLs"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_detects_schema_drift() {
+        fn source(fields: Vec<arrow::datatypes::Field>, columns: Vec<ArrayRef>) -> Arc<MemTable> {
+            let schema = Arc::new(Schema::new(fields));
+            let batch =
+                RecordBatch::try_new(Arc::clone(&schema), columns).expect("data should be created");
+            Arc::new(
+                MemTable::try_new(schema, vec![vec![batch]]).expect("mem table should be created"),
+            )
+        }
+
+        async fn refresh(
+            trigger: &mpsc::Sender<RefreshTrigger>,
+        ) -> crate::accelerated_table::Result<RefreshStats> {
+            let (completion, refresh_done) = oneshot::channel();
+            trigger
+                .send(Some(completion))
+                .await
+                .expect("trigger sent correctly to refresh");
+            timeout(Duration::from_secs(2), refresh_done)
+                .await
+                .expect("finish before the timeout")
+                .expect("refresh result is received")
+        }
+
+        let id = arrow::datatypes::Field::new("id", DataType::UInt64, false);
+        let name = arrow::datatypes::Field::new("name", DataType::Utf8, true);
+        let extra = arrow::datatypes::Field::new("extra", DataType::Utf8, true);
+        let ids = Arc::new(UInt64Array::from(vec![1, 2, 3])) as ArrayRef;
+        let names = Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef;
+
+        let initial = source(
+            vec![id.clone(), name.clone()],
+            vec![Arc::clone(&ids), Arc::clone(&names)],
+        );
+        let accelerator = Arc::new(
+            MemTable::try_new(initial.schema(), vec![]).expect("mem table should be created"),
+        ) as Arc<dyn TableProvider>;
+        let federated = Arc::new(SwappableTable {
+            table: std::sync::RwLock::new(initial),
+        });
+
+        let refresh = Refresh::new(None, None, None, None, RefreshMode::Full, None);
+        let refresher = Refresher::new(
+            TableReference::bare("test"),
+            Arc::clone(&federated) as Arc<dyn TableProvider>,
+            Arc::new(RwLock::new(refresh)),
+            Arc::clone(&accelerator),
+        );
+
+        let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+        let (ready_sender, _is_ready) = oneshot::channel::<()>();
+        let acceleration_refresh_mode = AccelerationRefreshMode::Full(receiver);
+        let refresh_handle = tokio::spawn(async move {
+            refresher
+                .start(acceleration_refresh_mode, ready_sender)
+                .await;
+        });
+
+        let stats = refresh(&trigger).await.expect("refresh should succeed");
+        assert_eq!(stats.num_rows, 3);
+
+        // A nullable column added to the source is dropped from the refreshed data
+        *federated
+            .table
+            .write()
+            .expect("lock should not be poisoned") = source(
+            vec![id.clone(), name, extra],
+            vec![
+                Arc::clone(&ids),
+                names,
+                Arc::new(StringArray::from(vec![None, Some("x"), None])),
+            ],
+        );
+        let stats = refresh(&trigger)
+            .await
+            .expect("refresh should tolerate added nullable columns");
+        assert_eq!(stats.num_rows, 3);
+
+        // Dropping a column the accelerator has fails the refresh, naming the column
+        *federated
+            .table
+            .write()
+            .expect("lock should not be poisoned") = source(vec![id], vec![ids]);
+        let err = refresh(&trigger)
+            .await
+            .expect_err("refresh should fail on a dropped column");
+        assert!(
+            err.to_string().contains("column `name` was removed"),
+            "unexpected error: {err}"
+        );
+
+        // The previously refreshed data is left in place
+        let ctx = SessionContext::new();
+        let plan = accelerator
+            .scan(&ctx.state(), None, &[], None)
+            .await
+            .expect("Scan plan can be constructed");
+        let result = collect(plan, ctx.task_ctx())
+            .await
+            .expect("Query successful");
+        assert_eq!(3, result.into_iter().map(|f| f.num_rows()).sum::<usize>());
+        assert_eq!(accelerator.schema().fields().len(), 2);
+
+        drop(refresh_handle);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rollup_stores_aggregated_rows() {
+        use arrow::array::AsArray;
+
+        let schema = Arc::new(Schema::new(vec![
+            arrow::datatypes::Field::new("region", DataType::Utf8, false),
+            arrow::datatypes::Field::new("amount", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from(vec!["eu", "us", "eu"])),
+                Arc::new(arrow::array::Int64Array::from(vec![10, 20, 30])),
+            ],
+        )
+        .expect("data should be created");
+        let federated = Arc::new(
+            MemTable::try_new(schema, vec![vec![batch]]).expect("mem table should be created"),
+        ) as Arc<dyn TableProvider>;
+
+        let dataset = TableReference::bare("orders");
+        let rollup = Rollup {
+            group_by: vec!["region".to_string()],
+            aggregations: vec![
+                "sum(amount) AS total_amount".to_string(),
+                "count(*) AS orders".to_string(),
+            ],
+        };
+        let rollup_schema = crate::datafusion::rollup::rollup_schema(
+            &dataset,
+            &rollup,
+            &crate::component::dataset::acceleration::Acceleration::default(),
+            crate::component::dataset::Mode::Read,
+            Arc::clone(&federated),
+        )
+        .await
+        .expect("valid rollup");
+        let accelerator = Arc::new(
+            MemTable::try_new(rollup_schema, vec![]).expect("mem table should be created"),
+        ) as Arc<dyn TableProvider>;
+
+        let refresh =
+            Refresh::new(None, None, None, None, RefreshMode::Full, None).rollup(Some(rollup));
+        let refresher = Refresher::new(
+            dataset,
+            federated,
+            Arc::new(RwLock::new(refresh)),
+            Arc::clone(&accelerator),
+        );
+
+        let (trigger, receiver) = mpsc::channel::<RefreshTrigger>(1);
+        let (ready_sender, is_ready) = oneshot::channel::<()>();
+        let acceleration_refresh_mode = AccelerationRefreshMode::Full(receiver);
+        let refresh_handle = tokio::spawn(async move {
+            refresher
+                .start(acceleration_refresh_mode, ready_sender)
+                .await;
+        });
+
+        trigger
+            .send(None)
+            .await
+            .expect("trigger sent correctly to refresh");
+        timeout(Duration::from_secs(2), async move {
+            is_ready.await.expect("data is received");
+        })
+        .await
+        .expect("finish before the timeout");
+
+        let ctx = SessionContext::new();
+        ctx.register_table("accelerated_orders", accelerator)
+            .expect("table registered");
+        let result = ctx
+            .sql("SELECT region, total_amount, orders FROM accelerated_orders ORDER BY region")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect("Query successful");
+
+        let rows = result
+            .iter()
+            .flat_map(|batch| {
+                let regions = batch.column(0).as_string::<i32>();
+                let totals = batch
+                    .column(1)
+                    .as_primitive::<arrow::datatypes::Int64Type>();
+                let orders = batch
+                    .column(2)
+                    .as_primitive::<arrow::datatypes::Int64Type>();
+                (0..batch.num_rows())
+                    .map(|i| {
+                        (
+                            regions.value(i).to_string(),
+                            totals.value(i),
+                            orders.value(i),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![("eu".to_string(), 40, 2), ("us".to_string(), 20, 1)],
+            "only the rolled-up rows are stored"
+        );
+
+        drop(refresh_handle);
+    }
+
+    #[test]
+    fn test_replication_lag_gauge() {
+        fn table(seconds: Vec<i64>) -> Arc<dyn TableProvider> {
+            let schema = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+                "time",
+                DataType::Int64,
+                false,
+            )]));
+            let batch = RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![Arc::new(arrow::array::Int64Array::from(seconds))],
+            )
+            .expect("data should be created");
+            Arc::new(
+                MemTable::try_new(schema, vec![vec![batch]]).expect("mem table should be created"),
+            )
+        }
+
+        let refresh = Refresh::new(
+            Some("time".to_string()),
+            Some(TimeFormat::UnixSeconds),
+            None,
+            None,
+            RefreshMode::Append,
+            None,
+        );
+        let refresher = Refresher::new(
+            TableReference::bare("replicated"),
+            table(vec![100, 200, 1000]),
+            Arc::new(RwLock::new(refresh)),
+            table(vec![100, 200]),
+        );
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        // A current thread runtime keeps the refresher on this thread, where the local recorder is installed
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime should be created");
+        metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(refresher.record_replication_lag());
+        });
+
+        let lag = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .find_map(|(key, _, _, value)| match value {
+                DebugValue::Gauge(lag)
+                    if key.key().name() == "replication_lag_seconds"
+                        && key.key().labels().any(|label| {
+                            label.key() == "dataset" && label.value() == "replicated"
+                        }) =>
+                {
+                    Some(lag.into_inner())
+                }
+                _ => None,
+            })
+            .expect("replication lag is recorded");
+        assert!((lag - 800.0).abs() < f64::EPSILON, "lag: {lag}");
+    }
 }
@@ -14,6 +14,7 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use chrono::format::{Item, StrftimeItems};
 use datafusion::sql::TableReference;
 use snafu::prelude::*;
 use spicepod::component::{dataset as spicepod_dataset, params::Params};
@@ -46,12 +47,28 @@ impl From<spicepod_dataset::Mode> for Mode {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum TimeFormat {
     #[default]
     UnixSeconds,
     UnixMillis,
     ISO8601,
+    /// A chrono `strftime` pattern used to parse string time columns.
+    Custom(String),
+}
+
+impl TimeFormat {
+    /// Returns `false` for a custom pattern that chrono is unable to parse timestamps with.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        match self {
+            TimeFormat::Custom(pattern) => {
+                !pattern.trim().is_empty()
+                    && !StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error))
+            }
+            _ => true,
+        }
+    }
 }
 
 impl From<spicepod_dataset::TimeFormat> for TimeFormat {
@@ -60,6 +77,7 @@ impl From<spicepod_dataset::TimeFormat> for TimeFormat {
             spicepod_dataset::TimeFormat::UnixSeconds => TimeFormat::UnixSeconds,
             spicepod_dataset::TimeFormat::UnixMillis => TimeFormat::UnixMillis,
             spicepod_dataset::TimeFormat::ISO8601 => TimeFormat::ISO8601,
+            spicepod_dataset::TimeFormat::Custom(pattern) => TimeFormat::Custom(pattern),
         }
     }
 }
@@ -85,6 +103,7 @@ pub struct Dataset {
     pub time_column: Option<String>,
     pub time_format: Option<TimeFormat>,
     pub acceleration: Option<acceleration::Acceleration>,
+    pub results_cache_ttl: Option<String>,
 }
 
 impl TryFrom<spicepod_dataset::Dataset> for Dataset {
@@ -98,6 +117,17 @@ impl TryFrom<spicepod_dataset::Dataset> for Dataset {
 
         let table_reference = Dataset::parse_table_reference(&dataset.name)?;
 
+        let time_format = dataset.time_format.map(TimeFormat::from);
+        if let Some(time_format @ TimeFormat::Custom(pattern)) = &time_format {
+            ensure!(
+                time_format.is_valid(),
+                crate::InvalidTimeFormatSnafu {
+                    dataset: dataset.name.clone(),
+                    pattern: pattern.clone(),
+                }
+            );
+        }
+
         Ok(Dataset {
             from: dataset.from,
             name: table_reference,
@@ -114,8 +144,9 @@ impl TryFrom<spicepod_dataset::Dataset> for Dataset {
                 .unwrap_or(Dataset::have_metadata_table_by_default()),
             replication: dataset.replication.map(replication::Replication::from),
             time_column: dataset.time_column,
-            time_format: dataset.time_format.map(TimeFormat::from),
+            time_format,
             acceleration,
+            results_cache_ttl: dataset.results_cache_ttl,
         })
     }
 }
@@ -134,6 +165,7 @@ impl Dataset {
             time_column: None,
             time_format: None,
             acceleration: None,
+            results_cache_ttl: None,
         })
     }
 
@@ -184,6 +216,9 @@ impl Dataset {
             if self.from == "localhost" || self.from.is_empty() {
                 return "localhost".to_string();
             }
+            if self.from == "sink" {
+                return "sink".to_string();
+            }
             "spiceai".to_string()
         }
     }
@@ -242,6 +277,22 @@ impl Dataset {
         None
     }
 
+    /// How long query results reading this dataset are cached, if it overrides the `item_ttl` of the results cache.
+    #[must_use]
+    pub fn results_cache_ttl(&self) -> Option<Duration> {
+        let results_cache_ttl = self.results_cache_ttl.as_ref()?;
+        if let Ok(duration) = fundu::parse_duration(results_cache_ttl) {
+            return Some(duration);
+        }
+        tracing::warn!(
+            "Unable to parse results cache ttl for dataset {}: {}",
+            self.name,
+            results_cache_ttl
+        );
+
+        None
+    }
+
     pub fn retention_check_interval(&self) -> Option<Duration> {
         if let Some(acceleration) = &self.acceleration {
             if let Some(retention_check_interval) = &acceleration.retention_check_interval {
@@ -303,6 +354,24 @@ impl Dataset {
         None
     }
 
+    #[must_use]
+    pub fn refresh_initial_backfill_window(&self) -> Option<Duration> {
+        if let Some(acceleration) = &self.acceleration {
+            if let Some(initial_backfill_window) = &acceleration.refresh_initial_backfill_window {
+                if let Ok(duration) = fundu::parse_duration(initial_backfill_window) {
+                    return Some(duration);
+                }
+                tracing::warn!(
+                    "Unable to parse initial backfill window for dataset {}: {}",
+                    self.name,
+                    initial_backfill_window
+                );
+            }
+        }
+
+        None
+    }
+
     #[must_use]
     pub fn is_view(&self) -> bool {
         self.sql.is_some() || self.sql_ref.is_some()
@@ -352,6 +421,7 @@ impl Dataset {
 }
 
 pub mod acceleration {
+    use datafusion::sql::TableReference;
     use spicepod::component::{dataset::acceleration as spicepod_acceleration, params::Params};
     use std::{collections::HashMap, fmt::Display};
 
@@ -426,6 +496,31 @@ pub mod acceleration {
         }
     }
 
+    /// How `refresh_sql` references to tables other than the dataset itself are reported.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum RefreshSqlReferenceCheck {
+        #[default]
+        Warn,
+        Error,
+        Off,
+    }
+
+    impl From<spicepod_acceleration::RefreshSqlReferenceCheck> for RefreshSqlReferenceCheck {
+        fn from(check: spicepod_acceleration::RefreshSqlReferenceCheck) -> Self {
+            match check {
+                spicepod_acceleration::RefreshSqlReferenceCheck::Warn => {
+                    RefreshSqlReferenceCheck::Warn
+                }
+                spicepod_acceleration::RefreshSqlReferenceCheck::Error => {
+                    RefreshSqlReferenceCheck::Error
+                }
+                spicepod_acceleration::RefreshSqlReferenceCheck::Off => {
+                    RefreshSqlReferenceCheck::Off
+                }
+            }
+        }
+    }
+
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
     pub enum Engine {
         #[default]
@@ -487,6 +582,12 @@ pub mod acceleration {
 
         pub refresh_data_window: Option<String>,
 
+        pub refresh_initial_backfill_window: Option<String>,
+
+        pub refresh_append_dedup_rows: bool,
+
+        pub refresh_append_boundary_inclusive: bool,
+
         pub params: HashMap<String, String>,
 
         pub engine_secret: Option<String>,
@@ -498,6 +599,45 @@ pub mod acceleration {
         pub retention_check_enabled: bool,
 
         pub on_zero_results: ZeroResultsAction,
+
+        pub refresh_sql_reference_check: RefreshSqlReferenceCheck,
+
+        pub rollup: Option<Rollup>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Rollup {
+        pub group_by: Vec<String>,
+
+        pub aggregations: Vec<String>,
+    }
+
+    impl Rollup {
+        /// The query computing the rollup from the rows of `dataset`.
+        #[must_use]
+        pub fn to_sql(&self, dataset: &TableReference) -> String {
+            let group_by = self.group_by.join(", ");
+            let projection = self
+                .group_by
+                .iter()
+                .chain(&self.aggregations)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "SELECT {projection} FROM {} GROUP BY {group_by}",
+                dataset.to_quoted_string()
+            )
+        }
+    }
+
+    impl From<spicepod_acceleration::Rollup> for Rollup {
+        fn from(rollup: spicepod_acceleration::Rollup) -> Self {
+            Rollup {
+                group_by: rollup.group_by,
+                aggregations: rollup.aggregations,
+            }
+        }
     }
 
     impl TryFrom<spicepod_acceleration::Acceleration> for Acceleration {
@@ -516,6 +656,9 @@ pub mod acceleration {
                 refresh_check_interval: acceleration.refresh_check_interval,
                 refresh_sql: acceleration.refresh_sql,
                 refresh_data_window: acceleration.refresh_data_window,
+                refresh_initial_backfill_window: acceleration.refresh_initial_backfill_window,
+                refresh_append_dedup_rows: acceleration.refresh_append_dedup_rows,
+                refresh_append_boundary_inclusive: acceleration.refresh_append_boundary_inclusive,
                 params: acceleration
                     .params
                     .as_ref()
@@ -526,6 +669,10 @@ pub mod acceleration {
                 retention_check_interval: acceleration.retention_check_interval,
                 retention_check_enabled: acceleration.retention_check_enabled,
                 on_zero_results: ZeroResultsAction::from(acceleration.on_zero_results),
+                refresh_sql_reference_check: RefreshSqlReferenceCheck::from(
+                    acceleration.refresh_sql_reference_check,
+                ),
+                rollup: acceleration.rollup.map(Rollup::from),
             })
         }
     }
@@ -540,12 +687,17 @@ pub mod acceleration {
                 refresh_check_interval: None,
                 refresh_sql: None,
                 refresh_data_window: None,
+                refresh_initial_backfill_window: None,
+                refresh_append_dedup_rows: false,
+                refresh_append_boundary_inclusive: false,
                 params: HashMap::default(),
                 engine_secret: None,
                 retention_period: None,
                 retention_check_interval: None,
                 retention_check_enabled: false,
                 on_zero_results: ZeroResultsAction::ReturnEmpty,
+                refresh_sql_reference_check: RefreshSqlReferenceCheck::Warn,
+                rollup: None,
             }
         }
     }
@@ -567,3 +719,23 @@ pub mod replication {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_time_format_is_validated() {
+        assert!(TimeFormat::Custom("%m/%d/%Y %H:%M".to_string()).is_valid());
+        assert!(!TimeFormat::Custom("%m/%d/%Y %Q".to_string()).is_valid());
+        assert!(!TimeFormat::Custom(String::new()).is_valid());
+
+        let mut dataset =
+            spicepod_dataset::Dataset::new("localhost".to_string(), "events".to_string());
+        dataset.time_format = Some(spicepod_dataset::TimeFormat::Custom("%Q".to_string()));
+        assert!(matches!(
+            Dataset::try_from(dataset),
+            Err(crate::Error::InvalidTimeFormat { .. })
+        ));
+    }
+}
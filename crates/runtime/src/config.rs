@@ -15,6 +15,8 @@ limitations under the License.
 */
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Clone, clap::Parser)]
 pub struct Config {
@@ -44,4 +46,158 @@ pub struct Config {
         action
     )]
     pub open_telemetry_bind_address: SocketAddr,
+
+    /// Maximum size in bytes of a serialized HTTP query response. Queries exceeding it are aborted with a 413.
+    #[arg(long = "max_response_bytes", value_name = "MAX_RESPONSE_BYTES", action)]
+    pub max_response_bytes: Option<usize>,
+
+    /// Rounds floats in JSON query results to this many decimal places.
+    #[arg(long = "json_float_precision", value_name = "DECIMAL_PLACES", action)]
+    pub json_float_precision: Option<u8>,
+
+    /// Writes decimals in JSON query results as numbers instead of strings, which clients may parse into imprecise floats.
+    #[arg(long = "json_decimals_as_numbers", action)]
+    pub json_decimals_as_numbers: bool,
+
+    /// How NaN and infinite floats, which JSON can't represent, are written in JSON query results.
+    #[arg(
+        long = "json_non_finite_floats",
+        value_name = "POLICY",
+        value_enum,
+        default_value_t = NonFiniteFloats::Null
+    )]
+    pub json_non_finite_floats: NonFiniteFloats,
+
+    /// How binary columns are encoded as strings in JSON query results.
+    #[arg(
+        long = "json_binary_encoding",
+        value_name = "ENCODING",
+        value_enum,
+        default_value_t = BinaryEncoding::Base64
+    )]
+    pub json_binary_encoding: BinaryEncoding,
+
+    /// Origins allowed to make cross-origin requests to the HTTP API, or `*` for any origin.
+    /// CORS is disabled when no origins are configured.
+    #[arg(
+        long = "cors_allowed_origins",
+        value_name = "ORIGINS",
+        value_delimiter = ',',
+        action
+    )]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// Methods allowed for cross-origin requests to the HTTP API.
+    #[arg(
+        long = "cors_allowed_methods",
+        value_name = "METHODS",
+        value_delimiter = ',',
+        default_value = "GET,POST,PATCH",
+        action
+    )]
+    pub cors_allowed_methods: Vec<String>,
+
+    /// Headers allowed for cross-origin requests to the HTTP API.
+    #[arg(
+        long = "cors_allowed_headers",
+        value_name = "HEADERS",
+        value_delimiter = ',',
+        default_value = "content-type",
+        action
+    )]
+    pub cors_allowed_headers: Vec<String>,
+
+    /// Maximum number of in-flight SQL queries over HTTP. Further queries are rejected with a 503 until one completes.
+    #[arg(
+        long = "max_concurrent_queries",
+        value_name = "MAX_CONCURRENT_QUERIES",
+        action
+    )]
+    pub max_concurrent_queries: Option<usize>,
+
+    /// Maximum number of in-flight requests to the lightweight HTTP endpoints (status, datasets, spicepods).
+    #[arg(
+        long = "max_concurrent_requests",
+        value_name = "MAX_CONCURRENT_REQUESTS",
+        action
+    )]
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Time zone of timestamps in query results, as an offset like `+05:00` or a name like `America/New_York`.
+    /// Takes precedence over `runtime.session_timezone` in the spicepod. Defaults to UTC.
+    #[arg(long = "session_timezone", value_name = "SESSION_TIMEZONE", action)]
+    pub session_timezone: Option<String>,
+
+    /// Maximum memory that queries may use, i.e. `8GB`. Sorts, joins and aggregations spill to disk or fail once it
+    /// is exhausted. Unlimited by default.
+    #[arg(
+        long = "max_memory",
+        value_name = "MAX_MEMORY",
+        value_parser = parse_memory_size,
+        action
+    )]
+    pub max_memory: Option<usize>,
+
+    /// Directory that queries spill to once `--max_memory` is exhausted, in a `spiced-spill` subdirectory. Spill files
+    /// left behind by a previous run are removed from it on startup. Defaults to the OS temp directory.
+    #[arg(long = "temp_dir", value_name = "TEMP_DIR", action)]
+    pub temp_dir: Option<PathBuf>,
+
+    /// Number of partitions that scans, joins and aggregations of a query are split into to run in parallel.
+    /// Takes precedence over `runtime.query_parallelism` in the spicepod. Defaults to the number of CPUs.
+    #[arg(long = "query_parallelism", value_name = "QUERY_PARALLELISM", action)]
+    pub query_parallelism: Option<usize>,
+
+    /// Logs queries that take longer than this, i.e. `500ms` or `5s`, with their SQL, duration and row count to the
+    /// `slow_query` log target. Slow queries aren't logged by default.
+    #[arg(
+        long = "slow_query_threshold",
+        value_name = "DURATION",
+        value_parser = fundu::parse_duration,
+        action
+    )]
+    pub slow_query_threshold: Option<Duration>,
+
+    /// Writes a JSON summary of the loaded datasets and models, with their sources, accelerations and load status,
+    /// to this file once loading completes.
+    #[arg(long = "startup_report", value_name = "PATH", action)]
+    pub startup_report: Option<PathBuf>,
+
+    /// Exits with an error if datasets and models haven't loaded within this duration, i.e. `5m`, instead of retrying
+    /// failing components indefinitely. Waits indefinitely by default.
+    #[arg(
+        long = "ready_timeout",
+        value_name = "DURATION",
+        value_parser = fundu::parse_duration,
+        action
+    )]
+    pub ready_timeout: Option<Duration>,
+}
+
+fn parse_memory_size(size: &str) -> Result<usize, String> {
+    let bytes = byte_unit::Byte::parse_str(size, true).map_err(|e| e.to_string())?;
+    usize::try_from(bytes.as_u64()).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum NonFiniteFloats {
+    /// Writes them as `null`.
+    #[default]
+    Null,
+
+    /// Writes them as the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    String,
+
+    /// Fails the query.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BinaryEncoding {
+    /// Standard base64 with padding.
+    #[default]
+    Base64,
+
+    /// Lowercase hexadecimal.
+    Hex,
 }
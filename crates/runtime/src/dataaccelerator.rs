@@ -26,8 +26,15 @@ use lazy_static::lazy_static;
 use secrets::ExposeSecret;
 use secrets::Secret;
 use snafu::prelude::*;
-use std::{any::Any, collections::HashMap, sync::Arc};
-use tokio::sync::Mutex;
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{Mutex, Semaphore};
 
 use self::arrow::ArrowAccelerator;
 
@@ -67,6 +74,18 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 lazy_static! {
     static ref DATA_ACCELERATOR_ENGINES: Mutex<HashMap<Engine, Arc<dyn DataAccelerator>>> =
         Mutex::new(HashMap::new());
+    static ref ACCELERATOR_FILE_REFRESH_SEMAPHORES: Mutex<HashMap<String, Arc<Semaphore>>> =
+        Mutex::new(HashMap::new());
+}
+
+static MAX_PARALLEL_REFRESHES_PER_ACCELERATOR_FILE: AtomicUsize = AtomicUsize::new(1);
+
+/// Sets how many refreshes may write to the same accelerator file concurrently.
+///
+/// Only affects accelerator files that haven't had a refresh semaphore created for them yet.
+pub fn set_max_parallel_refreshes_per_accelerator_file(max_parallel_refreshes: usize) {
+    MAX_PARALLEL_REFRESHES_PER_ACCELERATOR_FILE
+        .store(max_parallel_refreshes.max(1), Ordering::Relaxed);
 }
 
 pub async fn register_accelerator_engine(
@@ -99,6 +118,56 @@ pub async fn get_accelerator_engine(engine: Engine) -> Option<Arc<dyn DataAccele
     }
 }
 
+/// Returns the path of the file backing an accelerated table, if the accelerator is file-based.
+///
+/// Mirrors the defaults used by the accelerator table factories when no file parameter is set.
+#[must_use]
+pub fn accelerator_file_path(
+    table_name: &TableReference,
+    acceleration_settings: &acceleration::Acceleration,
+) -> Option<String> {
+    if acceleration_settings.mode != Mode::File {
+        return None;
+    }
+
+    let params = &acceleration_settings.params;
+    match acceleration_settings.engine {
+        Engine::DuckDB => Some(
+            params
+                .get("duckdb_file")
+                .cloned()
+                .unwrap_or(format!("{table_name}.db")),
+        ),
+        Engine::Sqlite => Some(
+            params
+                .get("sqlite_file")
+                .cloned()
+                .unwrap_or(format!("{table_name}_sqlite.db")),
+        ),
+        Engine::Arrow | Engine::PostgreSQL => None,
+    }
+}
+
+/// Returns the semaphore that refreshes writing to the same accelerator file must acquire before writing.
+///
+/// Tables that don't share a file with other tables (i.e. in-memory accelerators) don't need one.
+pub async fn refresh_write_semaphore(
+    table_name: &TableReference,
+    acceleration_settings: &acceleration::Acceleration,
+) -> Option<Arc<Semaphore>> {
+    let file_path = accelerator_file_path(table_name, acceleration_settings)?;
+    let key = format!("{}:{file_path}", acceleration_settings.engine);
+
+    let mut semaphores = ACCELERATOR_FILE_REFRESH_SEMAPHORES.lock().await;
+    let semaphore = semaphores.entry(key).or_insert_with(|| {
+        Arc::new(Semaphore::new(
+            MAX_PARALLEL_REFRESHES_PER_ACCELERATOR_FILE.load(Ordering::Relaxed),
+        ))
+    });
+
+    Some(Arc::clone(semaphore))
+}
+
 /// A `DataAccelerator` knows how to read, write and create new tables.
 #[async_trait]
 pub trait DataAccelerator: Send + Sync {
@@ -15,7 +15,7 @@ limitations under the License.
 */
 
 use crate::component::dataset::Dataset;
-use arrow::datatypes::SchemaRef;
+use arrow::datatypes::{Schema, SchemaRef};
 use async_trait::async_trait;
 use data_components::object::metadata::ObjectStoreMetadataTable;
 use data_components::object::text::ObjectStoreTextTable;
@@ -32,8 +32,9 @@ use datafusion::execution::config::SessionConfig;
 use datafusion::execution::context::SessionContext;
 use datafusion::logical_expr::{Expr, LogicalPlanBuilder};
 use datafusion::sql::TableReference;
+use futures::TryStreamExt;
 use lazy_static::lazy_static;
-use object_store::ObjectStore;
+use object_store::{path::Path as ObjectPath, ObjectStore};
 use snafu::prelude::*;
 use std::any::Any;
 use std::collections::HashMap;
@@ -69,9 +70,11 @@ pub mod mysql;
 pub mod odbc;
 #[cfg(feature = "postgres")]
 pub mod postgres;
+pub mod rest;
 pub mod s3;
 #[cfg(feature = "ftp")]
 pub mod sftp;
+pub mod sink;
 #[cfg(feature = "spark")]
 pub mod spark;
 pub mod spiceai;
@@ -233,6 +236,8 @@ pub async fn register_all() {
     #[cfg(feature = "flightsql")]
     register_connector_factory("flightsql", flightsql::FlightSQL::create).await;
     register_connector_factory("s3", s3::S3::create).await;
+    register_connector_factory("rest", rest::Rest::create).await;
+    register_connector_factory("sink", sink::Sink::create).await;
     #[cfg(feature = "ftp")]
     register_connector_factory("ftp", ftp::FTP::create).await;
     #[cfg(feature = "ftp")]
@@ -402,23 +407,33 @@ pub trait ListingTableConnector: DataConnector {
         let extension = params.get("file_extension").cloned();
 
         match params.get("file_format").map(String::as_str) {
-            Some("csv") => Ok((
-                Some(self.get_csv_format(params)?),
-                extension.unwrap_or(".csv".to_string()),
-            )),
+            Some("csv") => {
+                let (csv_format, compression_extension) = self.get_csv_format(params, dataset)?;
+                Ok((
+                    Some(csv_format),
+                    extension.unwrap_or(format!(".csv{compression_extension}")),
+                ))
+            }
             Some("parquet") => Ok((
                 Some(Arc::new(ParquetFormat::default())),
                 extension.unwrap_or(".parquet".to_string()),
             )),
             Some(format) => Ok((None, format!(".{format}"))),
             None => {
-                if let Some(ext) = std::path::Path::new(dataset.path().as_str()).extension() {
+                let path = dataset.path();
+                let uncompressed_path = csv_compression(&path)
+                    .map_or(path.as_str(), |(_, ext)| &path[..path.len() - ext.len()]);
+                if let Some(ext) = std::path::Path::new(uncompressed_path).extension() {
                     if ext.eq_ignore_ascii_case("csv") {
+                        let (csv_format, compression_extension) =
+                            self.get_csv_format(params, dataset)?;
                         return Ok((
-                            Some(self.get_csv_format(params)?),
-                            extension.unwrap_or(".csv".to_string()),
+                            Some(csv_format),
+                            extension.unwrap_or(format!(".csv{compression_extension}")),
                         ));
                     }
+                }
+                if let Some(ext) = std::path::Path::new(path.as_str()).extension() {
                     if ext.eq_ignore_ascii_case("parquet") {
                         return Ok((
                             Some(Arc::new(ParquetFormat::default())),
@@ -436,10 +451,15 @@ pub trait ListingTableConnector: DataConnector {
         }
     }
 
+    /// Returns the CSV format configured by `params`, and the extension of its compression, i.e. `.gz`.
+    ///
+    /// Without a `compression_type` param, the compression is inferred from the extension of the dataset's path,
+    /// i.e. `data.csv.gz` is read as GZIP compressed.
     fn get_csv_format(
         &self,
         params: &HashMap<String, String>,
-    ) -> DataConnectorResult<Arc<CsvFormat>>
+        dataset: &Dataset,
+    ) -> DataConnectorResult<(Arc<CsvFormat>, &'static str)>
     where
         Self: Display,
     {
@@ -456,9 +476,17 @@ pub trait ListingTableConnector: DataConnector {
         let delimiter = params
             .get("delimiter")
             .map_or(b',', |f| *f.as_bytes().first().unwrap_or(&b','));
-        let compression_type = params.get("compression_type").map_or("", |f| f);
-
-        Ok(Arc::new(
+        let path = dataset.path();
+        let compression_type = params.get("compression_type").map_or_else(
+            || csv_compression(&path).map_or("", |(compression_type, _)| compression_type),
+            String::as_str,
+        );
+        let compression_extension = CSV_COMPRESSION_EXTENSIONS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(compression_type))
+            .map_or("", |(_, ext)| *ext);
+
+        let csv_format = Arc::new(
             CsvFormat::default()
                 .with_has_header(has_header)
                 .with_quote(quote)
@@ -473,10 +501,92 @@ pub trait ListingTableConnector: DataConnector {
                             message: format!("Invalid CSV compression_type: {compression_type}, supported types are: GZIP, BZIP2, XZ, ZSTD, UNCOMPRESSED"),
                         })?,
                 ),
-        ))
+        );
+
+        Ok((csv_format, compression_extension))
     }
 }
 
+/// Compression types of CSV files, with the extension their files are recognized by.
+const CSV_COMPRESSION_EXTENSIONS: [(&str, &str); 4] = [
+    ("gzip", ".gz"),
+    ("bzip2", ".bz2"),
+    ("xz", ".xz"),
+    ("zstd", ".zst"),
+];
+
+/// The compression type and extension of a path ending in a compression extension, i.e. `data.csv.gz`.
+fn csv_compression(path: &str) -> Option<(&'static str, &'static str)> {
+    let path = path.to_ascii_lowercase();
+    CSV_COMPRESSION_EXTENSIONS
+        .iter()
+        .find(|(_, ext)| path.ends_with(ext))
+        .copied()
+}
+
+/// Lists the files matching the glob in the path of `url`, i.e. `s3://bucket/prefix/*.parquet` or
+/// `file:/data/**/*.csv`, where the glob starts at `glob_start`. A `*` matches within a single path segment,
+/// `**` across segments.
+async fn list_glob_matches(
+    dataconnector: String,
+    store: Arc<dyn ObjectStore>,
+    url: &Url,
+    glob_start: usize,
+) -> DataConnectorResult<Vec<ListingTableUrl>> {
+    let path = url.path();
+    let pattern = glob::Pattern::new(path.trim_start_matches('/'))
+        .boxed()
+        .context(InvalidConfigurationSnafu {
+            dataconnector: dataconnector.clone(),
+            message: format!("Invalid glob in {url}"),
+        })?;
+    let match_options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    let prefix = ObjectPath::from(
+        path[..glob_start]
+            .rsplit_once('/')
+            .map_or("", |(prefix, _)| prefix),
+    );
+
+    let mut files = store
+        .list(Some(&prefix))
+        .try_filter(|file| {
+            futures::future::ready(pattern.matches_with(file.location.as_ref(), match_options))
+        })
+        .map_ok(|file| file.location.to_string())
+        .try_collect::<Vec<_>>()
+        .await
+        .boxed()
+        .context(UnableToConnectInternalSnafu {
+            dataconnector: dataconnector.clone(),
+        })?;
+    files.sort();
+
+    if files.is_empty() {
+        return Err(DataConnectorError::InvalidConfiguration {
+            dataconnector,
+            message: format!("No files match {url}"),
+            source: "No files match the glob".into(),
+        });
+    }
+
+    files
+        .into_iter()
+        .map(|file| {
+            let mut file_url = url.clone();
+            file_url.set_path(&format!("/{file}"));
+            ListingTableUrl::parse(file_url)
+                .boxed()
+                .context(InternalSnafu {
+                    dataconnector: dataconnector.clone(),
+                    code: "LTC-LGM-LTUP".to_string(), // ListingTableConnector-ListGlobMatches-ListingTableUrlParse
+                })
+        })
+        .collect()
+}
+
 #[async_trait]
 impl<T: ListingTableConnector + Display> DataConnector for T {
     fn as_any(&self) -> &dyn Any {
@@ -504,14 +614,6 @@ impl<T: ListingTableConnector + Display> DataConnector for T {
         let ctx: SessionContext = Self::get_session_context();
         let url = self.get_object_store_url(dataset)?;
 
-        // This shouldn't error because we've already validated the URL in `get_object_store_url`.
-        let table_path = ListingTableUrl::parse(url.clone())
-            .boxed()
-            .context(InternalSnafu {
-                dataconnector: format!("{self}"),
-                code: "LTC-RP-LTUP".to_string(), // ListingTableConnector-ReadProvider-ListingTableUrlParse
-            })?;
-
         let (file_format_opt, extension) = self.get_file_format_and_extension(dataset)?;
         match file_format_opt {
             None => {
@@ -528,18 +630,48 @@ impl<T: ListingTableConnector + Display> DataConnector for T {
             }
             Some(file_format) => {
                 let options = ListingOptions::new(file_format).with_file_extension(&extension);
+                let table_paths = match url.path().find(['*', '[']) {
+                    Some(glob_start) => {
+                        list_glob_matches(
+                            format!("{self}"),
+                            self.get_object_store(dataset)?,
+                            &url,
+                            glob_start,
+                        )
+                        .await?
+                    }
+                    // This shouldn't error because we've already validated the URL in `get_object_store_url`.
+                    None => vec![ListingTableUrl::parse(url.clone()).boxed().context(
+                        InternalSnafu {
+                            dataconnector: format!("{self}"),
+                            code: "LTC-RP-LTUP".to_string(), // ListingTableConnector-ReadProvider-ListingTableUrlParse
+                        },
+                    )?],
+                };
+
+                // Files matched by a glob may have evolved schemas, so infer a schema covering all of them
+                let mut schemas = Vec::with_capacity(table_paths.len());
+                for table_path in &table_paths {
+                    let schema = options
+                        .infer_schema(&ctx.state(), table_path)
+                        .await
+                        .boxed()
+                        .context(UnableToConnectInternalSnafu {
+                            dataconnector: format!("{self}"),
+                        })?;
+                    schemas.push(schema.as_ref().clone());
+                }
+                let resolved_schema =
+                    Schema::try_merge(schemas)
+                        .boxed()
+                        .context(InvalidConfigurationSnafu {
+                            dataconnector: format!("{self}"),
+                            message: format!("Incompatible schemas of the files matching {url}"),
+                        })?;
 
-                let resolved_schema = options
-                    .infer_schema(&ctx.state(), &table_path)
-                    .await
-                    .boxed()
-                    .context(UnableToConnectInternalSnafu {
-                        dataconnector: format!("{self}"),
-                    })?;
-
-                let config = ListingTableConfig::new(table_path)
+                let config = ListingTableConfig::new_with_multi_paths(table_paths)
                     .with_listing_options(options)
-                    .with_schema(resolved_schema);
+                    .with_schema(Arc::new(resolved_schema));
 
                 // This shouldn't error because we're passing the schema and options correctly.
                 let table = ListingTable::try_new(config)
@@ -683,4 +815,35 @@ mod tests {
             panic!("Unexpected error");
         }
     }
+
+    #[test]
+    fn test_get_file_format_and_extension_detect_compressed_csv_extension() {
+        let (connector, dataset) = setup_connector("test:test.csv.gz".to_string(), HashMap::new());
+
+        if let Ok((Some(file_format), extension)) =
+            connector.get_file_format_and_extension(&dataset)
+        {
+            assert_eq!(file_format.file_type(), FileType::CSV);
+            assert_eq!(extension, ".csv.gz");
+        } else {
+            panic!("Unexpected error");
+        }
+    }
+
+    #[test]
+    fn test_get_file_format_and_extension_csv_compression_from_params() {
+        let mut params = HashMap::new();
+        params.insert("file_format".to_string(), "csv".to_string());
+        params.insert("compression_type".to_string(), "zstd".to_string());
+        let (connector, dataset) = setup_connector("test:test/".to_string(), params);
+
+        if let Ok((Some(file_format), extension)) =
+            connector.get_file_format_and_extension(&dataset)
+        {
+            assert_eq!(file_format.file_type(), FileType::CSV);
+            assert_eq!(extension, ".csv.zst");
+        } else {
+            panic!("Unexpected error");
+        }
+    }
 }
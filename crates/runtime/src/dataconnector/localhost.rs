@@ -87,30 +87,33 @@ impl DataConnectorFactory for LocalhostConnector {
     ) -> Pin<Box<dyn Future<Output = super::NewDataConnectorResult> + Send>> {
         Box::pin(async move {
             let schema = params.get("schema").ok_or(Error::MissingSchemaParameter)?;
+            let schema = schema_from_create_table(schema)?;
 
-            let statements = Parser::parse_sql(&PostgreSqlDialect {}, schema).context(
-                UnableToParseSchemaSnafu {
-                    schema: schema.clone(),
-                },
-            )?;
-            ensure!(statements.len() == 1, OneStatementExpectedSnafu);
+            Ok(Arc::new(LocalhostConnector { schema }) as Arc<dyn DataConnector>)
+        })
+    }
+}
 
-            let statement = statements[0].clone();
+/// Parses a single SQL `CREATE TABLE` statement into an Arrow schema.
+pub(crate) fn schema_from_create_table(schema: &str) -> Result<SchemaRef> {
+    let statements =
+        Parser::parse_sql(&PostgreSqlDialect {}, schema).context(UnableToParseSchemaSnafu {
+            schema: schema.to_string(),
+        })?;
+    ensure!(statements.len() == 1, OneStatementExpectedSnafu);
 
-            let columns = match statement {
-                Statement::CreateTable { columns, .. } => columns,
-                _ => CreateTableStatementExpectedSnafu.fail()?,
-            };
+    let statement = statements[0].clone();
 
-            let schema = SqlToRel::new(&LocalhostContextProvider::new())
-                .build_schema(columns)
-                .context(UnableToParseSchemaFromColumnDefinitionsSnafu)?;
+    let columns = match statement {
+        Statement::CreateTable { columns, .. } => columns,
+        _ => CreateTableStatementExpectedSnafu.fail()?,
+    };
 
-            Ok(Arc::new(LocalhostConnector {
-                schema: Arc::new(schema),
-            }) as Arc<dyn DataConnector>)
-        })
-    }
+    let schema = SqlToRel::new(&LocalhostContextProvider::new())
+        .build_schema(columns)
+        .context(UnableToParseSchemaFromColumnDefinitionsSnafu)?;
+
+    Ok(Arc::new(schema))
 }
 
 #[async_trait]
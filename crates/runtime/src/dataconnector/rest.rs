@@ -0,0 +1,1114 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use crate::component::dataset::Dataset;
+use arrow::array::RecordBatch;
+use arrow::datatypes::{DataType, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use async_trait::async_trait;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use reqwest::header::{HeaderMap, LINK};
+use secrets::{get_secret_or_param, Secret};
+use serde_json::Value;
+use snafu::prelude::*;
+use std::any::Any;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::{collections::HashMap, future::Future};
+use url::Url;
+use util::http::RequestLogging;
+use util::rate_limit::RateLimiter;
+
+use super::{DataConnector, DataConnectorFactory, DataConnectorResult};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const DEFAULT_MAX_PAGES: usize = 1000;
+const RECORDS_PER_BATCH: usize = 1024;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(
+        "Invalid pagination mode: {mode}. Expected one of: none, offset, cursor, link_header"
+    ))]
+    InvalidPaginationMode { mode: String },
+
+    #[snafu(display("Invalid value for {param}: {value}"))]
+    InvalidParameterValue { param: String, value: String },
+
+    #[snafu(display(r#"Missing required parameter "rest_cursor_pointer" for cursor pagination"#))]
+    MissingCursorPointer,
+
+    #[snafu(display("Invalid schema override: {source}"))]
+    InvalidSchemaOverride { source: super::localhost::Error },
+
+    #[snafu(display("Unable to create HTTP client: {source}"))]
+    UnableToCreateHttpClient { source: util::http::Error },
+
+    #[snafu(display("Invalid rate limit: {source}"))]
+    InvalidRateLimit { source: util::rate_limit::Error },
+
+    #[snafu(display("Request to {url} failed: {source}"))]
+    RequestFailed { url: String, source: reqwest::Error },
+
+    #[snafu(display("Unable to parse JSON response from {url}: {source}"))]
+    InvalidJsonResponse { url: String, source: reqwest::Error },
+
+    #[snafu(display(
+        "JSON pointer {pointer} does not reference an array in the response from {url}"
+    ))]
+    DataIsNotAnArray { pointer: String, url: String },
+
+    #[snafu(display("The response from {url} returned errors: {}", messages.join("; ")))]
+    ResponseErrors { url: String, messages: Vec<String> },
+
+    #[snafu(display(r#"The response from {url} has multiple arrays of records: {}. Select one with the "rest_json_pointer" parameter"#, candidates.join(", ")))]
+    AmbiguousRecords {
+        url: String,
+        candidates: Vec<String>,
+    },
+
+    #[snafu(display(r#"The response from {url} has no records to infer the schema from. Declare it with the "rest_schema" parameter"#))]
+    NoRecordsToInferSchema { url: String },
+
+    #[snafu(display("Unable to infer schema from the response: {source}"))]
+    UnableToInferSchema { source: ArrowError },
+
+    #[snafu(display("Unable to convert the response to Arrow: {source}"))]
+    UnableToDecodeRecords { source: ArrowError },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// How the connector requests subsequent pages from the REST endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pagination {
+    /// A single request returns all the data.
+    None,
+    /// Pages are requested with an increasing offset and fixed limit query parameter.
+    Offset {
+        offset_param: String,
+        limit_param: String,
+        page_size: usize,
+    },
+    /// The response contains a cursor (located by a JSON pointer) that is passed as a query parameter to request the next page.
+    Cursor {
+        cursor_param: String,
+        cursor_pointer: String,
+    },
+    /// The next page URL is provided in the `Link` response header with `rel="next"`.
+    LinkHeader,
+}
+
+impl Pagination {
+    fn from_params(params: &HashMap<String, String>) -> Result<Self> {
+        let mode = params.get("rest_pagination").map_or("none", String::as_str);
+
+        match mode {
+            "none" => Ok(Pagination::None),
+            "offset" => Ok(Pagination::Offset {
+                offset_param: param_or(params, "rest_offset_param", "offset"),
+                limit_param: param_or(params, "rest_limit_param", "limit"),
+                page_size: parse_param(params, "rest_page_size")?.unwrap_or(DEFAULT_PAGE_SIZE),
+            }),
+            "cursor" => Ok(Pagination::Cursor {
+                cursor_param: param_or(params, "rest_cursor_param", "cursor"),
+                cursor_pointer: params
+                    .get("rest_cursor_pointer")
+                    .cloned()
+                    .context(MissingCursorPointerSnafu)?,
+            }),
+            "link_header" => Ok(Pagination::LinkHeader),
+            _ => InvalidPaginationModeSnafu { mode }.fail(),
+        }
+    }
+}
+
+/// A connector that reads a JSON array from a REST endpoint.
+///
+/// The dataset path is the URL of the endpoint, i.e. `rest:https://api.example.com/v1/items`.
+pub struct Rest {
+    client: reqwest::Client,
+    request_logging: RequestLogging,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    token: Option<String>,
+    json_pointer: Option<String>,
+    pagination: Pagination,
+    max_pages: usize,
+    schema: Option<SchemaRef>,
+    string_columns: Vec<String>,
+}
+
+impl std::fmt::Display for Rest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rest")
+    }
+}
+
+impl DataConnectorFactory for Rest {
+    fn create(
+        secret: Option<Secret>,
+        params: Arc<HashMap<String, String>>,
+    ) -> Pin<Box<dyn Future<Output = super::NewDataConnectorResult> + Send>> {
+        Box::pin(async move {
+            let schema = match params.get("rest_schema") {
+                Some(schema) => Some(
+                    super::localhost::schema_from_create_table(schema)
+                        .context(InvalidSchemaOverrideSnafu)?,
+                ),
+                None => None,
+            };
+
+            let rest = Self {
+                client: util::http::TlsOptions::from_params(&params)
+                    .and_then(|tls| util::http::client_with_tls(&tls))
+                    .context(UnableToCreateHttpClientSnafu)?,
+                request_logging: RequestLogging::from_params(&params)
+                    .context(UnableToCreateHttpClientSnafu)?,
+                rate_limiter: RateLimiter::from_params(&params)
+                    .context(InvalidRateLimitSnafu)?
+                    .map(Arc::new),
+                token: get_secret_or_param(&params, &secret, "rest_token_key", "rest_token"),
+                json_pointer: params.get("rest_json_pointer").cloned(),
+                pagination: Pagination::from_params(&params)?,
+                max_pages: parse_param(&params, "rest_max_pages")?.unwrap_or(DEFAULT_MAX_PAGES),
+                schema,
+                // Read as strings, i.e. 64-bit IDs that clients would otherwise parse into imprecise floats
+                string_columns: params
+                    .get("rest_string_columns")
+                    .map(|columns| {
+                        columns
+                            .split(',')
+                            .map(|column| column.trim().to_string())
+                            .filter(|column| !column.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            };
+            Ok(Arc::new(rest) as Arc<dyn DataConnector>)
+        })
+    }
+}
+
+#[async_trait]
+impl DataConnector for Rest {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn read_provider(
+        &self,
+        dataset: &Dataset,
+    ) -> DataConnectorResult<Arc<dyn TableProvider>> {
+        let url =
+            Url::parse(&dataset.path())
+                .boxed()
+                .context(super::InvalidConfigurationSnafu {
+                    dataconnector: "rest",
+                    message: format!("{} is not a valid URL", dataset.path()),
+                })?;
+
+        let table = RestTable::try_new(self.request(url))
+            .await
+            .boxed()
+            .context(super::UnableToGetReadProviderSnafu {
+                dataconnector: "rest",
+            })?;
+
+        Ok(Arc::new(table))
+    }
+}
+
+impl Rest {
+    fn request(&self, url: Url) -> RestRequest {
+        RestRequest {
+            client: self.client.clone(),
+            request_logging: self.request_logging,
+            rate_limiter: self.rate_limiter.clone(),
+            url,
+            token: self.token.clone(),
+            json_pointer: self.json_pointer.clone(),
+            pagination: self.pagination.clone(),
+            max_pages: self.max_pages,
+            schema: self.schema.clone(),
+            string_columns: self.string_columns.clone(),
+        }
+    }
+}
+
+/// Everything needed to fetch all the records for a single REST dataset.
+#[derive(Clone)]
+struct RestRequest {
+    client: reqwest::Client,
+    request_logging: RequestLogging,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    url: Url,
+    token: Option<String>,
+    json_pointer: Option<String>,
+    pagination: Pagination,
+    max_pages: usize,
+    schema: Option<SchemaRef>,
+    string_columns: Vec<String>,
+}
+
+impl RestRequest {
+    /// Fetches records across all pages, stopping early once `limit` records have been read.
+    async fn fetch_records(&self, limit: Option<usize>) -> Result<Vec<Value>> {
+        let mut records = Vec::new();
+        let mut next_url = Some(self.url.clone());
+        let mut offset = 0;
+
+        if let Pagination::Offset {
+            offset_param,
+            limit_param,
+            page_size,
+        } = &self.pagination
+        {
+            next_url = Some(with_query_params(
+                &self.url,
+                &[(offset_param, "0"), (limit_param, &page_size.to_string())],
+            ));
+        }
+
+        for _ in 0..self.max_pages {
+            let Some(url) = next_url.take() else {
+                break;
+            };
+
+            let (headers, body) = self.get_page(&url).await?;
+            let page = self.extract_records(&body, &url)?;
+            let page_len = page.len();
+            records.extend(page);
+
+            if page_len == 0 || limit.is_some_and(|limit| records.len() >= limit) {
+                break;
+            }
+
+            next_url = match &self.pagination {
+                Pagination::None => None,
+                Pagination::Offset {
+                    offset_param,
+                    limit_param,
+                    page_size,
+                } => {
+                    offset += page_len;
+                    (page_len >= *page_size).then(|| {
+                        with_query_params(
+                            &self.url,
+                            &[
+                                (offset_param, &offset.to_string()),
+                                (limit_param, &page_size.to_string()),
+                            ],
+                        )
+                    })
+                }
+                Pagination::Cursor {
+                    cursor_param,
+                    cursor_pointer,
+                } => match body.pointer(cursor_pointer) {
+                    Some(Value::String(cursor)) if !cursor.is_empty() => {
+                        Some(with_query_params(&self.url, &[(cursor_param, cursor)]))
+                    }
+                    Some(Value::Number(cursor)) => Some(with_query_params(
+                        &self.url,
+                        &[(cursor_param, &cursor.to_string())],
+                    )),
+                    _ => None,
+                },
+                Pagination::LinkHeader => next_link(&headers),
+            };
+        }
+
+        if next_url.is_some() {
+            tracing::warn!(
+                "Stopped reading {} after rest_max_pages ({}) pages, results are incomplete",
+                self.url,
+                self.max_pages
+            );
+        }
+
+        if let Some(limit) = limit {
+            records.truncate(limit);
+        }
+
+        Ok(records)
+    }
+
+    async fn get_page(&self, url: &Url) -> Result<(HeaderMap, Value)> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        let mut request = self.client.get(url.clone());
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = self
+            .request_logging
+            .send(request)
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context(RequestFailedSnafu {
+                url: url.to_string(),
+            })?;
+
+        let headers = response.headers().clone();
+        let body = response
+            .json::<Value>()
+            .await
+            .context(InvalidJsonResponseSnafu {
+                url: url.to_string(),
+            })?;
+
+        Ok((headers, body))
+    }
+
+    fn extract_records(&self, body: &Value, url: &Url) -> Result<Vec<Value>> {
+        // APIs following the GraphQL and JSON:API conventions report failures in a top-level `errors` array
+        if self.json_pointer.as_deref() != Some("/errors") {
+            if let Some(Value::Array(errors)) = body.get("errors") {
+                if !errors.is_empty() {
+                    return ResponseErrorsSnafu {
+                        url: url.to_string(),
+                        messages: errors
+                            .iter()
+                            .map(|error| match error.get("message") {
+                                Some(Value::String(message)) => message.clone(),
+                                _ => error.to_string(),
+                            })
+                            .collect::<Vec<_>>(),
+                    }
+                    .fail();
+                }
+            }
+        }
+
+        let data = match &self.json_pointer {
+            Some(pointer) => body.pointer(pointer),
+            None if body.is_object() => {
+                let candidates = find_records_pointers(body);
+                match candidates.as_slice() {
+                    [pointer] => body.pointer(pointer),
+                    [] => None,
+                    _ => {
+                        return AmbiguousRecordsSnafu {
+                            url: url.to_string(),
+                            candidates,
+                        }
+                        .fail()
+                    }
+                }
+            }
+            None => Some(body),
+        };
+
+        match data {
+            Some(Value::Array(records)) => Ok(records.clone()),
+            // An empty result is commonly returned as `null` rather than `[]`
+            Some(Value::Null) => Ok(vec![]),
+            _ => DataIsNotAnArraySnafu {
+                pointer: self.json_pointer.clone().unwrap_or_default(),
+                url: url.to_string(),
+            }
+            .fail(),
+        }
+    }
+}
+
+/// A read-only table over a REST endpoint. The data is fetched on every scan.
+struct RestTable {
+    request: RestRequest,
+    schema: SchemaRef,
+}
+
+impl RestTable {
+    async fn try_new(request: RestRequest) -> Result<Self> {
+        let schema = match &request.schema {
+            Some(schema) => Arc::clone(schema),
+            None => {
+                let records = request.fetch_records(Some(RECORDS_PER_BATCH)).await?;
+                // Inferring from no records results in a schema without columns, which can't be queried
+                ensure!(
+                    !records.is_empty(),
+                    NoRecordsToInferSchemaSnafu {
+                        url: request.url.to_string()
+                    }
+                );
+                let schema =
+                    arrow_json::reader::infer_json_schema_from_iterator(records.iter().map(Ok))
+                        .context(UnableToInferSchemaSnafu)?;
+                Arc::new(schema)
+            }
+        };
+        let schema = with_string_columns(&schema, &request.string_columns);
+
+        Ok(Self { request, schema })
+    }
+}
+
+#[async_trait]
+impl TableProvider for RestTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        let records = self
+            .request
+            .fetch_records(limit)
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        let batches = records_to_batches(&records, &self.schema)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        Ok(Arc::new(MemoryExec::try_new(
+            &[batches],
+            Arc::clone(&self.schema),
+            projection.cloned(),
+        )?))
+    }
+}
+
+/// Pointers to the arrays that may hold the records of a response object without a `rest_json_pointer`: its top-level
+/// arrays, and for GraphQL style responses, the arrays under `data`.
+fn find_records_pointers(body: &Value) -> Vec<String> {
+    fn escape(name: &str) -> String {
+        name.replace('~', "~0").replace('/', "~1")
+    }
+
+    let Value::Object(fields) = body else {
+        return vec![];
+    };
+
+    let mut pointers = Vec::new();
+    for (name, value) in fields {
+        match value {
+            Value::Array(_) if name != "errors" => pointers.push(format!("/{}", escape(name))),
+            Value::Object(data) if name == "data" => pointers.extend(
+                data.iter()
+                    .filter(|(_, value)| value.is_array())
+                    .map(|(field, _)| format!("/data/{}", escape(field))),
+            ),
+            _ => {}
+        }
+    }
+    pointers
+}
+
+/// Changes the type of `columns` to `Utf8`, so that their values are read as strings.
+fn with_string_columns(schema: &SchemaRef, columns: &[String]) -> SchemaRef {
+    if columns.is_empty() {
+        return Arc::clone(schema);
+    }
+
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            if columns.contains(field.name()) {
+                Arc::new(field.as_ref().clone().with_data_type(DataType::Utf8))
+            } else {
+                Arc::clone(field)
+            }
+        })
+        .collect::<Vec<_>>();
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+fn records_to_batches(records: &[Value], schema: &SchemaRef) -> Result<Vec<RecordBatch>> {
+    let mut decoder = arrow_json::ReaderBuilder::new(Arc::clone(schema))
+        .with_batch_size(RECORDS_PER_BATCH)
+        // Numbers and booleans are read as is into string columns
+        .with_coerce_primitive(true)
+        .build_decoder()
+        .context(UnableToDecodeRecordsSnafu)?;
+
+    let mut batches = Vec::new();
+    for chunk in records.chunks(RECORDS_PER_BATCH) {
+        decoder
+            .serialize(chunk)
+            .context(UnableToDecodeRecordsSnafu)?;
+        if let Some(batch) = decoder.flush().context(UnableToDecodeRecordsSnafu)? {
+            batches.push(batch);
+        }
+    }
+
+    Ok(batches)
+}
+
+fn with_query_params(url: &Url, params: &[(&String, &str)]) -> Url {
+    let mut url = url.clone();
+    url.query_pairs_mut()
+        .extend_pairs(params.iter().map(|(k, v)| (k.as_str(), *v)));
+    url
+}
+
+/// Parses the `rel="next"` URL from a `Link` header, i.e. `<https://api.example.com/items?page=2>; rel="next"`.
+fn next_link(headers: &HeaderMap) -> Option<Url> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+
+    link.split(',').find_map(|part| {
+        let mut segments = part.split(';');
+        let target = segments.next()?.trim();
+        let is_next = segments.any(|s| {
+            let s = s.trim().replace(' ', "");
+            s == r#"rel="next""# || s == "rel=next"
+        });
+
+        if is_next {
+            Url::parse(target.trim_start_matches('<').trim_end_matches('>')).ok()
+        } else {
+            None
+        }
+    })
+}
+
+fn param_or(params: &HashMap<String, String>, key: &str, default: &str) -> String {
+    params
+        .get(key)
+        .cloned()
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn parse_param<T: FromStr>(params: &HashMap<String, String>, key: &str) -> Result<Option<T>> {
+    params
+        .get(key)
+        .map(|value| {
+            value
+                .parse::<T>()
+                .map_err(|_| Error::InvalidParameterValue {
+                    param: key.to_string(),
+                    value: value.to_string(),
+                })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use datafusion::execution::context::SessionContext;
+    use serde_json::json;
+
+    const TOTAL_ITEMS: usize = 5;
+
+    async fn items(Query(params): Query<HashMap<String, usize>>) -> Json<Value> {
+        let offset = params.get("offset").copied().unwrap_or(0);
+        let limit = params.get("limit").copied().unwrap_or(TOTAL_ITEMS);
+
+        let data: Vec<Value> = (offset..TOTAL_ITEMS.min(offset + limit))
+            .map(|i| json!({ "id": i, "name": format!("item_{i}") }))
+            .collect();
+
+        Json(json!({ "data": data }))
+    }
+
+    /// Pages of 2 items, linked by a `next_cursor` that is empty on the last page.
+    async fn cursor_items(Query(params): Query<HashMap<String, usize>>) -> Json<Value> {
+        let offset = params.get("cursor").copied().unwrap_or(0);
+        let end = TOTAL_ITEMS.min(offset + 2);
+        let data: Vec<Value> = (offset..end).map(|i| json!({ "id": i })).collect();
+        let next_cursor = if end < TOTAL_ITEMS {
+            end.to_string()
+        } else {
+            String::new()
+        };
+
+        Json(json!({ "data": data, "meta": { "next_cursor": next_cursor } }))
+    }
+
+    /// Pages of 2 items, linked by a `Link` header with `rel="next"` until the last page.
+    async fn linked_items(
+        axum::extract::Host(host): axum::extract::Host,
+        Query(params): Query<HashMap<String, usize>>,
+    ) -> (axum::http::HeaderMap, Json<Value>) {
+        let page = params.get("page").copied().unwrap_or(0);
+        let end = TOTAL_ITEMS.min(page * 2 + 2);
+        let data: Vec<Value> = (page * 2..end).map(|i| json!({ "id": i })).collect();
+
+        let mut headers = axum::http::HeaderMap::new();
+        if end < TOTAL_ITEMS {
+            let link = format!(r#"<http://{host}/linked?page={}>; rel="next""#, page + 1);
+            headers.insert(
+                axum::http::header::LINK,
+                link.parse().expect("valid link header"),
+            );
+        }
+
+        (headers, Json(Value::Array(data)))
+    }
+
+    async fn empty() -> Json<Value> {
+        Json(json!({ "data": { "users": [] } }))
+    }
+
+    async fn null() -> Json<Value> {
+        Json(json!({ "data": { "users": null } }))
+    }
+
+    async fn large_ids() -> Json<Value> {
+        Json(json!([
+            { "id": 9_007_199_254_740_993_u64, "name": "a" },
+            { "id": 18_446_744_073_709_551_615_u64, "name": "b" }
+        ]))
+    }
+
+    async fn ambiguous() -> Json<Value> {
+        Json(json!({
+            "data": {
+                "users": [{ "id": 1 }],
+                "teams": [{ "id": 2 }]
+            }
+        }))
+    }
+
+    async fn errors() -> Json<Value> {
+        Json(json!({
+            "data": null,
+            "errors": [{ "message": "Cannot query field \"users\" on type \"Query\"" }]
+        }))
+    }
+
+    async fn user_agent(headers: axum::http::HeaderMap) -> Json<Value> {
+        let user_agent = headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok());
+        Json(json!([{ "user_agent": user_agent }]))
+    }
+
+    async fn start_mock_server() -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server address");
+        let app = Router::new()
+            .route("/items", get(items))
+            .route("/cursor", get(cursor_items))
+            .route("/linked", get(linked_items))
+            .route("/user_agent", get(user_agent))
+            .route("/empty", get(empty))
+            .route("/null", get(null))
+            .route("/errors", get(errors))
+            .route("/ambiguous", get(ambiguous))
+            .route("/large_ids", get(large_ids));
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("mock server");
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_rest_offset_pagination() {
+        let addr = start_mock_server().await;
+
+        let params = HashMap::from([
+            ("rest_json_pointer".to_string(), "/data".to_string()),
+            ("rest_pagination".to_string(), "offset".to_string()),
+            ("rest_page_size".to_string(), "2".to_string()),
+        ]);
+        let connector = Rest::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+
+        let dataset = Dataset::try_new(format!("rest:http://{addr}/items"), "items")
+            .expect("a valid dataset");
+        let provider = connector
+            .read_provider(&dataset)
+            .await
+            .expect("read provider");
+
+        let schema = provider.schema();
+        assert_eq!(schema.fields().len(), 2);
+        assert!(schema.field_with_name("id").is_ok());
+        assert!(schema.field_with_name("name").is_ok());
+
+        let ctx = SessionContext::new();
+        ctx.register_table("items", provider)
+            .expect("table registered");
+        let batches = ctx
+            .sql("SELECT id, name FROM items ORDER BY id")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect("query executed");
+
+        let num_rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(num_rows, TOTAL_ITEMS);
+    }
+
+    #[tokio::test]
+    async fn test_rest_rate_limit() {
+        let addr = start_mock_server().await;
+
+        let params = HashMap::from([
+            ("rest_json_pointer".to_string(), "/data".to_string()),
+            ("rest_pagination".to_string(), "offset".to_string()),
+            ("rest_page_size".to_string(), "1".to_string()),
+            (
+                "rate_limit_requests_per_second".to_string(),
+                "20".to_string(),
+            ),
+        ]);
+        let connector = Rest::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+        let rest = connector
+            .as_any()
+            .downcast_ref::<Rest>()
+            .expect("a REST connector");
+        let url = Url::parse(&format!("http://{addr}/items")).expect("valid URL");
+
+        // One request per item, plus the empty page that ends the pagination, spaced 50ms apart
+        let start = std::time::Instant::now();
+        let records = rest
+            .request(url)
+            .fetch_records(None)
+            .await
+            .expect("records fetched");
+        assert_eq!(records.len(), TOTAL_ITEMS);
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= std::time::Duration::from_millis(240),
+            "{elapsed:?}"
+        );
+
+        let params = HashMap::from([(
+            "rate_limit_requests_per_second".to_string(),
+            "-1".to_string(),
+        )]);
+        assert!(Rest::create(None, Arc::new(params)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rest_http_debug_logs_redacted_requests() {
+        let (logs, _guard) = crate::test_util::LogBuffer::capture("http_debug=trace");
+
+        let addr = start_mock_server().await;
+        let params = HashMap::from([
+            ("http_debug".to_string(), "true".to_string()),
+            ("rest_token".to_string(), "secret-token".to_string()),
+        ]);
+        let connector = Rest::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+        let rest = connector
+            .as_any()
+            .downcast_ref::<Rest>()
+            .expect("a REST connector");
+
+        let url =
+            Url::parse(&format!("http://{addr}/large_ids?api_key=secret-key")).expect("valid URL");
+        let records = rest
+            .request(url)
+            .fetch_records(None)
+            .await
+            .expect("records fetched");
+        assert_eq!(records.len(), 2);
+
+        let logs = logs.contents();
+        let requests = logs
+            .lines()
+            .filter(|line| line.contains("http_debug") && line.contains("Sending request"))
+            .collect::<Vec<_>>();
+        assert_eq!(requests.len(), 1, "{logs}");
+        assert!(requests[0].contains("/large_ids"), "{logs}");
+        assert!(requests[0].contains("REDACTED"), "{logs}");
+        assert!(logs.contains("Received response"), "{logs}");
+        assert!(logs.contains("200 OK"), "{logs}");
+        assert!(!logs.contains("secret-"), "{logs}");
+    }
+
+    async fn users_provider(
+        addr: std::net::SocketAddr,
+        path: &str,
+        schema: Option<&str>,
+    ) -> DataConnectorResult<Arc<dyn TableProvider>> {
+        let mut params =
+            HashMap::from([("rest_json_pointer".to_string(), "/data/users".to_string())]);
+        if let Some(schema) = schema {
+            params.insert("rest_schema".to_string(), schema.to_string());
+        }
+        let connector = Rest::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+
+        let dataset = Dataset::try_new(format!("rest:http://{addr}{path}"), "users")
+            .expect("a valid dataset");
+        connector.read_provider(&dataset).await
+    }
+
+    #[tokio::test]
+    async fn test_rest_empty_response() {
+        let addr = start_mock_server().await;
+
+        for path in ["/empty", "/null"] {
+            let provider =
+                users_provider(addr, path, Some("CREATE TABLE t (id BIGINT, name TEXT)"))
+                    .await
+                    .expect("read provider");
+            assert_eq!(provider.schema().fields().len(), 2);
+
+            let ctx = SessionContext::new();
+            ctx.register_table("users", provider)
+                .expect("table registered");
+            let batches = ctx
+                .sql("SELECT id, name FROM users")
+                .await
+                .expect("query planned")
+                .collect()
+                .await
+                .expect("query executed");
+            let num_rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+            assert_eq!(num_rows, 0, "{path}");
+
+            let Err(err) = users_provider(addr, path, None).await else {
+                panic!("{path}: no schema to infer");
+            };
+            assert!(err.to_string().contains("rest_schema"), "{path}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rest_errors_in_response() {
+        let addr = start_mock_server().await;
+
+        let Err(err) = users_provider(addr, "/errors", None).await else {
+            panic!("errors fail schema inference");
+        };
+        assert!(
+            err.to_string()
+                .contains(r#"Cannot query field "users" on type "Query""#),
+            "{err}"
+        );
+
+        let provider = users_provider(addr, "/errors", Some("CREATE TABLE t (id BIGINT)"))
+            .await
+            .expect("read provider");
+        let ctx = SessionContext::new();
+        ctx.register_table("users", provider)
+            .expect("table registered");
+        let err = ctx
+            .sql("SELECT id FROM users")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect_err("errors fail the query");
+        assert!(
+            err.to_string()
+                .contains(r#"Cannot query field "users" on type "Query""#),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rest_detects_records_pointer() {
+        let addr = start_mock_server().await;
+
+        let connector = Rest::create(None, Arc::new(HashMap::new()))
+            .await
+            .expect("connector created");
+        let rest = connector
+            .as_any()
+            .downcast_ref::<Rest>()
+            .expect("a REST connector");
+
+        let url = Url::parse(&format!("http://{addr}/items")).expect("valid URL");
+        let records = rest
+            .request(url)
+            .fetch_records(None)
+            .await
+            .expect("records fetched");
+        assert_eq!(records.len(), TOTAL_ITEMS);
+
+        let url = Url::parse(&format!("http://{addr}/ambiguous")).expect("valid URL");
+        let err = rest
+            .request(url)
+            .fetch_records(None)
+            .await
+            .expect_err("multiple arrays of records");
+        assert!(
+            matches!(&err, Error::AmbiguousRecords { candidates, .. }
+                if candidates.contains(&"/data/users".to_string())
+                    && candidates.contains(&"/data/teams".to_string())),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rest_string_columns() {
+        let addr = start_mock_server().await;
+
+        let params = HashMap::from([("rest_string_columns".to_string(), "id".to_string())]);
+        let connector = Rest::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+        let dataset = Dataset::try_new(format!("rest:http://{addr}/large_ids"), "large_ids")
+            .expect("a valid dataset");
+        let provider = connector
+            .read_provider(&dataset)
+            .await
+            .expect("read provider");
+        let schema = provider.schema();
+        let id = schema.field_with_name("id").expect("id column");
+        assert_eq!(id.data_type(), &DataType::Utf8);
+
+        let ctx = SessionContext::new();
+        ctx.register_table("large_ids", provider)
+            .expect("table registered");
+        let batches = ctx
+            .sql("SELECT id FROM large_ids ORDER BY name")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect("query executed");
+        let ids = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow::array::StringArray>()
+            .expect("string ids");
+        assert_eq!(ids.value(0), "9007199254740993");
+        assert_eq!(ids.value(1), "18446744073709551615");
+    }
+
+    #[tokio::test]
+    async fn test_rest_sends_user_agent() {
+        let addr = start_mock_server().await;
+
+        let connector = Rest::create(None, Arc::new(HashMap::new()))
+            .await
+            .expect("connector created");
+        let rest = connector
+            .as_any()
+            .downcast_ref::<Rest>()
+            .expect("a REST connector");
+        let url = Url::parse(&format!("http://{addr}/user_agent")).expect("valid URL");
+
+        let records = rest
+            .request(url)
+            .fetch_records(None)
+            .await
+            .expect("records fetched");
+        assert_eq!(records.len(), 1);
+
+        let user_agent = records[0]["user_agent"]
+            .as_str()
+            .expect("a User-Agent header");
+        assert!(user_agent.starts_with("spiced/"), "got {user_agent}");
+    }
+
+    async fn fetch_ids(
+        addr: std::net::SocketAddr,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> Vec<u64> {
+        let params = params
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect::<HashMap<_, _>>();
+        let connector = Rest::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+        let rest = connector
+            .as_any()
+            .downcast_ref::<Rest>()
+            .expect("a REST connector");
+
+        let url = Url::parse(&format!("http://{addr}{path}")).expect("valid URL");
+        rest.request(url)
+            .fetch_records(None)
+            .await
+            .expect("records fetched")
+            .iter()
+            .filter_map(|record| record["id"].as_u64())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rest_cursor_pagination() {
+        let addr = start_mock_server().await;
+
+        let ids = fetch_ids(
+            addr,
+            "/cursor",
+            &[
+                ("rest_json_pointer", "/data"),
+                ("rest_pagination", "cursor"),
+                ("rest_cursor_pointer", "/meta/next_cursor"),
+            ],
+        )
+        .await;
+        assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_rest_link_header_pagination() {
+        let addr = start_mock_server().await;
+
+        let ids = fetch_ids(addr, "/linked", &[("rest_pagination", "link_header")]).await;
+        assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+
+        // Stops following links after rest_max_pages
+        let ids = fetch_ids(
+            addr,
+            "/linked",
+            &[("rest_pagination", "link_header"), ("rest_max_pages", "2")],
+        )
+        .await;
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_next_link() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            r#"<https://api.example.com/items?page=1>; rel="prev", <https://api.example.com/items?page=3>; rel="next""#
+                .parse()
+                .expect("valid header"),
+        );
+
+        assert_eq!(
+            next_link(&headers).as_ref().map(Url::as_str),
+            Some("https://api.example.com/items?page=3")
+        );
+        assert_eq!(next_link(&HeaderMap::new()), None);
+    }
+}
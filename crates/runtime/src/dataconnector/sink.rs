@@ -0,0 +1,195 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use data_components::arrow::write::MemTable;
+use datafusion::datasource::TableProvider;
+use futures::Future;
+use lazy_static::lazy_static;
+use secrets::Secret;
+use snafu::prelude::*;
+use std::{any::Any, collections::HashMap, pin::Pin, sync::Arc};
+use tokio::sync::Mutex;
+
+use crate::component::dataset::Dataset;
+
+use super::{DataConnector, DataConnectorFactory, DataConnectorResult};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(r#"Missing required parameter "schema": The sink connector requires specifying the schema up-front as a SQL CREATE TABLE statement."#))]
+    MissingSchemaParameter,
+
+    #[snafu(display("Invalid schema: {source}"))]
+    InvalidSchema { source: super::localhost::Error },
+
+    #[snafu(display("Unable to create in-memory table: {source}"))]
+    UnableToCreateMemTable {
+        source: datafusion::error::DataFusionError,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+lazy_static! {
+    /// Tables are kept for the lifetime of the process, so data written to a sink dataset survives
+    /// the connector being re-created (i.e. when the dataset is reloaded).
+    static ref SINK_TABLES: Mutex<HashMap<String, Arc<MemTable>>> = Mutex::new(HashMap::new());
+}
+
+/// An in-memory connector that accepts writes and can be queried back.
+///
+/// Useful for testing `read_write` datasets and replication without a real upstream source.
+#[derive(Debug, Clone)]
+pub struct Sink {
+    schema: SchemaRef,
+}
+
+impl DataConnectorFactory for Sink {
+    fn create(
+        _secret: Option<Secret>,
+        params: Arc<HashMap<String, String>>,
+    ) -> Pin<Box<dyn Future<Output = super::NewDataConnectorResult> + Send>> {
+        Box::pin(async move {
+            let schema = params.get("schema").ok_or(Error::MissingSchemaParameter)?;
+            let schema =
+                super::localhost::schema_from_create_table(schema).context(InvalidSchemaSnafu)?;
+
+            Ok(Arc::new(Sink { schema }) as Arc<dyn DataConnector>)
+        })
+    }
+}
+
+impl Sink {
+    async fn table(&self, dataset: &Dataset) -> Result<Arc<MemTable>> {
+        let mut tables = SINK_TABLES.lock().await;
+        let key = dataset.name.to_string();
+
+        if let Some(table) = tables.get(&key) {
+            if table.schema() == self.schema {
+                return Ok(Arc::clone(table));
+            }
+            tracing::debug!("Schema for sink dataset {key} changed, discarding existing data");
+        }
+
+        let table = Arc::new(
+            MemTable::try_new(Arc::clone(&self.schema), vec![])
+                .context(UnableToCreateMemTableSnafu)?,
+        );
+        tables.insert(key, Arc::clone(&table));
+
+        Ok(table)
+    }
+}
+
+#[async_trait]
+impl DataConnector for Sink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn read_provider(
+        &self,
+        dataset: &Dataset,
+    ) -> DataConnectorResult<Arc<dyn TableProvider>> {
+        let table =
+            self.table(dataset)
+                .await
+                .boxed()
+                .context(super::UnableToGetReadProviderSnafu {
+                    dataconnector: "sink",
+                })?;
+
+        Ok(table)
+    }
+
+    async fn read_write_provider(
+        &self,
+        dataset: &Dataset,
+    ) -> Option<DataConnectorResult<Arc<dyn TableProvider>>> {
+        let table = self
+            .table(dataset)
+            .await
+            .map(|table| table as Arc<dyn TableProvider>)
+            .boxed()
+            .context(super::UnableToGetReadWriteProviderSnafu {
+                dataconnector: "sink",
+            });
+
+        Some(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_sink_write_then_read() {
+        let params = HashMap::from([(
+            "schema".to_string(),
+            "CREATE TABLE t (id BIGINT, name TEXT)".to_string(),
+        )]);
+        let connector = Sink::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+
+        let dataset =
+            Dataset::try_new("sink".to_string(), "sink_write_then_read").expect("a valid dataset");
+
+        let write_provider = connector
+            .read_write_provider(&dataset)
+            .await
+            .expect("read write provider")
+            .expect("read write provider created");
+
+        let ctx = SessionContext::new();
+        ctx.register_table("sink_write", write_provider)
+            .expect("table registered");
+        ctx.sql("INSERT INTO sink_write VALUES (1, 'a'), (2, 'b'), (3, 'c')")
+            .await
+            .expect("insert planned")
+            .collect()
+            .await
+            .expect("insert executed");
+
+        let read_provider = connector
+            .read_provider(&dataset)
+            .await
+            .expect("read provider");
+        ctx.register_table("sink_read", read_provider)
+            .expect("table registered");
+
+        let batches = ctx
+            .sql("SELECT sum(id) FROM sink_read")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect("query executed");
+
+        let sum = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .expect("sum is an Int64Array")
+            .value(0);
+        assert_eq!(sum, 6);
+    }
+}
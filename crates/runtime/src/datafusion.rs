@@ -15,18 +15,25 @@ limitations under the License.
 */
 
 use std::borrow::Borrow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use crate::accelerated_table::{refresh::Refresh, AcceleratedTable, Retention};
+use crate::accelerated_table::{
+    refresh::{Refresh, RefreshStats},
+    AcceleratedTable, Retention,
+};
 use crate::component::dataset::{Dataset, Mode};
 use crate::dataaccelerator::{self, create_accelerator_table};
 use crate::dataconnector::{DataConnector, DataConnectorError};
 use crate::dataupdate::{DataUpdate, DataUpdateExecutionPlan, UpdateType};
 use crate::get_dependent_table_names;
-use crate::object_store_registry::default_runtime_env;
+use crate::object_store_registry::runtime_env;
 
+use arrow::array::timezone::Tz;
 use arrow::datatypes::Schema;
 use arrow_tools::schema::verify_schema;
 use cache::QueryResultsCacheProvider;
@@ -35,6 +42,8 @@ use datafusion::catalog::{CatalogProvider, MemoryCatalogProvider};
 use datafusion::datasource::{TableProvider, ViewTable};
 use datafusion::error::DataFusionError;
 use datafusion::execution::context::{SessionConfig, SessionContext, SessionState};
+use datafusion::execution::runtime_env::RuntimeEnv;
+use datafusion::logical_expr::{LogicalPlan, ScalarUDF};
 use datafusion::physical_plan::collect;
 use datafusion::sql::parser::DFParser;
 use datafusion::sql::sqlparser::dialect::PostgreSqlDialect;
@@ -50,16 +59,65 @@ use tokio::time::{sleep, Instant};
 pub mod query;
 
 pub mod filter_converter;
+pub mod plan_cache;
 pub mod refresh_sql;
+pub mod rollup;
 pub mod schema;
+pub mod vector_distance;
 
+use self::plan_cache::PlanCache;
 use self::schema::SpiceSchemaProvider;
+use self::vector_distance::VectorDistance;
 
 pub const SPICE_DEFAULT_CATALOG: &str = "spice";
 pub const SPICE_RUNTIME_SCHEMA: &str = "runtime";
 pub const SPICE_DEFAULT_SCHEMA: &str = "public";
 pub const SPICE_METADATA_SCHEMA: &str = "metadata";
 
+/// Maximum number of bytes that queries may use, 0 for no limit.
+static MAX_MEMORY: AtomicUsize = AtomicUsize::new(0);
+
+/// Bounds the memory of queries run by `DataFusion` instances created afterwards, so that large sorts, joins and
+/// aggregations spill to disk or fail with a resources exhausted error instead of running the process out of memory.
+pub fn set_max_memory(max_memory: usize) {
+    MAX_MEMORY.store(max_memory, Ordering::Relaxed);
+}
+
+/// Directory that queries spill to, the OS temp directory if it isn't set.
+static TEMP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Subdirectory of the temp directory that spiced spills to. The temp directory may be shared with other processes,
+/// so only this subdirectory is cleaned up.
+const SPILL_DIR: &str = "spiced-spill";
+
+/// Sets the directory that `DataFusion` instances created afterwards spill query data to, removing spill files
+/// left behind by a previous run that didn't shut down cleanly.
+///
+/// # Errors
+///
+/// Returns an error if the spill directory in `temp_dir` can't be created or cleaned up.
+pub fn set_temp_dir(temp_dir: &Path) -> Result<()> {
+    let spill_dir = prepare_spill_dir(temp_dir)?;
+    if let Ok(mut dir) = TEMP_DIR.write() {
+        *dir = Some(spill_dir);
+    }
+    Ok(())
+}
+
+/// Creates the spill directory in `temp_dir`, emptying it if a previous run left spill files behind.
+fn prepare_spill_dir(temp_dir: &Path) -> Result<PathBuf> {
+    let spill_dir = temp_dir.join(SPILL_DIR);
+    let context = || UnableToPrepareTempDirSnafu {
+        path: spill_dir.display().to_string(),
+    };
+    if spill_dir.exists() {
+        tracing::debug!("Removing stale spill files in {}", spill_dir.display());
+        std::fs::remove_dir_all(&spill_dir).with_context(|_| context())?;
+    }
+    std::fs::create_dir_all(&spill_dir).with_context(|_| context())?;
+    Ok(spill_dir)
+}
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug, Snafu)]
@@ -67,6 +125,12 @@ pub enum Error {
     #[snafu(display("Table already exists"))]
     TableAlreadyExists {},
 
+    #[snafu(display("Unable to prepare the temp directory {path} for spill files: {source}"))]
+    UnableToPrepareTempDir {
+        path: String,
+        source: std::io::Error,
+    },
+
     #[snafu(display("Unable to create dataset acceleration: {source}"))]
     UnableToCreateDataAccelerator { source: dataaccelerator::Error },
 
@@ -84,6 +148,9 @@ pub enum Error {
     #[snafu(display("{source}"))]
     RefreshSql { source: refresh_sql::Error },
 
+    #[snafu(display("{source}"))]
+    Rollup { source: rollup::Error },
+
     #[snafu(display("Unable to get table: {source}"))]
     UnableToGetTable { source: DataFusionError },
 
@@ -119,7 +186,9 @@ pub enum Error {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
-    #[snafu(display("The table {table_name} is not writable"))]
+    #[snafu(display(
+        "The table {table_name} is not writable. Datasets only accept writes with mode: read_write"
+    ))]
     TableNotWritable { table_name: String },
 
     #[snafu(display("Unable to plan the table insert for {table_name}: {source}"))]
@@ -168,6 +237,21 @@ pub enum Error {
 
     #[snafu(display("Unable to get the lock of data writers"))]
     UnableToLockDataWriters {},
+
+    #[snafu(display("Invalid session timezone {timezone}: {source}"))]
+    InvalidSessionTimezone {
+        timezone: String,
+        source: arrow::error::ArrowError,
+    },
+
+    #[snafu(display("Unable to set the session timezone: {source}"))]
+    UnableToSetSessionTimezone { source: DataFusionError },
+
+    #[snafu(display("Query parallelism must be at least 1"))]
+    InvalidQueryParallelism {},
+
+    #[snafu(display("Unable to set the query parallelism: {source}"))]
+    UnableToSetQueryParallelism { source: DataFusionError },
 }
 
 pub enum Table {
@@ -184,6 +268,12 @@ pub struct DataFusion {
     pub ctx: Arc<SessionContext>,
     data_writers: RwLock<HashSet<TableReference>>,
     pub cache_provider: RwLock<Option<Arc<QueryResultsCacheProvider>>>,
+    plan_cache: RwLock<Option<Arc<PlanCache>>>,
+    slow_query_threshold: RwLock<Option<Duration>>,
+    /// `results_cache_ttl` of the datasets that set one, by lowercased dataset name.
+    results_cache_ttls: RwLock<HashMap<String, Duration>>,
+    /// Incremented whenever a table is registered or removed, to invalidate cached plans.
+    schema_version: AtomicU64,
 }
 
 impl DataFusion {
@@ -199,6 +289,20 @@ impl DataFusion {
     /// Panics if the default schema cannot be registered.
     #[must_use]
     pub fn new_with_cache_provider(cache_provider: Option<Arc<QueryResultsCacheProvider>>) -> Self {
+        let max_memory = MAX_MEMORY.load(Ordering::Relaxed);
+        Self::new_with_runtime_env(
+            cache_provider,
+            runtime_env(
+                (max_memory > 0).then_some(max_memory),
+                TEMP_DIR.read().ok().and_then(|dir| dir.clone()),
+            ),
+        )
+    }
+
+    fn new_with_runtime_env(
+        cache_provider: Option<Arc<QueryResultsCacheProvider>>,
+        runtime_env: Arc<RuntimeEnv>,
+    ) -> Self {
         let mut df_config = SessionConfig::new()
             .with_information_schema(true)
             .with_create_default_catalog_and_schema(false)
@@ -210,11 +314,12 @@ impl DataFusion {
         df_config.options_mut().catalog.default_catalog = SPICE_DEFAULT_CATALOG.to_string();
         df_config.options_mut().catalog.default_schema = SPICE_DEFAULT_SCHEMA.to_string();
 
-        let state = SessionState::new_with_config_rt(df_config, default_runtime_env())
+        let state = SessionState::new_with_config_rt(df_config, runtime_env)
             .add_analyzer_rule(Arc::new(FederationAnalyzerRule::new()))
             .with_query_planner(Arc::new(FederatedQueryPlanner::new()));
 
         let ctx = SessionContext::new_with_state(state);
+        ctx.register_udf(ScalarUDF::from(VectorDistance::new()));
 
         let catalog = MemoryCatalogProvider::new();
         let default_schema = SpiceSchemaProvider::new();
@@ -248,6 +353,10 @@ impl DataFusion {
             ctx: Arc::new(ctx),
             data_writers: RwLock::new(HashSet::new()),
             cache_provider: RwLock::new(cache_provider),
+            plan_cache: RwLock::new(None),
+            slow_query_threshold: RwLock::new(None),
+            results_cache_ttls: RwLock::new(HashMap::new()),
+            schema_version: AtomicU64::new(0),
         }
     }
 
@@ -275,6 +384,118 @@ impl DataFusion {
         };
     }
 
+    pub fn set_plan_cache(&self, plan_cache: PlanCache) {
+        if let Ok(mut a) = self.plan_cache.write() {
+            *a = Some(Arc::new(plan_cache));
+        };
+    }
+
+    /// Logs queries that take longer than `threshold` to the `slow_query` target.
+    pub fn set_slow_query_threshold(&self, threshold: Duration) {
+        if let Ok(mut a) = self.slow_query_threshold.write() {
+            *a = Some(threshold);
+        };
+    }
+
+    #[must_use]
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_threshold.read().ok().and_then(|a| *a)
+    }
+
+    /// How long to cache the results of a query reading `datasets`: the shortest `results_cache_ttl` among them,
+    /// where datasets that don't set one use `default_ttl`. Dataset names are resolved against the default catalog
+    /// and schema, as queries may refer to `t` as `public.t`.
+    #[must_use]
+    #[allow(clippy::implicit_hasher)]
+    pub fn results_cache_ttl(&self, datasets: &HashSet<String>, default_ttl: Duration) -> Duration {
+        let Ok(ttls) = self.results_cache_ttls.read() else {
+            return default_ttl;
+        };
+
+        datasets
+            .iter()
+            .map(|dataset| {
+                let key = results_cache_ttl_key(TableReference::from(dataset.as_str()));
+                ttls.get(&key).copied().unwrap_or(default_ttl)
+            })
+            .min()
+            .unwrap_or(default_ttl)
+    }
+
+    fn set_results_cache_ttl(&self, dataset_name: &TableReference, ttl: Option<Duration>) {
+        let Ok(mut ttls) = self.results_cache_ttls.write() else {
+            return;
+        };
+
+        let dataset_name = results_cache_ttl_key(dataset_name.clone());
+        match ttl {
+            Some(ttl) => ttls.insert(dataset_name, ttl),
+            None => ttls.remove(&dataset_name),
+        };
+    }
+
+    /// Sets the time zone that timestamps with a time zone are rendered in for all queries. The instants themselves,
+    /// i.e. the result of `now()`, are unchanged.
+    pub async fn set_session_timezone(&self, timezone: &str) -> Result<()> {
+        Tz::from_str(timezone).context(InvalidSessionTimezoneSnafu { timezone })?;
+        self.ctx
+            .sql(&format!(
+                "SET datafusion.execution.time_zone = '{timezone}'"
+            ))
+            .await
+            .context(UnableToSetSessionTimezoneSnafu)?;
+        Ok(())
+    }
+
+    /// Sets the number of partitions that scans, joins and aggregations are split into to run in parallel,
+    /// which defaults to the number of CPUs.
+    pub async fn set_query_parallelism(&self, parallelism: usize) -> Result<()> {
+        ensure!(parallelism > 0, InvalidQueryParallelismSnafu);
+        self.ctx
+            .sql(&format!(
+                "SET datafusion.execution.target_partitions = {parallelism}"
+            ))
+            .await
+            .context(UnableToSetQueryParallelismSnafu)?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn plan_cache(&self) -> Option<Arc<PlanCache>> {
+        self.plan_cache
+            .read()
+            .ok()
+            .and_then(|a| a.as_ref().map(Arc::clone))
+    }
+
+    #[must_use]
+    pub fn schema_version(&self) -> u64 {
+        self.schema_version.load(Ordering::SeqCst)
+    }
+
+    fn schema_changed(&self) {
+        self.schema_version.fetch_add(1, Ordering::SeqCst);
+        if let Some(plan_cache) = self.plan_cache() {
+            plan_cache.invalidate_all();
+        }
+    }
+
+    /// Creates the logical plan for `sql`, reusing a previously created plan if the plan cache is enabled.
+    pub async fn logical_plan(&self, sql: &str) -> Result<LogicalPlan, DataFusionError> {
+        let Some(plan_cache) = self.plan_cache() else {
+            return self.ctx.state().create_logical_plan(sql).await;
+        };
+
+        let schema_version = self.schema_version();
+        if let Some(plan) = plan_cache.get(sql, schema_version) {
+            return Ok(plan);
+        }
+
+        let plan = self.ctx.state().create_logical_plan(sql).await?;
+        plan_cache.insert(sql, schema_version, &plan);
+        Ok(plan)
+    }
+
     pub async fn has_table(&self, table_reference: &TableReference) -> bool {
         let table_name = table_reference.table();
 
@@ -311,6 +532,7 @@ impl DataFusion {
                 .write()
                 .map_err(|_| Error::UnableToLockDataWriters {})?
                 .insert(table_name);
+            self.schema_changed();
         }
 
         Ok(())
@@ -335,11 +557,10 @@ impl DataFusion {
                     self.ctx
                         .register_table(dataset.name.clone(), Arc::new(accelerated_table))
                         .context(UnableToRegisterTableToDataFusionSnafu)?;
-
-                    return Ok(());
+                } else {
+                    self.register_accelerated_table(dataset, source, acceleration_secret)
+                        .await?;
                 }
-                self.register_accelerated_table(dataset, source, acceleration_secret)
-                    .await?;
             }
             Table::Federated(source) => self.register_federated_table(dataset, source).await?,
             Table::View(sql) => self.register_view(dataset.name.clone(), sql)?,
@@ -351,19 +572,41 @@ impl DataFusion {
                 .map_err(|_| Error::UnableToLockDataWriters {})?
                 .insert(dataset.name.clone());
         }
+        self.set_results_cache_ttl(&dataset.name, dataset.results_cache_ttl());
 
+        self.schema_changed();
         Ok(())
     }
 
+    /// Whether the table is a `read_write` dataset. The reference is resolved against the default catalog and
+    /// schema, so `t`, `public.t` and `spice.public.t` all refer to the same dataset.
     #[must_use]
     pub fn is_writable(&self, table_reference: &TableReference) -> bool {
+        let resolve = |table_reference: &TableReference| {
+            table_reference
+                .clone()
+                .resolve(SPICE_DEFAULT_CATALOG, SPICE_DEFAULT_SCHEMA)
+        };
+        let table_reference = resolve(table_reference);
         if let Ok(writers) = self.data_writers.read() {
-            writers.iter().any(|s| s == table_reference)
+            writers.iter().any(|s| resolve(s) == table_reference)
         } else {
             false
         }
     }
 
+    /// Rejects writes to tables that aren't writable, i.e. datasets in `read` mode, regardless of whether their
+    /// table provider would accept the insert.
+    pub fn ensure_writable(&self, table_reference: &TableReference) -> Result<()> {
+        ensure!(
+            self.is_writable(table_reference),
+            TableNotWritableSnafu {
+                table_name: table_reference.to_string(),
+            }
+        );
+        Ok(())
+    }
+
     async fn get_table_provider(
         &self,
         table_reference: &TableReference,
@@ -404,12 +647,7 @@ impl DataFusion {
         table_reference: TableReference,
         data_update: DataUpdate,
     ) -> Result<()> {
-        if !self.is_writable(&table_reference) {
-            TableNotWritableSnafu {
-                table_name: table_reference.to_string(),
-            }
-            .fail()?;
-        }
+        self.ensure_writable(&table_reference)?;
 
         let table_provider = self.get_table_provider(&table_reference).await?;
 
@@ -472,7 +710,9 @@ impl DataFusion {
                 .map_err(|_| Error::UnableToLockDataWriters {})?
                 .remove(dataset_name);
         }
+        self.set_results_cache_ttl(dataset_name, None);
 
+        self.schema_changed();
         Ok(())
     }
 
@@ -500,7 +740,6 @@ impl DataFusion {
                 .context(UnableToResolveTableProviderSnafu)?,
         };
 
-        let source_schema = source_table_provider.schema();
         let acceleration_settings =
             dataset
                 .acceleration
@@ -509,9 +748,23 @@ impl DataFusion {
                     name: dataset.name.to_string(),
                 })?;
 
+        // A rollup is accelerated with the schema of the aggregated rows, rather than the source's
+        let accelerator_schema = match &acceleration_settings.rollup {
+            Some(rollup) => rollup::rollup_schema(
+                &dataset.name,
+                rollup,
+                &acceleration_settings,
+                dataset.mode(),
+                Arc::clone(&source_table_provider),
+            )
+            .await
+            .context(RollupSnafu)?,
+            None => source_table_provider.schema(),
+        };
+
         let accelerated_table_provider = create_accelerator_table(
             dataset.name.clone(),
-            source_schema,
+            accelerator_schema,
             &acceleration_settings,
             acceleration_secret,
         )
@@ -522,6 +775,12 @@ impl DataFusion {
         if let Some(refresh_sql) = &refresh_sql {
             refresh_sql::validate_refresh_sql(dataset.name.clone(), refresh_sql.as_str())
                 .context(RefreshSqlSnafu)?;
+            refresh_sql::check_table_references(
+                &dataset.name,
+                refresh_sql,
+                acceleration_settings.refresh_sql_reference_check,
+            )
+            .context(RefreshSqlSnafu)?;
         }
 
         let mut accelerated_table_builder = AcceleratedTable::builder(
@@ -530,16 +789,20 @@ impl DataFusion {
             accelerated_table_provider,
             Refresh::new(
                 dataset.time_column.clone(),
-                dataset.time_format,
+                dataset.time_format.clone(),
                 dataset.refresh_check_interval(),
                 refresh_sql.clone(),
                 acceleration_settings.refresh_mode,
                 dataset.refresh_data_window(),
-            ),
+            )
+            .initial_backfill_window(dataset.refresh_initial_backfill_window())
+            .append_dedup_rows(acceleration_settings.refresh_append_dedup_rows)
+            .append_boundary_inclusive(acceleration_settings.refresh_append_boundary_inclusive)
+            .rollup(acceleration_settings.rollup.clone()),
         );
         accelerated_table_builder.retention(Retention::new(
             dataset.time_column.clone(),
-            dataset.time_format,
+            dataset.time_format.clone(),
             dataset.retention_period(),
             dataset.retention_check_interval(),
             acceleration_settings.retention_check_enabled,
@@ -549,6 +812,16 @@ impl DataFusion {
 
         accelerated_table_builder.cache_provider(self.cache_provider());
 
+        accelerated_table_builder.refresh_write_semaphore(
+            dataaccelerator::refresh_write_semaphore(&dataset.name, &acceleration_settings).await,
+        );
+
+        // Only replicated read_write datasets are written to the source, for others there's no replication to lag
+        accelerated_table_builder.replication_lag(
+            dataset.mode() == Mode::ReadWrite
+                && dataset.replication.as_ref().is_some_and(|r| r.enabled),
+        );
+
         Ok(accelerated_table_builder.build().await)
     }
 
@@ -604,6 +877,29 @@ impl DataFusion {
         Ok(())
     }
 
+    /// Triggers a refresh of an accelerated table and waits for the refreshed data to be loaded.
+    pub async fn refresh_table_and_wait(&self, dataset_name: &str) -> Result<RefreshStats> {
+        let table = self
+            .ctx
+            .table_provider(TableReference::bare(dataset_name.to_string()))
+            .await
+            .context(UnableToGetTableSnafu)?;
+
+        let Some(accelerated_table) = table.as_any().downcast_ref::<AcceleratedTable>() else {
+            return NotAcceleratedTableSnafu {
+                table_name: dataset_name.to_string(),
+            }
+            .fail();
+        };
+
+        accelerated_table
+            .refresh_and_wait()
+            .await
+            .context(UnableToTriggerRefreshSnafu {
+                table_name: dataset_name.to_string(),
+            })
+    }
+
     pub async fn update_refresh_sql(
         &self,
         dataset_name: TableReference,
@@ -803,3 +1099,173 @@ impl Default for DataFusion {
         Self::new()
     }
 }
+
+/// Key of the results cache TTL of `dataset`, the same whether the dataset is referred to by a bare or qualified name.
+fn results_cache_ttl_key(dataset: TableReference) -> String {
+    dataset
+        .resolve(SPICE_DEFAULT_CATALOG, SPICE_DEFAULT_SCHEMA)
+        .to_string()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{AsArray, Int64Array, RecordBatch},
+        datatypes::{DataType, Field, Int64Type},
+    };
+    use datafusion::datasource::MemTable;
+    use futures::StreamExt;
+
+    fn files_in(dir: &Path) -> usize {
+        std::fs::read_dir(dir)
+            .expect("dir is readable")
+            .map(|entry| {
+                let path = entry.expect("valid entry").path();
+                if path.is_dir() {
+                    files_in(&path)
+                } else {
+                    1
+                }
+            })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_spills_to_temp_dir() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("spice_spill_test_{}", std::process::id()));
+        let stale_dir = temp_dir.join(SPILL_DIR).join("datafusion-stale");
+        std::fs::create_dir_all(&stale_dir).expect("stale dir created");
+        std::fs::write(stale_dir.join("spill"), b"stale").expect("stale file written");
+        // Spill files of another process sharing the temp dir
+        let other_dir = temp_dir.join("datafusion-other");
+        std::fs::create_dir_all(&other_dir).expect("other dir created");
+        std::fs::write(other_dir.join("spill"), b"other").expect("other file written");
+
+        let spill_dir = prepare_spill_dir(&temp_dir).expect("spill dir prepared");
+        assert!(!stale_dir.exists(), "stale spill files are removed");
+        assert!(
+            other_dir.join("spill").exists(),
+            "other spill files are kept"
+        );
+
+        let df = DataFusion::new_with_runtime_env(
+            None,
+            runtime_env(Some(4 * 1024 * 1024), Some(spill_dir.clone())),
+        );
+        for setting in [
+            "SET datafusion.execution.target_partitions = 1",
+            "SET datafusion.execution.sort_spill_reservation_bytes = 1048576",
+        ] {
+            df.ctx.sql(setting).await.expect("setting applied");
+        }
+
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int64, false)]));
+        let batches = (0..128)
+            .map(|i| {
+                RecordBatch::try_new(
+                    Arc::clone(&schema),
+                    vec![Arc::new(Int64Array::from_iter_values(
+                        i * 8192..(i + 1) * 8192,
+                    ))],
+                )
+                .expect("data should be created")
+            })
+            .collect();
+        let table = MemTable::try_new(schema, vec![batches]).expect("mem table created");
+        df.ctx
+            .register_table("t", Arc::new(table))
+            .expect("table registered");
+
+        let mut stream = df
+            .ctx
+            .sql("SELECT x FROM t ORDER BY x DESC")
+            .await
+            .expect("query planned")
+            .execute_stream()
+            .await
+            .expect("query started");
+        let first = stream
+            .next()
+            .await
+            .expect("a batch")
+            .expect("query executed");
+        assert_eq!(
+            first.column(0).as_primitive::<Int64Type>().value(0),
+            128 * 8192 - 1
+        );
+        assert!(files_in(&spill_dir) > 0, "the sort spills to the spill dir");
+
+        drop(stream);
+        drop(df);
+        assert_eq!(files_in(&spill_dir), 0, "spill files are removed");
+
+        std::fs::remove_dir_all(&temp_dir).expect("temp dir removed");
+    }
+
+    #[tokio::test]
+    async fn test_query_parallelism() {
+        let df = DataFusion::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(Int64Array::from(vec![1, 2, 2, 3]))],
+        )
+        .expect("data should be created");
+        let table = MemTable::try_new(schema, vec![vec![batch]]).expect("mem table created");
+        df.ctx
+            .register_table("t", Arc::new(table))
+            .expect("table registered");
+
+        for parallelism in [3, 5] {
+            df.set_query_parallelism(parallelism)
+                .await
+                .expect("parallelism set");
+            let plan = df
+                .ctx
+                .sql("SELECT x, count(*) FROM t GROUP BY x")
+                .await
+                .expect("query planned")
+                .create_physical_plan()
+                .await
+                .expect("physical plan created");
+            assert_eq!(plan.output_partitioning().partition_count(), parallelism);
+        }
+
+        assert!(matches!(
+            df.set_query_parallelism(0).await,
+            Err(Error::InvalidQueryParallelism {})
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_max_memory_fails_query_gracefully() {
+        let df = DataFusion::new_with_runtime_env(None, runtime_env(Some(1024), None));
+
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(Int64Array::from_iter_values(0..100_000))],
+        )
+        .expect("data should be created");
+        let table = MemTable::try_new(schema, vec![vec![batch]]).expect("mem table created");
+        df.ctx
+            .register_table("t", Arc::new(table))
+            .expect("table registered");
+
+        let err = df
+            .ctx
+            .sql("SELECT a.x FROM t a JOIN t b ON a.x = b.x")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect_err("the join exceeds the memory limit");
+        assert!(
+            err.to_string().contains("Resources exhausted"),
+            "unexpected error: {err}"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::component::dataset::TimeFormat;
 use arrow::datatypes::DataType;
 use datafusion::{
+    functions::expr_fn::to_timestamp_millis,
     logical_expr::{binary_expr, cast, col, lit, Expr, Operator},
     scalar::ScalarValue,
 };
@@ -8,6 +9,8 @@ use datafusion::{
 #[derive(Debug, Clone)]
 enum ExprTimeFormat {
     ISO8601,
+    /// A string column parsed with a chrono `strftime` pattern.
+    Custom(String),
     UnixTimestamp(ExprUnixTimestamp),
     Timestamp,
 }
@@ -58,7 +61,10 @@ impl TimestampFilterConvert {
             | DataType::Date64
             | DataType::Time32(_)
             | DataType::Time64(_) => ExprTimeFormat::Timestamp,
-            DataType::Utf8 | DataType::LargeUtf8 => ExprTimeFormat::ISO8601,
+            DataType::Utf8 | DataType::LargeUtf8 => match time_format {
+                Some(TimeFormat::Custom(pattern)) => ExprTimeFormat::Custom(pattern),
+                _ => ExprTimeFormat::ISO8601,
+            },
             _ => {
                 tracing::warn!("Date type is not handled yet: {}", field.data_type());
                 return None;
@@ -87,6 +93,14 @@ impl TimestampFilterConvert {
                     None,
                 )),
             ),
+            ExprTimeFormat::Custom(pattern) => binary_expr(
+                to_timestamp_millis(vec![col(time_column), lit(pattern.as_str())]),
+                op,
+                Expr::Literal(ScalarValue::TimestampMillisecond(
+                    Some((timestamp_in_nanos / 1_000_000) as i64),
+                    None,
+                )),
+            ),
             ExprTimeFormat::UnixTimestamp(format) => binary_expr(
                 col(time_column),
                 op,
@@ -107,7 +121,12 @@ impl TimestampFilterConvert {
 #[cfg(test)]
 mod test {
     use super::*;
-    use arrow::datatypes::{DataType, Field, TimeUnit};
+    use arrow::{
+        array::{RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema, TimeUnit},
+    };
+    use datafusion::execution::context::SessionContext;
+    use std::sync::Arc;
 
     #[test]
     fn test_timestamp_filter_convert() {
@@ -145,6 +164,58 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_custom_time_format_filter() {
+        let field = Field::new("timestamp", DataType::Utf8, false);
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![field.clone()])),
+            vec![Arc::new(StringArray::from(vec![
+                "04/15/2024 10:30",
+                "04/15/2024 12:45",
+            ]))],
+        )
+        .expect("valid record batch");
+        let ctx = SessionContext::new();
+        ctx.register_batch("events", batch)
+            .expect("table registered");
+
+        let timestamp_filter_convert = TimestampFilterConvert::create(
+            Some(field),
+            Some("timestamp".to_string()),
+            Some(TimeFormat::Custom("%m/%d/%Y %H:%M".to_string())),
+        )
+        .expect("the convert can be created");
+        // 2024-04-15T11:00:00Z
+        let expr = timestamp_filter_convert.convert(1_713_178_800_000_000_000, Operator::Gt);
+
+        let batches = ctx
+            .table("events")
+            .await
+            .expect("table exists")
+            .filter(expr)
+            .expect("filter applied")
+            .collect()
+            .await
+            .expect("query executed");
+
+        let timestamps = batches
+            .iter()
+            .flat_map(|batch| {
+                let column = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .expect("string column");
+                column
+                    .iter()
+                    .flatten()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, vec!["04/15/2024 12:45".to_string()]);
+    }
+
     fn test(field: Field, time_format: TimeFormat, timestamp: u128, expected: &str) {
         let time_column = "timestamp".to_string();
         let timestamp_filter_convert =
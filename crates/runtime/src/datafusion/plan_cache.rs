@@ -0,0 +1,245 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use datafusion::{
+    common::tree_node::{TreeNode, TreeNodeRecursion},
+    logical_expr::{Expr, LogicalPlan, ScalarFunctionDefinition, Volatility},
+};
+
+pub const DEFAULT_MAX_ENTRIES: u64 = 1024;
+
+/// Caches logical plans of SQL queries, so that dashboards repeatedly issuing the same query skip parsing and planning.
+///
+/// Plans are keyed by the normalized SQL and the schema version of the catalog they were planned against,
+/// so a plan is never reused once a dataset has been registered, replaced or removed.
+pub struct PlanCache {
+    plans: moka::sync::Cache<(String, u64), LogicalPlan>,
+}
+
+impl PlanCache {
+    #[must_use]
+    pub fn new(max_entries: u64) -> Self {
+        Self {
+            plans: moka::sync::Cache::new(max_entries),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, sql: &str, schema_version: u64) -> Option<LogicalPlan> {
+        let plan = self.plans.get(&(normalize_sql(sql), schema_version));
+        if plan.is_some() {
+            metrics::counter!("plan_cache_hits_total").increment(1);
+        }
+        plan
+    }
+
+    /// Caches `plan`, unless it can't be reused, i.e. because it calls functions like `now()` or `random()`.
+    pub fn insert(&self, sql: &str, schema_version: u64, plan: &LogicalPlan) {
+        if is_cacheable(plan) {
+            self.plans
+                .insert((normalize_sql(sql), schema_version), plan.clone());
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        self.plans.invalidate_all();
+    }
+}
+
+/// Collapses whitespace outside of quoted strings and identifiers, and drops a trailing `;`.
+fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    let mut pending_space = false;
+
+    for c in sql.trim().trim_end_matches(';').trim_end().chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => {
+                pending_space = true;
+                continue;
+            }
+            None => {
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+            }
+        }
+
+        if pending_space {
+            normalized.push(' ');
+            pending_space = false;
+        }
+        normalized.push(c);
+    }
+
+    normalized
+}
+
+fn is_cacheable(plan: &LogicalPlan) -> bool {
+    let mut plan_stack = vec![plan];
+
+    while let Some(current_plan) = plan_stack.pop() {
+        match current_plan {
+            LogicalPlan::Ddl(..)
+            | LogicalPlan::Dml(..)
+            | LogicalPlan::Copy(..)
+            | LogicalPlan::Statement(..) => return false,
+            _ => {}
+        }
+
+        if current_plan
+            .expressions()
+            .iter()
+            .any(calls_non_immutable_function)
+        {
+            return false;
+        }
+
+        plan_stack.extend(current_plan.inputs());
+    }
+
+    true
+}
+
+/// Whether `expr` calls a function whose result may differ between executions, like `now()` or `random()`.
+fn calls_non_immutable_function(expr: &Expr) -> bool {
+    let mut found = false;
+    let _ = expr.apply(&mut |expr| {
+        if let Expr::ScalarFunction(function) = expr {
+            let volatility = match &function.func_def {
+                ScalarFunctionDefinition::BuiltIn(function) => Some(function.volatility()),
+                ScalarFunctionDefinition::UDF(udf) => Some(udf.signature().volatility),
+                ScalarFunctionDefinition::Name(_) => None,
+            };
+            if volatility != Some(Volatility::Immutable) {
+                found = true;
+                return Ok(TreeNodeRecursion::Stop);
+            }
+        }
+        Ok(TreeNodeRecursion::Continue)
+    });
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datafusion::{
+        query::{Protocol, QueryBuilder},
+        DataFusion,
+    };
+    use arrow::{
+        array::{Int64Array, RecordBatch},
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::{datasource::MemTable, sql::TableReference};
+    use futures::TryStreamExt;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use std::sync::Arc;
+
+    fn table() -> Arc<MemTable> {
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(Int64Array::from(vec![1, 2, 3]))],
+        )
+        .expect("data should be created");
+        Arc::new(MemTable::try_new(schema, vec![vec![batch]]).expect("mem table should be created"))
+    }
+
+    #[test]
+    fn test_normalize_sql() {
+        assert_eq!(
+            normalize_sql("  SELECT *\n  FROM   t ;"),
+            normalize_sql("SELECT * FROM t")
+        );
+        assert_ne!(
+            normalize_sql("SELECT * FROM t WHERE name = 'a  b'"),
+            normalize_sql("SELECT * FROM t WHERE name = 'a b'")
+        );
+    }
+
+    #[test]
+    fn test_plan_cache_hit_and_invalidation() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let hits = || {
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .find_map(|(key, _, _, value)| match value {
+                    DebugValue::Counter(value) if key.key().name() == "plan_cache_hits_total" => {
+                        Some(value)
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default()
+        };
+
+        // The local recorder is only visible on this thread, so the queries run on a current-thread runtime
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime should be created");
+            runtime.block_on(async {
+                let df = Arc::new(DataFusion::new());
+                df.set_plan_cache(PlanCache::new(16));
+                df.ctx
+                    .register_table("t", table())
+                    .expect("table registered");
+
+                let run_query = |sql: &str| {
+                    let query =
+                        QueryBuilder::new(sql.to_string(), Arc::clone(&df), Protocol::Http).build();
+                    async move {
+                        query
+                            .run()
+                            .await
+                            .expect("query should run")
+                            .data
+                            .try_collect::<Vec<_>>()
+                            .await
+                            .expect("results should be collected");
+                    }
+                };
+
+                run_query("SELECT x FROM t").await;
+                assert_eq!(hits(), 0);
+                run_query("SELECT x  FROM t;").await;
+                assert_eq!(hits(), 1, "equivalent SQL reuses the cached plan");
+
+                df.remove_table(&TableReference::bare("t"))
+                    .expect("table removed");
+                df.ctx
+                    .register_table("t", table())
+                    .expect("table registered");
+                run_query("SELECT x FROM t").await;
+                assert_eq!(hits(), 1, "schema changes invalidate cached plans");
+
+                run_query("SELECT now(), random()").await;
+                run_query("SELECT now(), random()").await;
+                assert_eq!(hits(), 1, "plans calling volatile functions aren't cached");
+            });
+        });
+    }
+}
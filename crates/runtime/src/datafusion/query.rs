@@ -14,18 +14,29 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use std::{collections::HashSet, string, sync::Arc, time::SystemTime};
+use std::{
+    collections::HashSet,
+    string,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
 
 use arrow::datatypes::Schema;
 use arrow_tools::schema::verify_schema;
 use cache::{
     cache_is_enabled_for_plan, get_logical_plan_input_tables, to_cached_record_batch_stream,
-    QueryResult,
+    CacheEntryTag,
 };
 use datafusion::{
+    common::plan_err,
     error::DataFusionError,
     execution::{context::SQLOptions, SendableRecordBatchStream},
-    physical_plan::{memory::MemoryStream, stream::RecordBatchStreamAdapter},
+    logical_expr::LogicalPlan,
+    physical_plan::{
+        execute_stream, memory::MemoryStream, stream::RecordBatchStreamAdapter, ExecutionPlan,
+        ExecutionPlanProperties,
+    },
+    scalar::ScalarValue,
 };
 use snafu::Snafu;
 use tokio::time::Instant;
@@ -47,6 +58,9 @@ pub enum Error {
     #[snafu(display("Failed to execute query: {source}"))]
     UnableToExecuteQuery { source: DataFusionError },
 
+    #[snafu(display("Unable to bind query parameters: {source}"))]
+    UnableToBindParameters { source: DataFusionError },
+
     #[snafu(display("Failed to access query results cache: {source}"))]
     FailedToAccessCache { source: cache::Error },
 
@@ -58,6 +72,9 @@ pub enum Error {
 
     #[snafu(display("Schema mismatch: {source}"))]
     SchemaMismatch { source: arrow_tools::schema::Error },
+
+    #[snafu(display("{source}"))]
+    TableNotWritable { source: crate::datafusion::Error },
 }
 
 #[derive(Debug)]
@@ -75,6 +92,65 @@ impl std::fmt::Display for Protocol {
     }
 }
 
+/// Timings and resource usage of a query, collected from `DataFusion` while it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// Time spent creating the logical and physical plans.
+    pub planning_time: Duration,
+    /// Time spent streaming the results, from the first poll until the stream is exhausted.
+    pub execution_time: Duration,
+    /// Bytes read by the scans of the query, as reported by the scan's `bytes_scanned` metric.
+    pub bytes_scanned: u64,
+    /// Number of output partitions of the executed plan.
+    pub partitions: usize,
+}
+
+/// A handle to the [`QueryStats`] of a query, which are complete once its result stream has been consumed.
+#[derive(Debug, Clone, Default)]
+pub struct QueryStatsHandle(Arc<Mutex<QueryStats>>);
+
+impl QueryStatsHandle {
+    #[must_use]
+    pub fn get(&self) -> QueryStats {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut QueryStats)) {
+        f(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+}
+
+/// The result stream of a query, along with its stats, which are complete once the stream has been consumed.
+pub struct QueryResult {
+    pub data: SendableRecordBatchStream,
+    pub from_cache: Option<bool>,
+    pub stats: QueryStatsHandle,
+    /// Identifies the results cache entry that the result was read from, or is cached in once it's consumed.
+    pub results_cache_tag: Option<CacheEntryTag>,
+}
+
+impl QueryResult {
+    #[must_use]
+    pub fn new(
+        data: SendableRecordBatchStream,
+        from_cache: Option<bool>,
+        stats: QueryStatsHandle,
+    ) -> Self {
+        QueryResult {
+            data,
+            from_cache,
+            stats,
+            results_cache_tag: None,
+        }
+    }
+
+    #[must_use]
+    fn results_cache_tag(mut self, results_cache_tag: Option<CacheEntryTag>) -> Self {
+        self.results_cache_tag = results_cache_tag;
+        self
+    }
+}
+
 pub struct Query {
     df: Arc<crate::datafusion::DataFusion>,
     sql: String,
@@ -86,11 +162,15 @@ pub struct Query {
     execution_time: Option<f32>,
     rows_produced: u64,
     results_cache_hit: Option<bool>,
+    results_cache_bypass: bool,
+    results_cache_key_components: Vec<String>,
     restricted_sql_options: Option<SQLOptions>,
+    parameters: Vec<ScalarValue>,
     error_message: Option<String>,
     timer: Instant,
     datasets: Arc<HashSet<String>>,
     protocol: Protocol,
+    stats: QueryStatsHandle,
 }
 
 macro_rules! handle_error {
@@ -111,17 +191,29 @@ macro_rules! handle_error {
 
 impl Query {
     pub async fn run(self) -> Result<QueryResult> {
-        let session = self.df.ctx.state();
-
         let mut ctx = self;
+        let planning_start = Instant::now();
 
-        let plan = match session.create_logical_plan(&ctx.sql).await {
+        let plan = match ctx.df.logical_plan(&ctx.sql).await {
             Ok(plan) => plan,
             Err(e) => handle_error!(ctx, e, UnableToExecuteQuery),
         };
 
-        if let Some(cache_provider) = &ctx.df.cache_provider() {
-            if let Some(cached_result) = match cache_provider.get(&plan).await {
+        let plan = match bind_parameters(plan, &ctx.parameters) {
+            Ok(plan) => plan,
+            Err(e) => handle_error!(ctx, e, UnableToBindParameters),
+        };
+
+        let cache_provider = ctx
+            .df
+            .cache_provider()
+            .filter(|_| !ctx.results_cache_bypass);
+
+        if let Some(cache_provider) = &cache_provider {
+            if let Some(cached_result) = match cache_provider
+                .get(&plan, &ctx.results_cache_key_components)
+                .await
+            {
                 Ok(Some(v)) => Some(v),
                 Ok(None) => None,
                 Err(e) => handle_error!(ctx, e, FailedToAccessCache),
@@ -129,6 +221,7 @@ impl Query {
                 ctx = ctx
                     .datasets(cached_result.input_tables)
                     .results_cache_hit(true);
+                record_results_cache_status(&ctx.datasets, true);
 
                 let record_batch_stream = match MemoryStream::try_new(
                     cached_result.records.to_vec(),
@@ -139,10 +232,19 @@ impl Query {
                     Err(e) => handle_error!(ctx, e, UnableToCreateMemoryStream),
                 };
 
+                let planning_time = planning_start.elapsed();
+                ctx.stats.update(|stats| {
+                    stats.planning_time = planning_time;
+                    stats.partitions = 1;
+                });
+
+                let stats = ctx.stats.clone();
                 return Ok(QueryResult::new(
-                    attach_query_context_to_stream(ctx, Box::pin(record_batch_stream)),
+                    attach_query_context_to_stream(ctx, Box::pin(record_batch_stream), None),
                     Some(true),
-                ));
+                    stats,
+                )
+                .results_cache_tag(Some(cached_result.tag)));
             }
 
             ctx = ctx.results_cache_hit(false);
@@ -154,7 +256,16 @@ impl Query {
             }
         }
 
+        if let LogicalPlan::Dml(dml) = &plan {
+            if let Err(e) = ctx.df.ensure_writable(&dml.table_name) {
+                handle_error!(ctx, e, TableNotWritable)
+            }
+        }
+
         ctx = ctx.datasets(Arc::new(get_logical_plan_input_tables(&plan)));
+        if ctx.results_cache_hit == Some(false) {
+            record_results_cache_status(&ctx.datasets, false);
+        }
 
         let plan_copy = plan.clone();
 
@@ -165,11 +276,25 @@ impl Query {
 
         let df_schema: Arc<Schema> = df.schema().clone().into();
 
-        let res_stream: SendableRecordBatchStream = match df.execute_stream().await {
-            Ok(stream) => stream,
+        let task_ctx = Arc::new(df.task_ctx());
+        let physical_plan = match df.create_physical_plan().await {
+            Ok(physical_plan) => physical_plan,
             Err(e) => handle_error!(ctx, e, UnableToCollectResults),
         };
 
+        let planning_time = planning_start.elapsed();
+        let partitions = physical_plan.output_partitioning().partition_count();
+        ctx.stats.update(|stats| {
+            stats.planning_time = planning_time;
+            stats.partitions = partitions;
+        });
+
+        let res_stream: SendableRecordBatchStream =
+            match execute_stream(Arc::clone(&physical_plan), task_ctx) {
+                Ok(stream) => stream,
+                Err(e) => handle_error!(ctx, e, UnableToCollectResults),
+            };
+
         let res_schema = res_stream.schema();
 
         if let Err(e) = verify_schema(df_schema.fields(), res_schema.fields()) {
@@ -177,24 +302,38 @@ impl Query {
         };
 
         if cache_is_enabled_for_plan(&plan_copy) {
-            if let Some(cache_provider) = &ctx.df.cache_provider() {
+            if let Some(cache_provider) = &cache_provider {
+                let ttl = ctx
+                    .df
+                    .results_cache_ttl(&ctx.datasets, cache_provider.ttl());
+                let tag =
+                    cache_provider.new_entry_tag(&plan_copy, &ctx.results_cache_key_components);
                 let record_batch_stream = to_cached_record_batch_stream(
                     Arc::clone(cache_provider),
                     res_stream,
                     plan_copy,
+                    ctx.results_cache_key_components.clone(),
+                    tag,
                     Arc::clone(&ctx.datasets),
+                    ttl,
                 );
 
+                let stats = ctx.stats.clone();
                 return Ok(QueryResult::new(
-                    attach_query_context_to_stream(ctx, record_batch_stream),
+                    attach_query_context_to_stream(ctx, record_batch_stream, Some(physical_plan)),
                     Some(false),
-                ));
+                    stats,
+                )
+                // Results with a zero ttl aren't cached
+                .results_cache_tag((!ttl.is_zero()).then_some(tag)));
             }
         }
 
+        let stats = ctx.stats.clone();
         Ok(QueryResult::new(
-            attach_query_context_to_stream(ctx, res_stream),
+            attach_query_context_to_stream(ctx, res_stream, Some(physical_plan)),
             None,
+            stats,
         ))
     }
 
@@ -246,6 +385,22 @@ impl Query {
 
         metrics::histogram!("query_duration_seconds", &labels).record(duration.as_secs_f32());
 
+        if let Some(threshold) = self.df.slow_query_threshold() {
+            if duration > threshold {
+                tracing::warn!(
+                    target: "slow_query",
+                    query_id = %self.query_id,
+                    protocol = %self.protocol,
+                    duration_ms = duration.as_millis(),
+                    rows_produced = self.rows_produced,
+                    error = self.error_message.as_deref(),
+                    sql = %self.sql,
+                    "Query exceeded the slow query threshold of {}ms",
+                    threshold.as_millis()
+                );
+            }
+        }
+
         if self.error_message.is_some() {
             metrics::counter!("query_failures", &labels).increment(1);
         }
@@ -284,10 +439,21 @@ impl Query {
     }
 }
 
+/// Sums the `bytes_scanned` metric over all nodes of an executed plan.
+fn bytes_scanned(plan: &Arc<dyn ExecutionPlan>) -> u64 {
+    let own = plan
+        .metrics()
+        .and_then(|metrics| metrics.sum_by_name("bytes_scanned"))
+        .map_or(0, |value| value.as_usize() as u64);
+
+    own + plan.children().iter().map(bytes_scanned).sum::<u64>()
+}
+
 #[must_use]
 fn attach_query_context_to_stream(
     ctx: Query,
     mut stream: SendableRecordBatchStream,
+    physical_plan: Option<Arc<dyn ExecutionPlan>>,
 ) -> SendableRecordBatchStream {
     let schema = stream.schema();
     let schema_copy = Arc::clone(&schema);
@@ -296,6 +462,7 @@ fn attach_query_context_to_stream(
     let mut ctx = ctx;
 
     let updated_stream = stream! {
+        let execution_start = Instant::now();
         while let Some(batch_result) = stream.next().await {
 
             match &batch_result {
@@ -309,6 +476,13 @@ fn attach_query_context_to_stream(
             yield batch_result;
         }
 
+        let execution_time = execution_start.elapsed();
+        let bytes_scanned = physical_plan.as_ref().map_or(0, bytes_scanned);
+        ctx.stats.update(|stats| {
+            stats.execution_time = execution_time;
+            stats.bytes_scanned = bytes_scanned;
+        });
+
         if let Err(e) = ctx
             .schema(schema_copy)
             .rows_produced(num_records)
@@ -323,3 +497,438 @@ fn attach_query_context_to_stream(
         Box::pin(updated_stream),
     ))
 }
+
+/// Replaces the `$1`, `$2`, ... placeholders of `plan` with `parameters`, cast to the types inferred for them.
+fn bind_parameters(
+    plan: LogicalPlan,
+    parameters: &[ScalarValue],
+) -> Result<LogicalPlan, DataFusionError> {
+    let parameter_types = plan.get_parameter_types()?;
+    if parameter_types.is_empty() && parameters.is_empty() {
+        return Ok(plan);
+    }
+
+    if parameter_types.len() != parameters.len() {
+        return plan_err!(
+            "Expected {} parameters, received {}",
+            parameter_types.len(),
+            parameters.len()
+        );
+    }
+
+    let parameters = parameters
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let placeholder = format!("${}", i + 1);
+            match parameter_types.get(&placeholder) {
+                Some(Some(data_type)) => value.cast_to(data_type).or_else(|_| {
+                    plan_err!(
+                        "Parameter {placeholder} has type {}, expected {data_type}",
+                        value.data_type()
+                    )
+                }),
+                Some(None) => Ok(value.clone()),
+                None => plan_err!("Placeholder {placeholder} is not used in the query"),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    plan.with_param_values(parameters)
+}
+
+/// Counts results cache hits and misses for each dataset a query reads from, so cache effectiveness can be
+/// monitored per dataset.
+fn record_results_cache_status(datasets: &HashSet<String>, cache_hit: bool) {
+    let name = if cache_hit {
+        "query_cache_hits_total"
+    } else {
+        "query_cache_misses_total"
+    };
+    for dataset in datasets {
+        metrics::counter!(name, "dataset" => dataset.clone()).increment(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[tokio::test]
+    async fn test_query_populates_stats() {
+        let df = Arc::new(crate::datafusion::DataFusion::new());
+        let query = QueryBuilder::new(
+            "SELECT * FROM (VALUES (1), (2), (3)) AS t(x)".to_string(),
+            df,
+            Protocol::Http,
+        )
+        .build();
+
+        let result = query.run().await.expect("query should run");
+        let stats = result.stats.clone();
+        let batches = result
+            .data
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("results should be collected");
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+        let stats = stats.get();
+        assert!(stats.planning_time > Duration::ZERO);
+        assert!(stats.execution_time > Duration::ZERO);
+        assert!(stats.partitions > 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_log() {
+        let (logs, _guard) = crate::test_util::LogBuffer::capture("info");
+
+        let df = Arc::new(crate::datafusion::DataFusion::new());
+        let run_query = |sql: &str| {
+            let query = QueryBuilder::new(sql.to_string(), Arc::clone(&df), Protocol::Http).build();
+            async move {
+                query
+                    .run()
+                    .await
+                    .expect("query should run")
+                    .data
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .expect("results should be collected");
+            }
+        };
+        let slow_query_logs = || {
+            logs.contents()
+                .lines()
+                .filter(|line| line.contains("slow_query"))
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        df.set_slow_query_threshold(Duration::from_secs(3600));
+        run_query("SELECT 1 AS fast").await;
+        assert!(slow_query_logs().is_empty());
+
+        df.set_slow_query_threshold(Duration::ZERO);
+        run_query("SELECT * FROM (VALUES (1), (2)) AS t(slow)").await;
+        let logs = slow_query_logs();
+        assert_eq!(logs.len(), 1, "{logs:?}");
+        assert!(logs[0].contains("AS t(slow)"), "{}", logs[0]);
+        assert!(logs[0].contains("rows_produced=2"), "{}", logs[0]);
+    }
+
+    async fn sink_dataset(
+        df: &crate::datafusion::DataFusion,
+        name: &str,
+        mode: crate::component::dataset::Mode,
+    ) {
+        use crate::dataconnector::{sink::Sink, DataConnectorFactory};
+
+        let params = std::collections::HashMap::from([(
+            "schema".to_string(),
+            "CREATE TABLE t (id BIGINT)".to_string(),
+        )]);
+        let connector = Sink::create(None, Arc::new(params))
+            .await
+            .expect("connector created");
+        let mut dataset = crate::component::dataset::Dataset::try_new("sink".to_string(), name)
+            .expect("a valid dataset");
+        dataset.mode = mode;
+        df.register_table(dataset, crate::datafusion::Table::Federated(connector))
+            .await
+            .expect("dataset registered");
+    }
+
+    #[tokio::test]
+    async fn test_writes_require_read_write_mode() {
+        use crate::component::dataset::Mode;
+        use crate::dataupdate::{DataUpdate, UpdateType};
+        use datafusion::sql::TableReference;
+
+        let df = Arc::new(crate::datafusion::DataFusion::new());
+        sink_dataset(&df, "mode_read", Mode::Read).await;
+        sink_dataset(&df, "mode_read_write", Mode::ReadWrite).await;
+
+        let insert = |table: &str| {
+            QueryBuilder::new(
+                format!("INSERT INTO {table} VALUES (1)"),
+                Arc::clone(&df),
+                Protocol::Flight,
+            )
+            .build()
+            .run()
+        };
+        let err = insert("mode_read")
+            .await
+            .err()
+            .expect("inserting into a read dataset fails");
+        assert!(matches!(err, Error::TableNotWritable { .. }), "{err}");
+        let err = insert("spice.public.mode_read")
+            .await
+            .err()
+            .expect("inserting into a qualified read dataset fails");
+        assert!(matches!(err, Error::TableNotWritable { .. }), "{err}");
+        for table in [
+            "mode_read_write",
+            "public.mode_read_write",
+            "spice.public.mode_read_write",
+        ] {
+            insert(table)
+                .await
+                .expect("insert planned")
+                .data
+                .try_collect::<Vec<_>>()
+                .await
+                .expect("insert executed");
+        }
+
+        let schema = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+            "id",
+            arrow::datatypes::DataType::Int64,
+            true,
+        )]));
+        let data_update = DataUpdate {
+            data: vec![arrow::array::RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![Arc::new(arrow::array::Int64Array::from(vec![2]))],
+            )
+            .expect("data should be created")],
+            schema,
+            update_type: UpdateType::Append,
+        };
+        let err = df
+            .write_data(TableReference::bare("mode_read"), data_update.clone())
+            .await
+            .expect_err("writing to a read dataset fails");
+        assert!(
+            matches!(err, crate::datafusion::Error::TableNotWritable { .. }),
+            "{err}"
+        );
+        df.write_data(
+            TableReference::partial("public", "mode_read_write"),
+            data_update,
+        )
+        .await
+        .expect("writing to a read_write dataset succeeds");
+    }
+
+    async fn cached_query_status(
+        df: &Arc<crate::datafusion::DataFusion>,
+        bypass: bool,
+        key_components: &[&str],
+    ) -> Option<bool> {
+        let query = QueryBuilder::new(
+            "SELECT * FROM t".to_string(),
+            Arc::clone(df),
+            Protocol::Http,
+        )
+        .results_cache_bypass(bypass)
+        .results_cache_key_components(key_components.iter().map(ToString::to_string).collect())
+        .build();
+
+        let result = query.run().await.expect("query should run");
+        // Results are cached once the stream has been consumed
+        result
+            .data
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("results should be collected");
+
+        result.from_cache
+    }
+
+    fn cached_datafusion() -> Arc<crate::datafusion::DataFusion> {
+        let cache_provider =
+            cache::QueryResultsCacheProvider::new(&spicepod::component::runtime::ResultsCache {
+                item_ttl: Some("60s".to_string()),
+                ..Default::default()
+            })
+            .expect("cache provider should be created");
+        let df = Arc::new(crate::datafusion::DataFusion::new_with_cache_provider(
+            Some(Arc::new(cache_provider)),
+        ));
+
+        let schema = Arc::new(Schema::new(vec![arrow::datatypes::Field::new(
+            "x",
+            arrow::datatypes::DataType::Int64,
+            false,
+        )]));
+        let batch = arrow::array::RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(arrow::array::Int64Array::from(vec![1, 2, 3]))],
+        )
+        .expect("data should be created");
+        df.ctx
+            .register_table(
+                "t",
+                Arc::new(
+                    datafusion::datasource::MemTable::try_new(schema, vec![vec![batch]])
+                        .expect("mem table should be created"),
+                ),
+            )
+            .expect("table should be registered");
+
+        df
+    }
+
+    #[tokio::test]
+    async fn test_results_cache_bypass_and_key_components() {
+        let df = cached_datafusion();
+
+        assert_eq!(cached_query_status(&df, false, &[]).await, Some(false));
+        assert_eq!(cached_query_status(&df, false, &[]).await, Some(true));
+        assert_eq!(
+            cached_query_status(&df, true, &[]).await,
+            None,
+            "bypassed queries should not use the cache"
+        );
+
+        assert_eq!(
+            cached_query_status(&df, false, &["tenant=a"]).await,
+            Some(false),
+            "results cached without key components should not be shared"
+        );
+        assert_eq!(
+            cached_query_status(&df, false, &["tenant=a"]).await,
+            Some(true)
+        );
+        assert_eq!(
+            cached_query_status(&df, false, &["tenant=b"]).await,
+            Some(false),
+            "results cached for another tenant should not be shared"
+        );
+    }
+
+    async fn sql_cache_status(df: &Arc<crate::datafusion::DataFusion>, sql: &str) -> Option<bool> {
+        let result = QueryBuilder::new(sql.to_string(), Arc::clone(df), Protocol::Http)
+            .build()
+            .run()
+            .await
+            .expect("query should run");
+        result
+            .data
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("results should be collected");
+
+        result.from_cache
+    }
+
+    #[tokio::test]
+    async fn test_results_cache_ttl_per_dataset() {
+        use crate::dataconnector::{sink::Sink, DataConnectorFactory};
+
+        let df = cached_datafusion();
+        for (name, ttl) in [("short_lived", "200ms"), ("realtime", "0s")] {
+            let params = std::collections::HashMap::from([(
+                "schema".to_string(),
+                "CREATE TABLE t (x BIGINT)".to_string(),
+            )]);
+            let connector = Sink::create(None, Arc::new(params))
+                .await
+                .expect("connector created");
+            let mut dataset = crate::component::dataset::Dataset::try_new("sink".to_string(), name)
+                .expect("a valid dataset");
+            dataset.results_cache_ttl = Some(ttl.to_string());
+            df.register_table(dataset, crate::datafusion::Table::Federated(connector))
+                .await
+                .expect("dataset registered");
+        }
+
+        let short_lived = "SELECT * FROM short_lived";
+        assert_eq!(sql_cache_status(&df, short_lived).await, Some(false));
+        assert_eq!(sql_cache_status(&df, short_lived).await, Some(true));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(
+            sql_cache_status(&df, short_lived).await,
+            Some(false),
+            "results expire after the ttl of the dataset"
+        );
+
+        for realtime in [
+            "SELECT * FROM realtime",
+            "SELECT * FROM public.realtime",
+            "SELECT * FROM spice.public.realtime",
+        ] {
+            assert_eq!(sql_cache_status(&df, realtime).await, Some(false));
+            assert_eq!(
+                sql_cache_status(&df, realtime).await,
+                Some(false),
+                "a zero ttl disables caching of {realtime}"
+            );
+        }
+
+        let joined = "SELECT t.x FROM t JOIN realtime ON t.x = realtime.x";
+        sql_cache_status(&df, joined).await;
+        assert_eq!(
+            sql_cache_status(&df, joined).await,
+            Some(false),
+            "queries use the shortest ttl of their datasets"
+        );
+
+        let ttls = HashSet::from(["t".to_string(), "short_lived".to_string()]);
+        assert_eq!(
+            df.results_cache_ttl(&ttls, Duration::from_secs(60)),
+            Duration::from_millis(200)
+        );
+        let ttls = HashSet::from(["public.t".to_string(), "public.short_lived".to_string()]);
+        assert_eq!(
+            df.results_cache_ttl(&ttls, Duration::from_secs(60)),
+            Duration::from_millis(200)
+        );
+    }
+
+    #[test]
+    fn test_results_cache_metrics() {
+        fn counter(snapshot: &[(String, u64)], name: &str) -> u64 {
+            snapshot
+                .iter()
+                .find(|(key, _)| key == name)
+                .map_or(0, |(_, value)| *value)
+        }
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let snapshot = || {
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .filter_map(|(key, _, _, value)| match value {
+                    DebugValue::Counter(value)
+                        if key
+                            .key()
+                            .labels()
+                            .any(|label| label.key() == "dataset" && label.value() == "t") =>
+                    {
+                        Some((key.key().name().to_string(), value))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The local recorder is only visible on this thread, so the queries run on a current-thread runtime
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime should be created");
+            runtime.block_on(async {
+                let df = cached_datafusion();
+
+                assert_eq!(cached_query_status(&df, false, &[]).await, Some(false));
+                let after_miss = snapshot();
+                assert_eq!(counter(&after_miss, "query_cache_misses_total"), 1);
+                assert_eq!(counter(&after_miss, "query_cache_hits_total"), 0);
+
+                assert_eq!(cached_query_status(&df, false, &[]).await, Some(true));
+                let after_hit = snapshot();
+                assert_eq!(counter(&after_hit, "query_cache_misses_total"), 1);
+                assert_eq!(counter(&after_hit, "query_cache_hits_total"), 1);
+            });
+        });
+    }
+}
@@ -16,7 +16,7 @@ limitations under the License.
 
 use std::{collections::HashSet, sync::Arc, time::SystemTime};
 
-use datafusion::execution::context::SQLOptions;
+use datafusion::{execution::context::SQLOptions, scalar::ScalarValue};
 use tokio::time::Instant;
 use uuid::Uuid;
 
@@ -32,6 +32,9 @@ pub struct QueryBuilder {
     nsql: Option<String>,
     restricted_sql_options: Option<SQLOptions>,
     protocol: Protocol,
+    results_cache_bypass: bool,
+    results_cache_key_components: Vec<String>,
+    parameters: Vec<ScalarValue>,
 }
 
 impl QueryBuilder {
@@ -43,6 +46,9 @@ impl QueryBuilder {
             nsql: None,
             restricted_sql_options: None,
             protocol,
+            results_cache_bypass: false,
+            results_cache_key_components: vec![],
+            parameters: vec![],
         }
     }
 
@@ -70,6 +76,27 @@ impl QueryBuilder {
         self
     }
 
+    /// Neither read from nor write to the results cache for this query.
+    #[must_use]
+    pub fn results_cache_bypass(mut self, bypass: bool) -> Self {
+        self.results_cache_bypass = bypass;
+        self
+    }
+
+    /// Scopes the cached results of this query, they're only shared with queries using the same components.
+    #[must_use]
+    pub fn results_cache_key_components(mut self, key_components: Vec<String>) -> Self {
+        self.results_cache_key_components = key_components;
+        self
+    }
+
+    /// Values bound to the `$1`, `$2`, ... placeholders of the SQL.
+    #[must_use]
+    pub fn parameters(mut self, parameters: Vec<ScalarValue>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
     #[must_use]
     pub fn build(self) -> Query {
         Query {
@@ -83,11 +110,15 @@ impl QueryBuilder {
             execution_time: None,
             rows_produced: 0,
             results_cache_hit: None,
+            results_cache_bypass: self.results_cache_bypass,
+            results_cache_key_components: self.results_cache_key_components,
             restricted_sql_options: self.restricted_sql_options,
+            parameters: self.parameters,
             error_message: None,
             datasets: Arc::new(HashSet::default()),
             timer: Instant::now(),
             protocol: self.protocol,
+            stats: super::QueryStatsHandle::default(),
         }
     }
 }
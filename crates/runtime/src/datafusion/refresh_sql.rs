@@ -14,11 +14,15 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::SessionContext;
 use datafusion::sql::parser::{DFParser, Statement};
 use datafusion::sql::sqlparser::ast::SetExpr;
 use datafusion::sql::sqlparser::dialect::PostgreSqlDialect;
 use datafusion::sql::{sqlparser, TableReference};
 use snafu::prelude::*;
+
+use crate::component::dataset::acceleration::RefreshSqlReferenceCheck;
 use sqlparser::ast::Statement as SQLStatement;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -40,6 +44,15 @@ pub enum Error {
 
     #[snafu(display("Missing expected SQL statement - this is a bug in Spice.ai"))]
     MissingStatement,
+
+    #[snafu(display("Unable to resolve the tables referenced by the refresh SQL: {source}"))]
+    UnableToResolveTableReferences { source: DataFusionError },
+
+    #[snafu(display("The refresh SQL for {expected_table} references other tables: {references}. Only {expected_table} can be referenced, or set refresh_sql_reference_check to off."))]
+    UnexpectedTableReferences {
+        expected_table: TableReference,
+        references: String,
+    },
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -107,3 +120,101 @@ pub fn validate_refresh_sql(expected_table: TableReference, refresh_sql: &str) -
         _ => InvalidSqlStatementSnafu { expected_table }.fail()?,
     }
 }
+
+/// Returns the tables referenced by the refresh SQL (i.e. through joins or subqueries) other than `expected_table`.
+pub fn unexpected_table_references(
+    expected_table: &TableReference,
+    refresh_sql: &str,
+) -> Result<Vec<TableReference>> {
+    let statements = DFParser::parse_sql_with_dialect(refresh_sql, &PostgreSqlDialect {})
+        .context(UnableToParseSqlSnafu)?;
+
+    let state = SessionContext::new().state();
+    let mut unexpected: Vec<TableReference> = vec![];
+    for statement in &statements {
+        for reference in state
+            .resolve_table_references(statement)
+            .context(UnableToResolveTableReferencesSnafu)?
+        {
+            if !reference.resolved_eq(expected_table) && !unexpected.contains(&reference) {
+                unexpected.push(reference);
+            }
+        }
+    }
+    unexpected.sort_by_key(ToString::to_string);
+
+    Ok(unexpected)
+}
+
+/// Reports references to tables other than `expected_table` in the refresh SQL, according to `check`.
+pub fn check_table_references(
+    expected_table: &TableReference,
+    refresh_sql: &str,
+    check: RefreshSqlReferenceCheck,
+) -> Result<()> {
+    if check == RefreshSqlReferenceCheck::Off {
+        return Ok(());
+    }
+
+    let unexpected = unexpected_table_references(expected_table, refresh_sql)?;
+    if unexpected.is_empty() {
+        return Ok(());
+    }
+
+    let err = UnexpectedTableReferencesSnafu {
+        expected_table: expected_table.clone(),
+        references: unexpected
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+    .build();
+
+    if check == RefreshSqlReferenceCheck::Error {
+        return Err(err);
+    }
+    tracing::warn!("{err}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_sql_referencing_only_own_table() {
+        let table = TableReference::bare("orders");
+        let sql = "SELECT * FROM orders WHERE status = 'open'";
+
+        validate_refresh_sql(table.clone(), sql).expect("valid refresh SQL");
+        assert!(unexpected_table_references(&table, sql)
+            .expect("table references resolved")
+            .is_empty());
+        check_table_references(&table, sql, RefreshSqlReferenceCheck::Error)
+            .expect("no unexpected references");
+    }
+
+    #[test]
+    fn test_refresh_sql_referencing_unexpected_table() {
+        let table = TableReference::bare("orders");
+        let sql = "SELECT * FROM orders WHERE customer_id IN (SELECT id FROM customers)";
+
+        assert_eq!(
+            unexpected_table_references(&table, sql).expect("table references resolved"),
+            vec![TableReference::bare("customers")]
+        );
+        check_table_references(&table, sql, RefreshSqlReferenceCheck::Warn)
+            .expect("unexpected references only warn");
+        check_table_references(&table, sql, RefreshSqlReferenceCheck::Off)
+            .expect("check is disabled");
+
+        let err = check_table_references(&table, sql, RefreshSqlReferenceCheck::Error)
+            .expect_err("unexpected references are an error");
+        assert!(matches!(
+            err,
+            Error::UnexpectedTableReferences { ref references, .. } if references == "customers"
+        ));
+    }
+}
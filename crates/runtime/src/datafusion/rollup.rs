@@ -0,0 +1,242 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::sync::Arc;
+
+use arrow::datatypes::{Schema, SchemaRef};
+use datafusion::datasource::TableProvider;
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::SessionContext;
+use datafusion::sql::TableReference;
+use snafu::prelude::*;
+
+use crate::component::dataset::{
+    acceleration::{Acceleration, RefreshMode, Rollup, ZeroResultsAction},
+    Mode,
+};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("The rollup for {dataset} requires at least one group_by column"))]
+    MissingGroupByColumns { dataset: TableReference },
+
+    #[snafu(display(
+        "The rollup for {dataset} groups by {column}, which is not a column of the dataset"
+    ))]
+    GroupByColumnNotFound {
+        dataset: TableReference,
+        column: String,
+    },
+
+    #[snafu(display("The rollup for {dataset} requires refresh_mode: full, as appending would aggregate rows more than once"))]
+    RollupRequiresFullRefresh { dataset: TableReference },
+
+    #[snafu(display("The rollup for {dataset} can't be combined with refresh_sql"))]
+    RollupWithRefreshSql { dataset: TableReference },
+
+    #[snafu(display("The rollup for {dataset} can't fall back to the source with on_zero_results: use_source, as the source isn't rolled up"))]
+    RollupWithSourceFallback { dataset: TableReference },
+
+    #[snafu(display("The rollup for {dataset} can't be written to, use mode: read"))]
+    RollupWithReadWriteMode { dataset: TableReference },
+
+    #[snafu(display("Unable to plan the rollup for {dataset}: {source}"))]
+    UnableToPlanRollup {
+        dataset: TableReference,
+        source: DataFusionError,
+    },
+}
+
+/// Checks that the rollup can be refreshed, and returns the schema of the rolled-up rows to create the accelerator with.
+///
+/// The rolled-up rows can't stand in for the source rows, so a rollup can't fall back to the source or be written to.
+pub async fn rollup_schema(
+    dataset: &TableReference,
+    rollup: &Rollup,
+    acceleration: &Acceleration,
+    mode: Mode,
+    source: Arc<dyn TableProvider>,
+) -> Result<SchemaRef> {
+    ensure!(
+        !rollup.group_by.is_empty(),
+        MissingGroupByColumnsSnafu {
+            dataset: dataset.clone()
+        }
+    );
+    ensure!(
+        acceleration.refresh_mode == RefreshMode::Full,
+        RollupRequiresFullRefreshSnafu {
+            dataset: dataset.clone()
+        }
+    );
+    ensure!(
+        acceleration.refresh_sql.is_none(),
+        RollupWithRefreshSqlSnafu {
+            dataset: dataset.clone()
+        }
+    );
+    ensure!(
+        acceleration.on_zero_results != ZeroResultsAction::UseSource,
+        RollupWithSourceFallbackSnafu {
+            dataset: dataset.clone()
+        }
+    );
+    ensure!(
+        mode != Mode::ReadWrite,
+        RollupWithReadWriteModeSnafu {
+            dataset: dataset.clone()
+        }
+    );
+
+    let source_schema = source.schema();
+    if let Some(column) = rollup
+        .group_by
+        .iter()
+        .find(|column| source_schema.field_with_name(column).is_err())
+    {
+        return GroupByColumnNotFoundSnafu {
+            dataset: dataset.clone(),
+            column: column.clone(),
+        }
+        .fail();
+    }
+
+    // Only the schema of the result is needed, so plan against the bare table name without creating its schema.
+    let table = TableReference::bare(dataset.table().to_string());
+    let ctx = SessionContext::new();
+    let plan = async {
+        ctx.register_table(table.clone(), source)?;
+        ctx.state()
+            .create_logical_plan(&rollup.to_sql(&table))
+            .await
+    }
+    .await
+    .context(UnableToPlanRollupSnafu {
+        dataset: dataset.clone(),
+    })?;
+
+    Ok(Arc::new(Schema::from(plan.schema().as_ref())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{Int64Array, RecordBatch, StringArray},
+        datatypes::{DataType, Field},
+    };
+    use datafusion::datasource::MemTable;
+
+    fn orders() -> Arc<dyn TableProvider> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, false),
+            Field::new("amount", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from(vec!["eu", "us", "eu"])),
+                Arc::new(Int64Array::from(vec![10, 20, 30])),
+            ],
+        )
+        .expect("data should be created");
+        Arc::new(MemTable::try_new(schema, vec![vec![batch]]).expect("mem table created"))
+    }
+
+    fn rollup(group_by: &str) -> Rollup {
+        Rollup {
+            group_by: vec![group_by.to_string()],
+            aggregations: vec![
+                "sum(amount) AS total_amount".to_string(),
+                "count(*) AS orders".to_string(),
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rollup_schema() {
+        let dataset = TableReference::bare("orders");
+        let schema = rollup_schema(
+            &dataset,
+            &rollup("region"),
+            &Acceleration::default(),
+            Mode::Read,
+            orders(),
+        )
+        .await
+        .expect("valid rollup");
+
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec!["region", "total_amount", "orders"]);
+    }
+
+    #[tokio::test]
+    async fn test_rollup_validation() {
+        let dataset = TableReference::bare("orders");
+
+        let err = rollup_schema(
+            &dataset,
+            &rollup("country"),
+            &Acceleration::default(),
+            Mode::Read,
+            orders(),
+        )
+        .await
+        .expect_err("unknown group_by column");
+        assert!(matches!(err, Error::GroupByColumnNotFound { column, .. } if column == "country"));
+
+        let append = Acceleration {
+            refresh_mode: RefreshMode::Append,
+            ..Default::default()
+        };
+        let err = rollup_schema(&dataset, &rollup("region"), &append, Mode::Read, orders())
+            .await
+            .expect_err("append refreshes aren't supported");
+        assert!(matches!(err, Error::RollupRequiresFullRefresh { .. }));
+
+        let use_source = Acceleration {
+            on_zero_results: ZeroResultsAction::UseSource,
+            ..Default::default()
+        };
+        let err = rollup_schema(
+            &dataset,
+            &rollup("region"),
+            &use_source,
+            Mode::Read,
+            orders(),
+        )
+        .await
+        .expect_err("falling back to the source isn't supported");
+        assert!(matches!(err, Error::RollupWithSourceFallback { .. }));
+
+        let err = rollup_schema(
+            &dataset,
+            &rollup("region"),
+            &Acceleration::default(),
+            Mode::ReadWrite,
+            orders(),
+        )
+        .await
+        .expect_err("writes aren't supported");
+        assert!(matches!(err, Error::RollupWithReadWriteMode { .. }));
+    }
+}
@@ -0,0 +1,286 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Float64Array},
+    compute::cast,
+    datatypes::{DataType, Field, Float64Type},
+};
+use datafusion::{
+    common::{exec_err, plan_err, Result, ScalarValue},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+pub const VECTOR_DISTANCE: &str = "vector_distance";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    Cosine,
+    L2,
+}
+
+impl DistanceMetric {
+    fn parse(metric: &str) -> Result<Self> {
+        match metric.to_lowercase().as_str() {
+            "cosine" => Ok(Self::Cosine),
+            "l2" | "euclidean" => Ok(Self::L2),
+            _ => plan_err!(
+                "Unsupported {VECTOR_DISTANCE} metric '{metric}', expected 'cosine' or 'l2'"
+            ),
+        }
+    }
+
+    /// Distance between two vectors of equal length, `None` if it is undefined, i.e. the cosine distance to a zero vector.
+    fn distance(self, a: &[f64], b: &[f64]) -> Option<f64> {
+        match self {
+            Self::Cosine => {
+                let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+                for (x, y) in a.iter().zip(b) {
+                    dot += x * y;
+                    norm_a += x * x;
+                    norm_b += y * y;
+                }
+                let norm = norm_a.sqrt() * norm_b.sqrt();
+                (norm > 0.0).then(|| 1.0 - dot / norm)
+            }
+            Self::L2 => Some(
+                a.iter()
+                    .zip(b)
+                    .map(|(x, y)| (x - y) * (x - y))
+                    .sum::<f64>()
+                    .sqrt(),
+            ),
+        }
+    }
+}
+
+/// `vector_distance(a, b [, metric])` computes the distance between two embedding vectors, so that similarity
+/// search can be written as `ORDER BY vector_distance(embedding, <query vector>) LIMIT k`.
+///
+/// Vectors are lists or fixed size lists of numbers. `metric` is either `'cosine'` (the default) or `'l2'`.
+#[derive(Debug)]
+pub struct VectorDistance {
+    signature: Signature,
+}
+
+impl VectorDistance {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::one_of(
+                vec![TypeSignature::Any(2), TypeSignature::Any(3)],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl Default for VectorDistance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScalarUDFImpl for VectorDistance {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        VECTOR_DISTANCE
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        for arg_type in &arg_types[..2] {
+            match arg_type {
+                DataType::List(field)
+                | DataType::LargeList(field)
+                | DataType::FixedSizeList(field, _)
+                    if field.data_type().is_numeric() => {}
+                DataType::Null => {}
+                _ => return plan_err!("{VECTOR_DISTANCE} expects numeric vectors, got {arg_type}"),
+            }
+        }
+        if let Some(metric_type) = arg_types.get(2) {
+            if !matches!(metric_type, DataType::Utf8 | DataType::LargeUtf8) {
+                return plan_err!(
+                    "{VECTOR_DISTANCE} expects the metric as a string, got {metric_type}"
+                );
+            }
+        }
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let metric = match args.get(2) {
+            None => DistanceMetric::Cosine,
+            Some(ColumnarValue::Scalar(
+                ScalarValue::Utf8(Some(metric)) | ScalarValue::LargeUtf8(Some(metric)),
+            )) => DistanceMetric::parse(metric)?,
+            Some(_) => {
+                return plan_err!("{VECTOR_DISTANCE} expects the metric as a string literal")
+            }
+        };
+
+        let num_rows = args[..2]
+            .iter()
+            .find_map(|arg| match arg {
+                ColumnarValue::Array(array) => Some(array.len()),
+                ColumnarValue::Scalar(_) => None,
+            })
+            .unwrap_or(1);
+        let a = as_f64_list(&args[0].clone().into_array(num_rows)?)?;
+        let b = as_f64_list(&args[1].clone().into_array(num_rows)?)?;
+        let (a, b) = (a.as_list::<i64>(), b.as_list::<i64>());
+
+        let mut distances = Vec::with_capacity(num_rows);
+        for row in 0..num_rows {
+            if a.is_null(row) || b.is_null(row) {
+                distances.push(None);
+                continue;
+            }
+            let (a_row, b_row) = (a.value(row), b.value(row));
+            let (a_row, b_row) = (
+                a_row.as_primitive::<Float64Type>(),
+                b_row.as_primitive::<Float64Type>(),
+            );
+            if a_row.len() != b_row.len() {
+                return exec_err!(
+                    "{VECTOR_DISTANCE} expects vectors of equal length, got {} and {}",
+                    a_row.len(),
+                    b_row.len()
+                );
+            }
+            if a_row.null_count() > 0 || b_row.null_count() > 0 {
+                distances.push(None);
+                continue;
+            }
+            distances.push(metric.distance(a_row.values(), b_row.values()));
+        }
+
+        let distances: ArrayRef = Arc::new(Float64Array::from(distances));
+        if args
+            .iter()
+            .all(|arg| matches!(arg, ColumnarValue::Scalar(_)))
+        {
+            return Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(
+                &distances, 0,
+            )?));
+        }
+        Ok(ColumnarValue::Array(distances))
+    }
+}
+
+/// Casts any list of numbers to a `LargeList<Float64>`, so that vectors of any element type and list kind can be compared.
+fn as_f64_list(array: &ArrayRef) -> Result<ArrayRef> {
+    let target = DataType::LargeList(Arc::new(Field::new("item", DataType::Float64, true)));
+    Ok(cast(array, &target)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datafusion::DataFusion;
+    use arrow::{
+        array::{FixedSizeListArray, Float32Array, Int64Array, RecordBatch},
+        datatypes::Schema,
+    };
+    use datafusion::datasource::MemTable;
+
+    #[test]
+    fn test_distances() {
+        let cosine = DistanceMetric::Cosine;
+        assert_eq!(cosine.distance(&[1.0, 0.0], &[2.0, 0.0]), Some(0.0));
+        assert_eq!(cosine.distance(&[1.0, 0.0], &[0.0, 3.0]), Some(1.0));
+        assert_eq!(cosine.distance(&[1.0, 0.0], &[-1.0, 0.0]), Some(2.0));
+        assert_eq!(cosine.distance(&[0.0, 0.0], &[1.0, 0.0]), None);
+
+        let l2 = DistanceMetric::L2;
+        assert_eq!(l2.distance(&[0.0, 0.0], &[3.0, 4.0]), Some(5.0));
+        assert_eq!(l2.distance(&[1.0, 1.0], &[1.0, 1.0]), Some(0.0));
+
+        assert!(DistanceMetric::parse("manhattan").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vector_distance_orders_results() {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new(
+                "embedding",
+                DataType::FixedSizeList(Arc::clone(&item), 2),
+                false,
+            ),
+        ]));
+        let embeddings = FixedSizeListArray::try_new(
+            item,
+            2,
+            Arc::new(Float32Array::from(vec![0.0, 1.0, 1.0, 0.0, 5.0, 5.0])),
+            None,
+        )
+        .expect("embeddings created");
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(embeddings),
+            ],
+        )
+        .expect("data should be created");
+        let table = MemTable::try_new(schema, vec![vec![batch]]).expect("mem table created");
+
+        let df = DataFusion::new();
+        df.ctx
+            .register_table("docs", Arc::new(table))
+            .expect("table registered");
+
+        for (metric, expected_ids) in [("cosine", [2, 3, 1]), ("l2", [2, 1, 3])] {
+            let batches = df
+                .ctx
+                .sql(&format!(
+                    "SELECT id, vector_distance(embedding, make_array(1.0, 0.1), '{metric}') AS distance \
+                     FROM docs ORDER BY distance LIMIT 3"
+                ))
+                .await
+                .expect("query planned")
+                .collect()
+                .await
+                .expect("query executed");
+            let ids = batches[0]
+                .column(0)
+                .as_primitive::<arrow::datatypes::Int64Type>();
+            assert_eq!(ids.values(), &expected_ids, "{metric} ordering");
+        }
+
+        let batches = df
+            .ctx
+            .sql("SELECT vector_distance(make_array(0.0, 0.0), make_array(3.0, 4.0), 'l2')")
+            .await
+            .expect("query planned")
+            .collect()
+            .await
+            .expect("query executed");
+        let distance = batches[0].column(0).as_primitive::<Float64Type>();
+        assert!((distance.value(0) - 5.0).abs() < f64::EPSILON);
+    }
+}
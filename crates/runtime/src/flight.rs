@@ -20,8 +20,8 @@ use crate::dataupdate::DataUpdate;
 use crate::measure_scope_ms;
 use arrow::array::RecordBatch;
 use arrow::datatypes::Schema;
-use arrow::ipc::writer::{DictionaryTracker, IpcDataGenerator};
 use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
 use arrow_flight::{Action, ActionType, Criteria, IpcMessage, PollInfo, SchemaResult};
 use arrow_ipc::writer::IpcWriteOptions;
 use bytes::Bytes;
@@ -184,46 +184,15 @@ impl Service {
             .protocol(Protocol::Flight)
             .build();
 
-        let batches_stream = query.run().await.map_err(to_tonic_err)?;
-
-        let schema = batches_stream.data.schema();
-        let options = datafusion::arrow::ipc::writer::IpcWriteOptions::default();
-        let schema_as_ipc = SchemaAsIpc::new(&schema, &options);
-        let schema_flight_data = FlightData::from(schema_as_ipc);
-
-        let batches_stream = batches_stream
-            .data
-            .then(move |batch_result| {
-                let options_clone = options.clone();
-                async move {
-                    let encoder = IpcDataGenerator::default();
-                    let mut tracker = DictionaryTracker::new(false);
-
-                    match batch_result {
-                        Ok(batch) => {
-                            let (flight_dictionaries, flight_batch) = encoder
-                                .encoded_batch(&batch, &mut tracker, &options_clone)
-                                .map_err(|e| Status::internal(e.to_string()))?;
-
-                            let mut flights: Vec<FlightData> =
-                                flight_dictionaries.into_iter().map(Into::into).collect();
-                            flights.push(flight_batch.into());
-                            Ok(flights)
-                        }
-                        Err(e) => Err(Status::internal(e.to_string())),
-                    }
-                }
-            })
-            .map(|result| {
-                // Convert Result<Vec<FlightData>, Status> into Stream of Result<FlightData, Status>
-                match result {
-                    Ok(flights) => stream::iter(flights.into_iter().map(Ok)).left_stream(),
-                    Err(e) => stream::once(async { Err(e) }).right_stream(),
-                }
-            })
-            .flatten();
-
-        let flights_stream = stream::once(async { Ok(schema_flight_data) }).chain(batches_stream);
+        let batches_stream = query.run().await.map_err(to_tonic_err)?.data;
+        let schema = batches_stream.schema();
+
+        // Batches are only executed and encoded as the client reads the stream, so results are never buffered in full
+        // and a slow client holds back the query instead of growing the server's memory.
+        let flights_stream = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches_stream.map_err(|e| FlightError::ExternalError(Box::new(e))))
+            .map_err(to_tonic_err);
 
         Ok(flights_stream.boxed())
     }
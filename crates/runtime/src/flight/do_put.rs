@@ -16,6 +16,7 @@ limitations under the License.
 
 use std::{collections::HashMap, sync::Arc};
 
+use arrow::datatypes::Schema;
 use arrow_flight::{flight_service_server::FlightService, FlightData, PutResult};
 use arrow_ipc::convert::try_schema_from_flatbuffer_bytes;
 use datafusion::sql::TableReference;
@@ -43,6 +44,49 @@ async fn get_sender_channel(
     }
 }
 
+/// Rejects a `do_put` whose schema can't be inserted into the target table, naming every mismatched field, rather
+/// than failing deep inside the insertion.
+fn validate_schema(
+    path: &TableReference,
+    target: &Schema,
+    incoming: &Schema,
+) -> Result<(), Status> {
+    let mut mismatches = vec![];
+
+    for target_field in target.fields() {
+        match incoming.field_with_name(target_field.name()) {
+            Ok(field) if field.data_type() != target_field.data_type() => {
+                mismatches.push(format!(
+                    "field \"{}\" has type {} but {path} expects {}",
+                    field.name(),
+                    field.data_type(),
+                    target_field.data_type()
+                ));
+            }
+            Ok(_) => {}
+            Err(_) => mismatches.push(format!("missing field \"{}\"", target_field.name())),
+        }
+    }
+
+    for field in incoming.fields() {
+        if target.field_with_name(field.name()).is_err() {
+            mismatches.push(format!(
+                "unexpected field \"{}\" not present in {path}",
+                field.name()
+            ));
+        }
+    }
+
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+
+    Err(Status::invalid_argument(format!(
+        "Schema does not match {path}: {}",
+        mismatches.join(", ")
+    )))
+}
+
 pub(crate) async fn handle(
     flight_svc: &Service,
     request: Request<Streaming<FlightData>>,
@@ -64,14 +108,25 @@ pub(crate) async fn handle(
 
     duration_metric.with_labels(vec![("path", path.to_string())]);
 
-    if !flight_svc.datafusion.is_writable(&path) {
+    if !flight_svc.datafusion.table_exists(path.clone()) {
         return Err(Status::invalid_argument(format!(
-            "Path doesn't exist or is not writable: {path}",
+            "Path doesn't exist: {path}"
         )));
     };
+    flight_svc
+        .datafusion
+        .ensure_writable(&path)
+        .map_err(|e| Status::permission_denied(e.to_string()))?;
 
     let schema = try_schema_from_flatbuffer_bytes(&message.data_header)
         .map_err(|e| Status::internal(format!("Failed to get schema from data header: {e}")))?;
+    let target_schema = flight_svc
+        .datafusion
+        .get_arrow_schema(&path.to_string())
+        .await
+        .map_err(|e| Status::internal(format!("Failed to get schema of {path}: {e}")))?;
+    validate_schema(&path, &target_schema, &schema)?;
+
     let schema = Arc::new(schema);
     let dictionaries_by_id = Arc::new(HashMap::new());
 
@@ -148,3 +203,37 @@ pub(crate) async fn handle(
 
     Ok(Response::new(Box::pin(timed_stream)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{DataType, Field};
+    use tonic::Code;
+
+    #[test]
+    fn test_validate_schema_names_mismatched_fields() {
+        let path = TableReference::bare("my_table");
+        let target = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("created_at", DataType::Int64, true),
+        ]);
+
+        assert!(validate_schema(&path, &target, &target).is_ok());
+
+        let incoming = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Int32, true),
+            Field::new("extra", DataType::Utf8, true),
+        ]);
+
+        let status =
+            validate_schema(&path, &target, &incoming).expect_err("schema should be rejected");
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "Schema does not match my_table: field \"name\" has type Int32 but my_table expects Utf8, missing field \"created_at\", \
+             unexpected field \"extra\" not present in my_table"
+        );
+    }
+}
@@ -26,6 +26,8 @@ use tokio::{
 
 use crate::{config, datafusion::DataFusion, EmbeddingModelStore, LLMModelStore};
 
+mod concurrency;
+mod cors;
 mod routes;
 mod v1;
 
@@ -36,6 +38,9 @@ pub enum Error {
 
     #[snafu(display("Unable to start HTTP server: {source}"))]
     UnableToStartHttpServer { source: std::io::Error },
+
+    #[snafu(display("Unable to configure CORS: {source}"))]
+    UnableToConfigureCors { source: cors::Error },
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -54,7 +59,11 @@ pub(crate) async fn start<A>(
 where
     A: ToSocketAddrs + Debug,
 {
-    let routes = routes::routes(app, df, models, llms, embeddings, config, with_metrics);
+    let cors = cors::layer(&config).context(UnableToConfigureCorsSnafu)?;
+    let mut routes = routes::routes(app, df, models, llms, embeddings, config, with_metrics);
+    if let Some(cors) = cors {
+        routes = routes.layer(cors);
+    }
 
     let listener = TcpListener::bind(&bind_address)
         .await
@@ -0,0 +1,132 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header::RETRY_AFTER, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+
+/// Seconds a client is asked to wait before retrying a request that was shed.
+const RETRY_AFTER_SECS: &str = "1";
+
+/// Bounds the number of in-flight requests to a group of routes.
+#[derive(Debug, Clone)]
+pub(crate) struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimit {
+    pub(crate) fn new(max_in_flight: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+        }
+    }
+}
+
+/// Rejects requests with a `503 Service Unavailable` and a `Retry-After` header while the limit is reached,
+/// instead of queueing them and letting memory grow without bound.
+pub(crate) async fn shed_load(
+    State(limit): State<ConcurrencyLimit>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = Arc::clone(&limit.semaphore).try_acquire_owned() else {
+        metrics::counter!("http_requests_shed").increment(1);
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, RETRY_AFTER_SECS)],
+            "Too many concurrent requests, retry later.",
+        )
+            .into_response();
+    };
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn request() -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/sql")
+            .body(Body::empty())
+            .expect("valid request")
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_limit_are_shed() {
+        const MAX_IN_FLIGHT: usize = 2;
+
+        let release = Arc::new(Semaphore::new(0));
+        let entered = Arc::new(Semaphore::new(0));
+        let router = {
+            let release = Arc::clone(&release);
+            let entered = Arc::clone(&entered);
+            Router::new()
+                .route(
+                    "/v1/sql",
+                    post(move || {
+                        let entered = Arc::clone(&entered);
+                        let release = Arc::clone(&release);
+                        async move {
+                            entered.add_permits(1);
+                            let _released = release.acquire().await.expect("released");
+                            "ok"
+                        }
+                    }),
+                )
+                .route_layer(middleware::from_fn_with_state(
+                    ConcurrencyLimit::new(MAX_IN_FLIGHT),
+                    shed_load,
+                ))
+        };
+
+        let in_flight = (0..MAX_IN_FLIGHT)
+            .map(|_| tokio::spawn(router.clone().oneshot(request())))
+            .collect::<Vec<_>>();
+        // Wait for the first N queries to be running before issuing one more
+        let _entered = entered
+            .acquire_many(u32::try_from(MAX_IN_FLIGHT).expect("fits in u32"))
+            .await
+            .expect("handlers entered");
+
+        let rejected = router.clone().oneshot(request()).await.expect("response");
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            rejected
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()),
+            Some(RETRY_AFTER_SECS)
+        );
+
+        release.add_permits(MAX_IN_FLIGHT);
+        for handle in in_flight {
+            let response = handle.await.expect("task joined").expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+}
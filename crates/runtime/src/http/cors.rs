@@ -0,0 +1,159 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use snafu::prelude::*;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Invalid CORS allowed origin {origin:?}"))]
+    InvalidAllowedOrigin { origin: String },
+
+    #[snafu(display("Invalid CORS allowed method {method:?}"))]
+    InvalidAllowedMethod { method: String },
+
+    #[snafu(display("Invalid CORS allowed header {header:?}"))]
+    InvalidAllowedHeader { header: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Builds the CORS layer for the HTTP API from the runtime configuration.
+///
+/// Returns `None` when no allowed origins are configured, leaving browsers to enforce the same-origin policy.
+/// Preflight `OPTIONS` requests are answered by the layer without reaching the routes.
+pub(crate) fn layer(config: &config::Config) -> Result<Option<CorsLayer>> {
+    if config.cors_allowed_origins.is_empty() {
+        return Ok(None);
+    }
+
+    let allow_origin = if config
+        .cors_allowed_origins
+        .iter()
+        .any(|origin| origin == "*")
+    {
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .cors_allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin.trim()).map_err(|_| Error::InvalidAllowedOrigin {
+                    origin: origin.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    let methods = config
+        .cors_allowed_methods
+        .iter()
+        .map(|method| {
+            Method::from_bytes(method.trim().to_uppercase().as_bytes()).map_err(|_| {
+                Error::InvalidAllowedMethod {
+                    method: method.clone(),
+                }
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let headers = config
+        .cors_allowed_headers
+        .iter()
+        .map(|header| {
+            HeaderName::from_bytes(header.trim().as_bytes()).map_err(|_| {
+                Error::InvalidAllowedHeader {
+                    header: header.clone(),
+                }
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(methods)
+            .allow_headers(headers),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        routing::post,
+        Router,
+    };
+    use clap::Parser;
+    use tower::ServiceExt;
+
+    fn router(args: &[&str]) -> Router {
+        let config =
+            config::Config::parse_from(std::iter::once("spiced").chain(args.iter().copied()));
+        let router = Router::new().route("/v1/sql", post(|| async { "ok" }));
+        match layer(&config).expect("valid CORS config") {
+            Some(cors) => router.layer(cors),
+            None => router,
+        }
+    }
+
+    async fn preflight(router: Router, origin: &str) -> (StatusCode, Option<HeaderValue>) {
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/v1/sql")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .expect("valid request");
+        let response = router.oneshot(request).await.expect("response");
+
+        (
+            response.status(),
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .cloned(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origins() {
+        let args = ["--cors_allowed_origins", "https://dashboard.example.com"];
+
+        let (status, allow_origin) =
+            preflight(router(&args), "https://dashboard.example.com").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            allow_origin,
+            Some(HeaderValue::from_static("https://dashboard.example.com"))
+        );
+
+        let (_, allow_origin) = preflight(router(&args), "https://other.example.com").await;
+        assert_eq!(allow_origin, None);
+    }
+
+    #[tokio::test]
+    async fn test_cors_disabled_by_default() {
+        let (_, allow_origin) = preflight(router(&[]), "https://dashboard.example.com").await;
+        assert_eq!(allow_origin, None);
+    }
+}
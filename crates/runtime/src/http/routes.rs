@@ -25,15 +25,20 @@ use std::{collections::HashMap, sync::Arc};
 use axum::{
     body::Body,
     extract::MatchedPath,
-    http::Request,
+    http::{HeaderName, HeaderValue, Request},
     middleware::{self, Next},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post, Router},
     Extension,
 };
 use tokio::{sync::RwLock, time::Instant};
+use tracing::Instrument;
+use uuid::Uuid;
 
-use super::v1;
+use super::{
+    concurrency::{self, ConcurrencyLimit},
+    v1,
+};
 
 pub(crate) fn routes(
     app: Arc<RwLock<Option<App>>>,
@@ -44,20 +49,40 @@ pub(crate) fn routes(
     config: Arc<config::Config>,
     with_metrics: Option<SocketAddr>,
 ) -> Router {
+    let mut queries = Router::new().route("/v1/sql", post(v1::query::post));
+    if let Some(max_concurrent_queries) = config.max_concurrent_queries {
+        queries = queries.route_layer(middleware::from_fn_with_state(
+            ConcurrencyLimit::new(max_concurrent_queries),
+            concurrency::shed_load,
+        ));
+    }
+
     let mut router = Router::new()
-        .route("/health", get(|| async { "ok\n" }))
-        .route("/v1/sql", post(v1::query::post))
         .route("/v1/status", get(v1::status::get))
         .route("/v1/datasets", get(v1::datasets::get))
+        .route("/v1/datasets/lineage", get(v1::datasets::lineage))
         .route(
             "/v1/datasets/:name/acceleration/refresh",
             post(v1::datasets::refresh),
         )
+        .route("/v1/datasets/:name/schema", get(v1::datasets::schema))
+        .route("/v1/datasets/:name/config", get(v1::datasets::config))
         .route(
             "/v1/datasets/:name/acceleration",
             patch(v1::datasets::acceleration),
         )
-        .route("/v1/spicepods", get(v1::spicepods::get))
+        .route("/v1/spicepods", get(v1::spicepods::get));
+    if let Some(max_concurrent_requests) = config.max_concurrent_requests {
+        router = router.route_layer(middleware::from_fn_with_state(
+            ConcurrencyLimit::new(max_concurrent_requests),
+            concurrency::shed_load,
+        ));
+    }
+
+    let mut router = Router::new()
+        .route("/health", get(|| async { "ok\n" }))
+        .merge(router)
+        .merge(queries)
         .route_layer(middleware::from_fn(track_metrics));
 
     if cfg!(feature = "models") {
@@ -76,10 +101,36 @@ pub(crate) fn routes(
         .layer(Extension(app))
         .layer(Extension(df))
         .layer(Extension(with_metrics))
-        .layer(Extension(config));
+        .layer(Extension(config))
+        .layer(middleware::from_fn(propagate_request_id));
     router
 }
 
+static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Uses the incoming `X-Request-Id` (or generates one) to tie the request's logs to the client, and echoes it back
+/// on every response, including errors.
+async fn propagate_request_id(req: Request<Body>, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map_or_else(|| Uuid::new_v4().to_string(), ToString::to_string);
+
+    let span = tracing::info_span!("http_request", request_id = %request_id);
+    let mut response = next.run(req).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    response
+}
+
 async fn track_metrics(req: Request<Body>, next: Next) -> impl IntoResponse {
     let start = Instant::now();
     let path = if let Some(matched_path) = req.extensions().get::<MatchedPath>() {
@@ -105,3 +156,57 @@ async fn track_metrics(req: Request<Body>, next: Next) -> impl IntoResponse {
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    fn router() -> Router {
+        Router::new()
+            .route(
+                "/v1/sql",
+                post(|| async { (StatusCode::BAD_REQUEST, "invalid query") }),
+            )
+            .layer(middleware::from_fn(propagate_request_id))
+    }
+
+    async fn response_request_id(request: Request<Body>) -> Option<String> {
+        let response = router().oneshot(request).await.expect("response");
+        response
+            .headers()
+            .get(&REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_request_id_echoed_when_provided() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/sql")
+            .header("X-Request-Id", "client-request-1")
+            .body(Body::empty())
+            .expect("valid request");
+
+        assert_eq!(
+            response_request_id(request).await.as_deref(),
+            Some("client-request-1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated_when_absent() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/sql")
+            .body(Body::empty())
+            .expect("valid request");
+
+        let request_id = response_request_id(request)
+            .await
+            .expect("request id is generated");
+        assert!(Uuid::parse_str(&request_id).is_ok());
+    }
+}
@@ -14,19 +14,35 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use std::sync::Arc;
+use std::{
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use crate::{
     component::dataset::Dataset,
-    datafusion::query::{Protocol, QueryBuilder},
+    config::{self, BinaryEncoding, NonFiniteFloats},
+    datafusion::query::{Protocol, QueryBuilder, QueryStats},
+};
+use arrow::{
+    array::{ArrayRef, AsArray, Float64Array, RecordBatch, StringArray},
+    compute::cast,
+    datatypes::{DataType, Field, Float64Type, Schema},
+    error::ArrowError,
 };
-use arrow::array::RecordBatch;
 use axum::{
-    http::{HeaderMap, StatusCode},
+    http::{
+        header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH},
+        HeaderMap, StatusCode,
+    },
     response::{IntoResponse, Response},
 };
+use base64::Engine;
 use csv::Writer;
-use datafusion::execution::context::SQLOptions;
+use datafusion::{execution::context::SQLOptions, scalar::ScalarValue};
 use serde::{Deserialize, Serialize};
 
 use crate::{datafusion::DataFusion, status::ComponentStatus};
@@ -58,22 +74,389 @@ fn dataset_status(df: &DataFusion, ds: &Dataset) -> ComponentStatus {
     }
 }
 
+/// Results cache behavior requested through the headers of an HTTP query.
+#[derive(Debug, Default)]
+pub struct ResultsCacheRequest {
+    bypass: bool,
+    key_components: Vec<String>,
+    if_none_match: Option<String>,
+}
+
+impl ResultsCacheRequest {
+    /// Bypasses the cache on `Cache-Control: no-cache` or `no-store`, and scopes the cache key by the values
+    /// of `key_headers`. An `If-None-Match` header makes the response conditional on the result's `ETag`.
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap, key_headers: &[String]) -> Self {
+        let bypass = headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .any(|directive| {
+                directive.eq_ignore_ascii_case("no-cache")
+                    || directive.eq_ignore_ascii_case("no-store")
+            });
+
+        let key_components = key_headers
+            .iter()
+            .map(|name| {
+                let value = headers
+                    .get(name.as_str())
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+                format!("{name}={value}")
+            })
+            .collect();
+
+        let if_none_match = headers
+            .get(IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
+        Self {
+            bypass,
+            key_components,
+            if_none_match,
+        }
+    }
+
+    /// Whether the client already holds the result identified by `etag`.
+    fn matches_etag(&self, etag: &str) -> bool {
+        let Some(if_none_match) = &self.if_none_match else {
+            return false;
+        };
+
+        if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    }
+}
+
+/// Buffers serialized JSON output while keeping a count of the bytes written.
+struct CountingBuffer {
+    buf: Vec<u8>,
+    written: Arc<AtomicUsize>,
+}
+
+impl CountingBuffer {
+    fn new(written: Arc<AtomicUsize>) -> Self {
+        Self {
+            buf: Vec::new(),
+            written,
+        }
+    }
+}
+
+impl std::io::Write for CountingBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        self.written.fetch_add(buf.len(), Ordering::Relaxed);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// How numbers are written in JSON query results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonFormat {
+    /// Rounds floats to this many decimal places.
+    pub float_precision: Option<u8>,
+
+    /// Writes decimals as strings, as JSON numbers are commonly parsed into floats that can't represent them exactly.
+    pub decimals_as_strings: bool,
+
+    /// How NaN and infinite floats are written, as JSON can't represent them.
+    /// With [`NonFiniteFloats::String`], float columns containing them are written as strings.
+    pub non_finite_floats: NonFiniteFloats,
+
+    /// How binary values are encoded as strings.
+    pub binary_encoding: BinaryEncoding,
+}
+
+impl Default for JsonFormat {
+    fn default() -> Self {
+        Self {
+            float_precision: None,
+            decimals_as_strings: true,
+            non_finite_floats: NonFiniteFloats::Null,
+            binary_encoding: BinaryEncoding::Base64,
+        }
+    }
+}
+
+impl From<&config::Config> for JsonFormat {
+    fn from(config: &config::Config) -> Self {
+        Self {
+            float_precision: config.json_float_precision,
+            decimals_as_strings: !config.json_decimals_as_numbers,
+            non_finite_floats: config.json_non_finite_floats,
+            binary_encoding: config.json_binary_encoding,
+        }
+    }
+}
+
+impl JsonFormat {
+    /// Converts the top-level decimal, float and binary columns of `batch` to be written as configured.
+    fn apply(self, batch: RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let schema = batch.schema();
+        let mut fields = Vec::with_capacity(schema.fields().len());
+        let mut columns = Vec::with_capacity(batch.num_columns());
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            let column = match field.data_type() {
+                DataType::Decimal128(..) | DataType::Decimal256(..) if self.decimals_as_strings => {
+                    cast(column, &DataType::Utf8)?
+                }
+                DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+                    self.format_floats(field.name(), column)?
+                }
+                DataType::Binary
+                | DataType::LargeBinary
+                | DataType::FixedSizeBinary(_)
+                | DataType::BinaryView => self.encode_binary(column)?,
+                _ => {
+                    fields.push(Arc::clone(field));
+                    columns.push(Arc::clone(column));
+                    continue;
+                }
+            };
+
+            fields.push(Arc::new(
+                Field::new(
+                    field.name(),
+                    column.data_type().clone(),
+                    field.is_nullable() || column.null_count() > 0,
+                )
+                .with_metadata(field.metadata().clone()),
+            ));
+            columns.push(column);
+        }
+
+        RecordBatch::try_new(
+            Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone())),
+            columns,
+        )
+    }
+
+    fn format_floats(self, name: &str, column: &ArrayRef) -> Result<ArrayRef, ArrowError> {
+        let floats = cast(column, &DataType::Float64)?;
+        let mut floats = floats.as_primitive::<Float64Type>().clone();
+        if let Some(precision) = self.float_precision {
+            floats = floats.unary(|value| round_to(value, precision));
+        }
+
+        if floats.iter().flatten().all(f64::is_finite) {
+            return Ok(if self.float_precision.is_some() {
+                Arc::new(floats)
+            } else {
+                Arc::clone(column)
+            });
+        }
+
+        match self.non_finite_floats {
+            NonFiniteFloats::Null => Ok(Arc::new(
+                floats
+                    .iter()
+                    .map(|value| value.filter(|value| value.is_finite()))
+                    .collect::<Float64Array>(),
+            )),
+            NonFiniteFloats::String => Ok(Arc::new(
+                floats
+                    .iter()
+                    .map(|value| value.map(float_to_string))
+                    .collect::<StringArray>(),
+            )),
+            NonFiniteFloats::Error => Err(ArrowError::InvalidArgumentError(format!(
+                "Column {name} contains NaN or infinite values, which can't be written as JSON"
+            ))),
+        }
+    }
+
+    fn encode_binary(self, column: &ArrayRef) -> Result<ArrayRef, ArrowError> {
+        let encode = |value: Option<&[u8]>| value.map(|value| self.binary_encoding.encode(value));
+        let encoded: StringArray = match column.data_type() {
+            DataType::Binary => column.as_binary::<i32>().iter().map(encode).collect(),
+            DataType::LargeBinary => column.as_binary::<i64>().iter().map(encode).collect(),
+            DataType::FixedSizeBinary(_) => {
+                column.as_fixed_size_binary().iter().map(encode).collect()
+            }
+            DataType::BinaryView => column.as_binary_view().iter().map(encode).collect(),
+            data_type => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "Expected a binary column, got {data_type}"
+                )))
+            }
+        };
+        Ok(Arc::new(encoded))
+    }
+}
+
+impl BinaryEncoding {
+    fn encode(self, value: &[u8]) -> String {
+        match self {
+            Self::Base64 => base64::engine::general_purpose::STANDARD.encode(value),
+            Self::Hex => {
+                value
+                    .iter()
+                    .fold(String::with_capacity(value.len() * 2), |mut hex, byte| {
+                        let _ = write!(hex, "{byte:02x}");
+                        hex
+                    })
+            }
+        }
+    }
+}
+
+fn float_to_string(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value.is_sign_positive() {
+            "Infinity"
+        } else {
+            "-Infinity"
+        }
+        .to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn round_to(value: f64, decimal_places: u8) -> f64 {
+    let scale = 10_f64.powi(i32::from(decimal_places));
+    let scaled = value * scale;
+    if scaled.is_finite() {
+        scaled.round() / scale
+    } else {
+        value
+    }
+}
+
+/// Reports the stats of a completed query in `X-Query-*` response headers, with times in milliseconds.
+fn insert_query_stats_headers(headers: &mut HeaderMap, stats: &QueryStats) {
+    let values = [
+        (
+            "X-Query-Planning-Time-Ms",
+            format!("{:.3}", stats.planning_time.as_secs_f64() * 1000.0),
+        ),
+        (
+            "X-Query-Execution-Time-Ms",
+            format!("{:.3}", stats.execution_time.as_secs_f64() * 1000.0),
+        ),
+        ("X-Query-Bytes-Scanned", stats.bytes_scanned.to_string()),
+        ("X-Query-Partitions", stats.partitions.to_string()),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = value.parse() {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// Returns a `413 Payload Too Large` response once more than `max_response_bytes` have been serialized.
+fn check_response_size(
+    response_bytes: &AtomicUsize,
+    max_response_bytes: Option<usize>,
+) -> Option<Response> {
+    let max_response_bytes = max_response_bytes?;
+    let response_bytes = response_bytes.load(Ordering::Relaxed);
+    if response_bytes <= max_response_bytes {
+        return None;
+    }
+
+    tracing::debug!(
+        "Query response exceeded the maximum size of {max_response_bytes} bytes, aborting"
+    );
+    Some(
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "Query response exceeds the maximum allowed size of {max_response_bytes} bytes. Add a LIMIT or select fewer columns to reduce the result size."
+            ),
+        )
+            .into_response(),
+    )
+}
+
 // Runs query and converts query results to HTTP response (as JSON).
+#[allow(clippy::too_many_arguments)]
 pub async fn sql_to_http_response(
     df: Arc<DataFusion>,
     sql: &str,
+    parameters: Vec<ScalarValue>,
     restricted_sql_options: Option<SQLOptions>,
     nsql: Option<String>,
+    results_cache: ResultsCacheRequest,
+    max_response_bytes: Option<usize>,
+    json_format: JsonFormat,
 ) -> Response {
+    let cache_bypassed = results_cache.bypass && df.cache_provider().is_some();
     let query = QueryBuilder::new(sql.to_string(), Arc::clone(&df), Protocol::Http)
+        .parameters(parameters)
         .restricted_sql_options(restricted_sql_options)
         .nsql(nsql)
         .protocol(Protocol::Http)
+        .results_cache_bypass(results_cache.bypass)
+        .results_cache_key_components(results_cache.key_components.clone())
         .build();
 
-    let (data, is_data_from_cache) = match query.run().await {
-        Ok(query_result) => match query_result.data.try_collect::<Vec<RecordBatch>>().await {
-            Ok(batches) => (batches, query_result.from_cache),
+    let query_result = match query.run().await {
+        Ok(query_result) => query_result,
+        Err(e) => {
+            tracing::debug!("Error executing query: {e}");
+            return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+        }
+    };
+    let is_data_from_cache = query_result.from_cache;
+    let stats = query_result.stats;
+    let mut data = query_result.data;
+
+    let mut headers = HeaderMap::new();
+
+    match is_data_from_cache {
+        Some(true) => {
+            if let Ok(value) = "Hit from spiceai".parse() {
+                headers.insert("X-Cache", value);
+            }
+        }
+        Some(false) => {
+            if let Ok(value) = "Miss from spiceai".parse() {
+                headers.insert("X-Cache", value);
+            }
+        }
+        None => {
+            if cache_bypassed {
+                if let Ok(value) = "Bypass from spiceai".parse() {
+                    headers.insert("X-Cache", value);
+                }
+            }
+        }
+    };
+
+    // Only results in the results cache have an ETag, which identifies their cache entry. A client that already
+    // holds the cached result is answered without reading it.
+    if let Some(tag) = query_result.results_cache_tag {
+        let etag = format!("\"{tag}\"");
+        if let Ok(value) = etag.parse() {
+            headers.insert(ETAG, value);
+        }
+        if results_cache.matches_etag(&etag) {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+    }
+
+    let response_bytes = Arc::new(AtomicUsize::new(0));
+    let mut writer = arrow_json::ArrayWriter::new(CountingBuffer::new(Arc::clone(&response_bytes)));
+
+    // Serialize batch by batch so that an oversized response is rejected before it is fully buffered.
+    loop {
+        let batch = match data.try_next().await {
+            Ok(Some(batch)) => batch,
+            Ok(None) => break,
             Err(e) => {
                 tracing::debug!("Error executing query: {e}");
                 return (
@@ -82,25 +465,32 @@ pub async fn sql_to_http_response(
                 )
                     .into_response();
             }
-        },
-        Err(e) => {
-            tracing::debug!("Error executing query: {e}");
-            return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
-        }
-    };
-    let buf = Vec::new();
-    let mut writer = arrow_json::ArrayWriter::new(buf);
+        };
 
-    if let Err(e) = writer.write_batches(data.iter().collect::<Vec<&RecordBatch>>().as_slice()) {
-        tracing::debug!("Error converting results to JSON: {e}");
-        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        let batch = match json_format.apply(batch) {
+            Ok(batch) => batch,
+            Err(e) => {
+                tracing::debug!("Error formatting numbers for JSON: {e}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        };
+        if let Err(e) = writer.write(&batch) {
+            tracing::debug!("Error converting results to JSON: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+        if let Some(response) = check_response_size(&response_bytes, max_response_bytes) {
+            return response;
+        }
     }
     if let Err(e) = writer.finish() {
         tracing::debug!("Error finishing JSON conversion: {e}");
         return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
     }
+    if let Some(response) = check_response_size(&response_bytes, max_response_bytes) {
+        return response;
+    }
 
-    let buf = writer.into_inner();
+    let buf = writer.into_inner().buf;
     let res = match String::from_utf8(buf) {
         Ok(res) => res,
         Err(e) => {
@@ -109,21 +499,8 @@ pub async fn sql_to_http_response(
         }
     };
 
-    let mut headers = HeaderMap::new();
+    insert_query_stats_headers(&mut headers, &stats.get());
 
-    match is_data_from_cache {
-        Some(true) => {
-            if let Ok(value) = "Hit from spiceai".parse() {
-                headers.insert("X-Cache", value);
-            }
-        }
-        Some(false) => {
-            if let Ok(value) = "Miss from spiceai".parse() {
-                headers.insert("X-Cache", value);
-            }
-        }
-        None => {}
-    };
     (StatusCode::OK, headers, res).into_response()
 }
 
@@ -132,22 +509,113 @@ pub(crate) mod query {
 
     use axum::{
         body::Bytes,
-        http::StatusCode,
+        extract::Query,
+        http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
         response::{IntoResponse, Response},
         Extension,
     };
-    use datafusion::execution::context::SQLOptions;
+    use datafusion::{execution::context::SQLOptions, scalar::ScalarValue};
+    use serde::Deserialize;
+    use serde_json::Value;
 
-    use crate::datafusion::DataFusion;
+    use crate::{config, datafusion::DataFusion};
 
-    use super::sql_to_http_response;
+    use super::{sql_to_http_response, JsonFormat, ResultsCacheRequest};
 
-    pub(crate) async fn post(Extension(df): Extension<Arc<DataFusion>>, body: Bytes) -> Response {
-        let query = match String::from_utf8(body.to_vec()) {
-            Ok(query) => query,
-            Err(e) => {
-                tracing::debug!("Error reading query: {e}");
-                return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    #[derive(Debug, Default, Deserialize)]
+    pub struct QueryParams {
+        /// Lowers the runtime's maximum response size for this request.
+        #[serde(default)]
+        max_response_bytes: Option<usize>,
+
+        /// Runs the query to completion and responds with its physical plan annotated with the actual per-operator
+        /// metrics (i.e. `output_rows`, `elapsed_compute`) instead of its results, like `EXPLAIN ANALYZE`.
+        /// The results are discarded and neither read from nor written to the results cache.
+        #[serde(default)]
+        analyze: bool,
+
+        /// Rounds floats in the results to this many decimal places, overriding the runtime's precision.
+        #[serde(default)]
+        float_precision: Option<u8>,
+    }
+
+    pub(crate) fn explain_analyze(sql: &str) -> String {
+        format!("EXPLAIN ANALYZE {sql}")
+    }
+
+    /// A query sent as JSON, with values for the `$1`, `$2`, ... placeholders of the SQL.
+    #[derive(Debug, Deserialize)]
+    pub struct ParameterizedQuery {
+        sql: String,
+        #[serde(default)]
+        parameters: Vec<Value>,
+    }
+
+    /// Converts JSON parameter values to scalars, which are cast to the placeholder types once the query is planned.
+    pub(crate) fn parameter_values(parameters: Vec<Value>) -> Result<Vec<ScalarValue>, String> {
+        parameters
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| match value {
+                Value::Null => Ok(ScalarValue::Null),
+                Value::Bool(value) => Ok(ScalarValue::Boolean(Some(value))),
+                Value::Number(number) => {
+                    if let Some(value) = number.as_i64() {
+                        Ok(ScalarValue::Int64(Some(value)))
+                    } else if let Some(value) = number.as_u64() {
+                        Ok(ScalarValue::UInt64(Some(value)))
+                    } else {
+                        Ok(ScalarValue::Float64(number.as_f64()))
+                    }
+                }
+                Value::String(value) => Ok(ScalarValue::Utf8(Some(value))),
+                Value::Array(_) | Value::Object(_) => Err(format!(
+                    "Parameter ${} must be a string, number, boolean or null",
+                    i + 1
+                )),
+            })
+            .collect()
+    }
+
+    /// The per-request limit can only lower the limit configured for the runtime, never raise it.
+    fn max_response_bytes(configured: Option<usize>, requested: Option<usize>) -> Option<usize> {
+        match (configured, requested) {
+            (Some(configured), Some(requested)) => Some(configured.min(requested)),
+            (configured, requested) => configured.or(requested),
+        }
+    }
+
+    pub(crate) async fn post(
+        Extension(df): Extension<Arc<DataFusion>>,
+        Extension(config): Extension<Arc<config::Config>>,
+        Query(params): Query<QueryParams>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Response {
+        let is_json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+
+        let (query, parameters) = if is_json {
+            let request: ParameterizedQuery = match serde_json::from_slice(&body) {
+                Ok(request) => request,
+                Err(e) => {
+                    tracing::debug!("Error reading query: {e}");
+                    return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+                }
+            };
+            match parameter_values(request.parameters) {
+                Ok(parameters) => (request.sql, parameters),
+                Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+            }
+        } else {
+            match String::from_utf8(body.to_vec()) {
+                Ok(query) => (query, vec![]),
+                Err(e) => {
+                    tracing::debug!("Error reading query: {e}");
+                    return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+                }
             }
         };
 
@@ -156,7 +624,32 @@ pub(crate) mod query {
             .with_allow_dml(false)
             .with_allow_statements(false);
 
-        sql_to_http_response(df, &query, Some(restricted_sql_options), None).await
+        let key_headers = df
+            .cache_provider()
+            .map(|cache_provider| cache_provider.key_headers().to_vec())
+            .unwrap_or_default();
+        let results_cache = ResultsCacheRequest::from_headers(&headers, &key_headers);
+
+        let query = if params.analyze {
+            explain_analyze(&query)
+        } else {
+            query
+        };
+
+        sql_to_http_response(
+            df,
+            &query,
+            parameters,
+            Some(restricted_sql_options),
+            None,
+            results_cache,
+            max_response_bytes(config.max_response_bytes, params.max_response_bytes),
+            JsonFormat {
+                float_precision: params.float_precision.or(config.json_float_precision),
+                ..JsonFormat::from(config.as_ref())
+            },
+        )
+        .await
     }
 }
 
@@ -324,9 +817,15 @@ pub(crate) mod status {
 }
 
 pub(crate) mod datasets {
-    use std::sync::Arc;
+    use std::{
+        collections::{BTreeMap, HashMap},
+        sync::Arc,
+    };
 
-    use crate::{component::dataset::Dataset, Runtime};
+    use crate::{
+        component::dataset::{acceleration::RefreshMode, Dataset, Mode},
+        lineage, Runtime,
+    };
     use app::App;
     use axum::{
         extract::Path,
@@ -434,15 +933,230 @@ pub(crate) mod datasets {
         pub message: String,
     }
 
+    /// Returns the dependency edges between datasets, from their view definitions and `refresh_sql`.
+    pub(crate) async fn lineage(Extension(app): Extension<Arc<RwLock<Option<App>>>>) -> Response {
+        let app_lock = app.read().await;
+        let Some(readable_app) = &*app_lock else {
+            return (
+                status::StatusCode::INTERNAL_SERVER_ERROR,
+                Json::<Vec<lineage::LineageEdge>>(vec![]),
+            )
+                .into_response();
+        };
+
+        let datasets = Runtime::get_valid_datasets(readable_app, false);
+        (status::StatusCode::OK, Json(lineage::lineage(&datasets))).into_response()
+    }
+
+    fn redact_params(params: &HashMap<String, String>) -> BTreeMap<String, String> {
+        params
+            .iter()
+            .map(|(key, value)| {
+                let value = if util::http::is_sensitive(key) {
+                    util::http::REDACTED.to_string()
+                } else {
+                    value.clone()
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct AccelerationConfigResponse {
+        pub enabled: bool,
+        pub mode: String,
+        pub engine: String,
+        pub refresh_mode: String,
+        pub refresh_check_interval: Option<String>,
+        pub refresh_sql: Option<String>,
+        pub refresh_data_window: Option<String>,
+        pub refresh_initial_backfill_window: Option<String>,
+        pub refresh_append_dedup_rows: bool,
+        pub refresh_append_boundary_inclusive: bool,
+        pub retention_period: Option<String>,
+        pub retention_check_interval: Option<String>,
+        pub retention_check_enabled: bool,
+        pub on_zero_results: String,
+        pub params: BTreeMap<String, String>,
+    }
+
+    /// The effective configuration of a dataset as interpreted by the runtime, with sensitive params redacted.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct DatasetConfigResponse {
+        pub from: String,
+        pub name: String,
+        pub mode: String,
+        pub is_view: bool,
+        pub time_column: Option<String>,
+        pub time_format: Option<String>,
+        pub params: BTreeMap<String, String>,
+        pub replication_enabled: bool,
+        pub acceleration: Option<AccelerationConfigResponse>,
+    }
+
+    impl From<&Dataset> for DatasetConfigResponse {
+        fn from(dataset: &Dataset) -> Self {
+            let mode = match dataset.mode {
+                Mode::Read => "read",
+                Mode::ReadWrite => "read_write",
+            };
+
+            Self {
+                from: dataset.from.clone(),
+                name: dataset.name.to_string(),
+                mode: mode.to_string(),
+                is_view: dataset.is_view(),
+                time_column: dataset.time_column.clone(),
+                time_format: dataset.time_format.as_ref().map(ToString::to_string),
+                params: redact_params(&dataset.params),
+                replication_enabled: dataset.replication.as_ref().is_some_and(|r| r.enabled),
+                acceleration: dataset.acceleration.as_ref().map(|acceleration| {
+                    AccelerationConfigResponse {
+                        enabled: acceleration.enabled,
+                        mode: acceleration.mode.to_string(),
+                        engine: acceleration.engine.to_string(),
+                        refresh_mode: match acceleration.refresh_mode {
+                            RefreshMode::Full => "full".to_string(),
+                            RefreshMode::Append => "append".to_string(),
+                        },
+                        refresh_check_interval: acceleration.refresh_check_interval.clone(),
+                        refresh_sql: acceleration.refresh_sql.clone(),
+                        refresh_data_window: acceleration.refresh_data_window.clone(),
+                        refresh_initial_backfill_window: acceleration
+                            .refresh_initial_backfill_window
+                            .clone(),
+                        refresh_append_dedup_rows: acceleration.refresh_append_dedup_rows,
+                        refresh_append_boundary_inclusive: acceleration
+                            .refresh_append_boundary_inclusive,
+                        retention_period: acceleration.retention_period.clone(),
+                        retention_check_interval: acceleration.retention_check_interval.clone(),
+                        retention_check_enabled: acceleration.retention_check_enabled,
+                        on_zero_results: acceleration.on_zero_results.to_string(),
+                        params: redact_params(&acceleration.params),
+                    }
+                }),
+            }
+        }
+    }
+
+    /// Returns the effective configuration of a dataset, to verify how the spicepod was interpreted.
+    pub(crate) async fn config(
+        Extension(app): Extension<Arc<RwLock<Option<App>>>>,
+        Path(dataset_name): Path<String>,
+    ) -> Response {
+        let app_lock = app.read().await;
+        let Some(readable_app) = &*app_lock else {
+            return (status::StatusCode::INTERNAL_SERVER_ERROR).into_response();
+        };
+
+        let datasets = Runtime::get_valid_datasets(readable_app, false);
+        let Some(dataset) = datasets
+            .iter()
+            .find(|d| d.name.to_string().to_lowercase() == dataset_name.to_lowercase())
+        else {
+            return (
+                status::StatusCode::NOT_FOUND,
+                Json(MessageResponse {
+                    message: format!("Dataset {dataset_name} not found"),
+                }),
+            )
+                .into_response();
+        };
+
+        (
+            status::StatusCode::OK,
+            Json(DatasetConfigResponse::from(dataset)),
+        )
+            .into_response()
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct SchemaFieldResponse {
+        pub name: String,
+        pub data_type: String,
+        pub nullable: bool,
+
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub metadata: HashMap<String, String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub(crate) struct SchemaResponse {
+        pub fields: Vec<SchemaFieldResponse>,
+
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub metadata: HashMap<String, String>,
+    }
+
+    /// Returns the Arrow schema of a loaded dataset without running a query.
+    pub(crate) async fn schema(
+        Extension(df): Extension<Arc<DataFusion>>,
+        Path(dataset_name): Path<String>,
+    ) -> Response {
+        if !df.table_exists(TableReference::from(dataset_name.as_str())) {
+            return (
+                status::StatusCode::NOT_FOUND,
+                Json(MessageResponse {
+                    message: format!("Dataset {dataset_name} not found"),
+                }),
+            )
+                .into_response();
+        }
+
+        let schema = match df.get_arrow_schema(&dataset_name).await {
+            Ok(schema) => schema,
+            Err(e) => {
+                tracing::error!("Error getting schema for dataset {dataset_name}: {e}");
+                return (status::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        };
+
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|field| SchemaFieldResponse {
+                name: field.name().to_string(),
+                data_type: field.data_type().to_string(),
+                nullable: field.is_nullable(),
+                metadata: field.metadata().clone(),
+            })
+            .collect();
+
+        (
+            status::StatusCode::OK,
+            Json(SchemaResponse {
+                fields,
+                metadata: schema.metadata().clone(),
+            }),
+        )
+            .into_response()
+    }
+
     #[derive(Deserialize)]
     pub struct AccelerationRequest {
         pub refresh_sql: Option<String>,
     }
 
+    #[derive(Debug, Deserialize)]
+    pub(crate) struct RefreshQueryParams {
+        /// Wait for the refresh to finish before responding.
+        #[serde(default)]
+        wait: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub(crate) struct RefreshResponse {
+        pub message: String,
+        pub num_rows: usize,
+    }
+
     pub(crate) async fn refresh(
         Extension(app): Extension<Arc<RwLock<Option<App>>>>,
         Extension(df): Extension<Arc<DataFusion>>,
         Path(dataset_name): Path<String>,
+        Query(params): Query<RefreshQueryParams>,
     ) -> Response {
         let app_lock = app.read().await;
         let Some(readable_app) = &*app_lock else {
@@ -475,6 +1189,30 @@ pub(crate) mod datasets {
                 .into_response();
         };
 
+        if params.wait {
+            // Don't block app reloads while waiting on the refresh
+            let name = dataset.name.clone();
+            drop(app_lock);
+
+            return match df.refresh_table_and_wait(&name).await {
+                Ok(stats) => (
+                    status::StatusCode::OK,
+                    Json(RefreshResponse {
+                        message: format!("Dataset {dataset_name} refreshed."),
+                        num_rows: stats.num_rows,
+                    }),
+                )
+                    .into_response(),
+                Err(err) => (
+                    status::StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(MessageResponse {
+                        message: format!("Failed to refresh {dataset_name}: {err}."),
+                    }),
+                )
+                    .into_response(),
+            };
+        }
+
         match df.refresh_table(&dataset.name).await {
             Ok(()) => (
                 status::StatusCode::CREATED,
@@ -925,7 +1663,7 @@ pub(crate) mod inference {
 pub(crate) mod nsql {
     use arrow_sql_gen::statement::CreateTableBuilder;
     use axum::{
-        http::StatusCode,
+        http::{HeaderMap, StatusCode},
         response::{IntoResponse, Response},
         Extension, Json,
     };
@@ -934,7 +1672,12 @@ pub(crate) mod nsql {
     use std::sync::Arc;
     use tokio::sync::RwLock;
 
-    use crate::{datafusion::DataFusion, http::v1::sql_to_http_response, LLMModelStore};
+    use crate::{
+        config,
+        datafusion::DataFusion,
+        http::v1::{sql_to_http_response, ResultsCacheRequest},
+        LLMModelStore,
+    };
 
     fn clean_model_based_sql(input: &str) -> String {
         let no_dashes = match input.strip_prefix("--") {
@@ -952,19 +1695,41 @@ pub(crate) mod nsql {
     pub struct Request {
         pub query: String,
 
-        #[serde(rename = "use", default = "default_model")]
-        pub model: String,
+        #[serde(rename = "use", default)]
+        pub model: Option<String>,
     }
 
-    fn default_model() -> String {
-        "nql".to_string()
+    const DEFAULT_MODEL: &str = "nql";
+
+    /// Selects the model for a single request, e.g. for A/B testing: the `X-Model` header takes precedence over the
+    /// `use` field of the request, which falls back to the default model.
+    fn requested_model(headers: &HeaderMap, payload: &Request) -> String {
+        headers
+            .get("X-Model")
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .or(payload.model.as_deref())
+            .unwrap_or(DEFAULT_MODEL)
+            .to_string()
     }
 
     pub(crate) async fn post(
         Extension(df): Extension<Arc<DataFusion>>,
         Extension(nsql_models): Extension<Arc<RwLock<LLMModelStore>>>,
+        Extension(config): Extension<Arc<config::Config>>,
+        headers: HeaderMap,
         Json(payload): Json<Request>,
     ) -> Response {
+        let model = requested_model(&headers, &payload);
+        if !nsql_models.read().await.contains_key(&model) {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("Model {model} is not loaded"),
+            )
+                .into_response();
+        }
+
         // Get all public table CREATE TABLE statements to add to prompt.
         let tables = match df.get_public_table_names() {
             Ok(t) => t,
@@ -999,12 +1764,12 @@ pub(crate) mod nsql {
 
         tracing::trace!("Running prompt: {nsql_query}");
 
-        let result = match nsql_models.read().await.get(&payload.model) {
+        let result = match nsql_models.read().await.get(&model) {
             Some(nql_model) => nql_model.write().await.run(nsql_query).await,
             None => {
                 return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Model {} not found", payload.model),
+                    StatusCode::NOT_FOUND,
+                    format!("Model {model} is not loaded"),
                 )
                     .into_response()
             }
@@ -1024,8 +1789,12 @@ pub(crate) mod nsql {
                 sql_to_http_response(
                     Arc::clone(&df),
                     &cleaned_query,
+                    vec![],
                     Some(restricted_sql_options),
                     Some(nsql_query_copy),
+                    ResultsCacheRequest::default(),
+                    config.max_response_bytes,
+                    JsonFormat::from(config.as_ref()),
                 )
                 .await
             }
@@ -1178,3 +1947,497 @@ pub(crate) mod embed {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = "SELECT * FROM (VALUES (1, 'a'), (2, 'b'), (3, 'c')) AS t(id, name)";
+
+    #[tokio::test]
+    async fn test_sql_response_exceeding_max_response_bytes() {
+        let df = Arc::new(DataFusion::new());
+
+        let response = sql_to_http_response(
+            Arc::clone(&df),
+            QUERY,
+            vec![],
+            None,
+            None,
+            ResultsCacheRequest::default(),
+            Some(16),
+            JsonFormat::default(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = sql_to_http_response(
+            df,
+            QUERY,
+            vec![],
+            None,
+            None,
+            ResultsCacheRequest::default(),
+            Some(1024),
+            JsonFormat::default(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    async fn parameterized_query(parameters: serde_json::Value) -> Response {
+        let parameters = match parameters {
+            serde_json::Value::Array(parameters) => parameters,
+            _ => panic!("parameters must be an array"),
+        };
+        sql_to_http_response(
+            Arc::new(DataFusion::new()),
+            &format!("SELECT name FROM ({QUERY}) WHERE id = $1"),
+            query::parameter_values(parameters).expect("valid parameters"),
+            None,
+            None,
+            ResultsCacheRequest::default(),
+            None,
+            JsonFormat::default(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_explain_analyze() {
+        let response = sql_to_http_response(
+            Arc::new(DataFusion::new()),
+            &query::explain_analyze(QUERY),
+            vec![],
+            None,
+            None,
+            ResultsCacheRequest::default(),
+            None,
+            JsonFormat::default(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("X-Cache"), None);
+        for header in [
+            "X-Query-Planning-Time-Ms",
+            "X-Query-Execution-Time-Ms",
+            "X-Query-Bytes-Scanned",
+            "X-Query-Partitions",
+        ] {
+            assert!(response.headers().contains_key(header), "missing {header}");
+        }
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        let plans: Vec<serde_json::Value> =
+            serde_json::from_slice(&body).expect("JSON response body");
+        let plan = plans
+            .iter()
+            .find(|plan| plan["plan_type"] == "Plan with Metrics")
+            .and_then(|plan| plan["plan"].as_str())
+            .expect("plan annotated with metrics");
+        assert!(plan.contains("output_rows=3"), "{plan}");
+        assert!(plan.contains("elapsed_compute="), "{plan}");
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_with_bound_parameters() {
+        let response = parameterized_query(serde_json::json!([2])).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        assert_eq!(body.as_ref(), br#"[{"name":"b"}]"#);
+
+        let response = parameterized_query(serde_json::json!([2, "extra"])).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = parameterized_query(serde_json::json!([])).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn conditional_query(df: &Arc<DataFusion>, if_none_match: Option<&str>) -> Response {
+        let results_cache = ResultsCacheRequest {
+            if_none_match: if_none_match.map(ToString::to_string),
+            ..Default::default()
+        };
+        sql_to_http_response(
+            Arc::clone(df),
+            QUERY,
+            vec![],
+            None,
+            None,
+            results_cache,
+            None,
+            JsonFormat::default(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_if_none_match() {
+        fn etag(response: &Response) -> Option<String> {
+            response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        }
+
+        let cache_provider =
+            cache::QueryResultsCacheProvider::new(&spicepod::component::runtime::ResultsCache {
+                item_ttl: Some("60s".to_string()),
+                ..Default::default()
+            })
+            .expect("cache provider should be created");
+        let df = Arc::new(DataFusion::new_with_cache_provider(Some(Arc::new(
+            cache_provider,
+        ))));
+
+        let response = conditional_query(&df, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let cached = etag(&response).expect("a cached result has an ETag");
+
+        let response = conditional_query(&df, Some(&cached)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(
+            response
+                .headers()
+                .get("X-Cache")
+                .and_then(|v| v.to_str().ok()),
+            Some("Hit from spiceai")
+        );
+        assert_eq!(etag(&response), Some(cached.clone()));
+
+        let response = conditional_query(&df, Some("\"stale\"")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(etag(&response), Some(cached.clone()));
+
+        // A result cached again gets a new ETag
+        let other = Arc::new(DataFusion::new_with_cache_provider(Some(Arc::new(
+            cache::QueryResultsCacheProvider::new(&spicepod::component::runtime::ResultsCache {
+                item_ttl: Some("60s".to_string()),
+                ..Default::default()
+            })
+            .expect("cache provider should be created"),
+        ))));
+        let response = conditional_query(&other, Some(&cached)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(etag(&response), Some(cached));
+
+        let uncached = Arc::new(DataFusion::new());
+        let response = conditional_query(&uncached, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(etag(&response), None, "only cached results have an ETag");
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_in_session_timezone() {
+        let df = Arc::new(DataFusion::new());
+        df.set_session_timezone("+05:00")
+            .await
+            .expect("valid timezone");
+        assert!(df.set_session_timezone("Not/AZone").await.is_err());
+
+        let response = sql_to_http_response(
+            df,
+            "SELECT CAST('2024-01-01T00:00:00Z' AS TIMESTAMPTZ) AS t",
+            vec![],
+            None,
+            None,
+            ResultsCacheRequest::default(),
+            None,
+            JsonFormat::default(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        assert_eq!(body.as_ref(), br#"[{"t":"2024-01-01T05:00:00+05:00"}]"#);
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_number_formatting() {
+        let sql = "SELECT CAST('12345678901234567.89' AS DECIMAL(20, 2)) AS d, 1.23456 AS f";
+        let query = |json_format| {
+            sql_to_http_response(
+                Arc::new(DataFusion::new()),
+                sql,
+                vec![],
+                None,
+                None,
+                ResultsCacheRequest::default(),
+                None,
+                json_format,
+            )
+        };
+
+        let response = query(JsonFormat {
+            float_precision: Some(2),
+            ..JsonFormat::default()
+        })
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        assert_eq!(body.as_ref(), br#"[{"d":"12345678901234567.89","f":1.23}]"#);
+
+        let response = query(JsonFormat {
+            decimals_as_strings: false,
+            ..JsonFormat::default()
+        })
+        .await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        assert_eq!(
+            body.as_ref(),
+            br#"[{"d":12345678901234567.89,"f":1.23456}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_non_finite_floats() {
+        let sql = "SELECT * FROM (VALUES (1.5), ('NaN'::double), ('inf'::double), ('-inf'::double)) AS t(f)";
+        let query = |non_finite_floats| {
+            sql_to_http_response(
+                Arc::new(DataFusion::new()),
+                sql,
+                vec![],
+                None,
+                None,
+                ResultsCacheRequest::default(),
+                None,
+                JsonFormat {
+                    non_finite_floats,
+                    ..JsonFormat::default()
+                },
+            )
+        };
+
+        let response = query(NonFiniteFloats::Null).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        assert_eq!(body.as_ref(), br#"[{"f":1.5},{},{},{}]"#);
+
+        let response = query(NonFiniteFloats::String).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        assert_eq!(
+            body.as_ref(),
+            br#"[{"f":"1.5"},{"f":"NaN"},{"f":"Infinity"},{"f":"-Infinity"}]"#
+        );
+
+        let response = query(NonFiniteFloats::Error).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_sql_response_binary_encoding() {
+        let bytes: &[u8] = &[0x00, 0xde, 0xad, 0xbe, 0xef, 0xff];
+        let sql = "SELECT X'00DEADBEEFFF' AS b, arrow_cast(X'00DEADBEEFFF', 'LargeBinary') AS l";
+        let query = |binary_encoding| async move {
+            let response = sql_to_http_response(
+                Arc::new(DataFusion::new()),
+                sql,
+                vec![],
+                None,
+                None,
+                ResultsCacheRequest::default(),
+                None,
+                JsonFormat {
+                    binary_encoding,
+                    ..JsonFormat::default()
+                },
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("response body");
+            let rows: Vec<serde_json::Value> =
+                serde_json::from_slice(&body).expect("JSON response body");
+            ["b", "l"].map(|column| {
+                rows[0][column]
+                    .as_str()
+                    .expect("binary is encoded as a string")
+                    .to_string()
+            })
+        };
+
+        for encoded in query(BinaryEncoding::Base64).await {
+            assert_eq!(encoded, "AN6tvu//");
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .expect("valid base64");
+            assert_eq!(decoded, bytes);
+        }
+
+        for encoded in query(BinaryEncoding::Hex).await {
+            assert_eq!(encoded, "00deadbeefff");
+            let decoded = (0..encoded.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&encoded[i..i + 2], 16).expect("valid hex"))
+                .collect::<Vec<_>>();
+            assert_eq!(decoded, bytes);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dataset_schema() {
+        use arrow::datatypes::{DataType, Field, Schema};
+        use axum::{extract::Path, Extension};
+        use datafusion::datasource::MemTable;
+
+        let df = Arc::new(DataFusion::new());
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        df.ctx
+            .register_table(
+                "schema_test",
+                Arc::new(MemTable::try_new(schema, vec![vec![]]).expect("mem table created")),
+            )
+            .expect("table registered");
+
+        let response =
+            datasets::schema(Extension(Arc::clone(&df)), Path("schema_test".to_string())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body read");
+        let schema: datasets::SchemaResponse =
+            serde_json::from_slice(&body).expect("valid schema response");
+        let fields = schema
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    field.name.as_str(),
+                    field.data_type.as_str(),
+                    field.nullable,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec![("id", "Int64", false), ("name", "Utf8", true)]);
+
+        let response = datasets::schema(Extension(df), Path("missing".to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_dataset_config_reflects_acceleration_and_refresh_settings() {
+        use crate::component::dataset::acceleration::{Acceleration, Engine, RefreshMode};
+        use std::collections::HashMap;
+
+        let mut dataset =
+            Dataset::try_new("postgres:orders".to_string(), "orders").expect("a valid dataset");
+        dataset.time_column = Some("created_at".to_string());
+        dataset.params = HashMap::from([
+            ("pg_host".to_string(), "localhost".to_string()),
+            ("pg_pass".to_string(), "hunter2".to_string()),
+        ]);
+        dataset.acceleration = Some(Acceleration {
+            engine: Engine::DuckDB,
+            refresh_mode: RefreshMode::Append,
+            refresh_check_interval: Some("10s".to_string()),
+            refresh_sql: Some("SELECT * FROM orders WHERE status = 'open'".to_string()),
+            ..Default::default()
+        });
+
+        let config = datasets::DatasetConfigResponse::from(&dataset);
+        assert_eq!(config.time_column.as_deref(), Some("created_at"));
+        assert_eq!(
+            config.params.get("pg_host").map(String::as_str),
+            Some("localhost")
+        );
+        assert_eq!(
+            config.params.get("pg_pass").map(String::as_str),
+            Some("[REDACTED]")
+        );
+
+        let acceleration = config.acceleration.expect("acceleration config");
+        assert_eq!(acceleration.engine, "duckdb");
+        assert_eq!(acceleration.refresh_mode, "append");
+        assert_eq!(acceleration.refresh_check_interval.as_deref(), Some("10s"));
+        assert_eq!(
+            acceleration.refresh_sql.as_deref(),
+            Some("SELECT * FROM orders WHERE status = 'open'")
+        );
+    }
+
+    struct NamedModel(&'static str);
+
+    #[async_trait::async_trait]
+    impl llms::nql::Nql for NamedModel {
+        async fn run(&mut self, _prompt: String) -> llms::nql::Result<Option<String>> {
+            Ok(Some(format!("SELECT '{}' AS model", self.0)))
+        }
+    }
+
+    async fn nsql_with_models(headers: HeaderMap, payload: serde_json::Value) -> Response {
+        use axum::{Extension, Json};
+        use clap::Parser;
+
+        let models: crate::LLMModelStore = ["nql", "candidate"]
+            .into_iter()
+            .map(|name| {
+                let model: Box<dyn llms::nql::Nql> = Box::new(NamedModel(name));
+                (name.to_string(), tokio::sync::RwLock::new(model))
+            })
+            .collect();
+        let config = crate::config::Config::parse_from(["spiced"]);
+
+        nsql::post(
+            Extension(Arc::new(DataFusion::new())),
+            Extension(Arc::new(tokio::sync::RwLock::new(models))),
+            Extension(Arc::new(config)),
+            headers,
+            Json(serde_json::from_value(payload).expect("valid request")),
+        )
+        .await
+    }
+
+    async fn answering_model(response: Response) -> String {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        let rows: serde_json::Value = serde_json::from_slice(&body).expect("JSON response body");
+        rows[0]["model"].as_str().expect("model column").to_string()
+    }
+
+    #[tokio::test]
+    async fn test_nsql_model_override() {
+        use axum::http::HeaderValue;
+
+        let query = serde_json::json!({ "query": "how many orders?" });
+        let response = nsql_with_models(HeaderMap::new(), query.clone()).await;
+        assert_eq!(answering_model(response).await, "nql");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Model", HeaderValue::from_static("candidate"));
+        let response = nsql_with_models(headers, query.clone()).await;
+        assert_eq!(answering_model(response).await, "candidate");
+
+        let response = nsql_with_models(
+            HeaderMap::new(),
+            serde_json::json!({ "query": "how many orders?", "use": "candidate" }),
+        )
+        .await;
+        assert_eq!(answering_model(response).await, "candidate");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Model", HeaderValue::from_static("missing"));
+        let response = nsql_with_models(headers, query).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -19,6 +19,7 @@ limitations under the License.
 use std::borrow::Borrow;
 use std::collections::HashSet;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::time::Duration;
 use std::{collections::HashMap, sync::Arc};
 
@@ -35,10 +36,11 @@ use app::App;
 use cache::QueryResultsCacheProvider;
 use component::dataset::{self, Dataset};
 use config::Config;
+use datafusion::plan_cache::{self, PlanCache};
 use datafusion::query::query_history;
 use datafusion::SPICE_RUNTIME_SCHEMA;
 use futures::future::join_all;
-use futures::StreamExt;
+use futures::{Future, StreamExt};
 use llms::embeddings::Embed;
 use llms::nql::Nql;
 use metrics::SetRecorderError;
@@ -67,13 +69,17 @@ pub mod extension;
 mod flight;
 mod http;
 pub mod internal_table;
+pub mod lineage;
 pub mod model;
 pub mod object_store_registry;
 pub mod objectstore;
 mod opentelemetry;
 pub mod podswatcher;
 pub mod spice_metrics;
+pub mod startup_report;
 pub mod status;
+#[cfg(test)]
+pub(crate) mod test_util;
 pub mod timing;
 pub(crate) mod tracers;
 
@@ -161,6 +167,11 @@ pub enum Error {
     ))]
     DatasetNameIncludesCatalog { catalog: Arc<str>, name: Arc<str> },
 
+    #[snafu(display(
+        "Invalid time_format for dataset {dataset}: {pattern:?} is not a valid strftime pattern"
+    ))]
+    InvalidTimeFormat { dataset: String, pattern: String },
+
     #[snafu(display("Unable to load dataset connector: {dataset}"))]
     UnableToLoadDatasetConnector { dataset: TableReference },
 
@@ -189,6 +200,9 @@ pub enum Error {
 
     #[snafu(display("Unable to register metrics table: {source}"))]
     UnableToRegisterMetricsTable { source: datafusion::Error },
+
+    #[snafu(display("Components didn't load within the ready timeout of {ready_timeout:?}"))]
+    ReadyTimeout { ready_timeout: Duration },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -219,6 +233,29 @@ impl Runtime {
         dataconnector::register_all().await;
         dataaccelerator::register_all().await;
 
+        if let Some(parallel_num) = app
+            .as_ref()
+            .and_then(|app| app.runtime.num_of_parallel_refreshes_per_accelerator_file)
+        {
+            dataaccelerator::set_max_parallel_refreshes_per_accelerator_file(parallel_num);
+        }
+
+        if let Some(user_agent) = app.as_ref().and_then(|app| app.runtime.user_agent.as_ref()) {
+            util::http::set_user_agent(user_agent.clone());
+        }
+
+        if let Some(proxy) = app.as_ref().and_then(|app| app.runtime.http_proxy.as_ref()) {
+            let proxy_config = util::http::ProxyConfig {
+                url: proxy.url.clone(),
+                no_proxy: proxy.no_proxy.clone(),
+                username: proxy.username.clone(),
+                password: proxy.password.clone(),
+            };
+            if let Err(err) = util::http::set_proxy(&proxy_config) {
+                tracing::error!("Invalid HTTP proxy configuration: {err}");
+            }
+        }
+
         let mut rt = Runtime {
             app: Arc::new(RwLock::new(app)),
             df: Arc::new(DataFusion::new()),
@@ -252,6 +289,41 @@ impl Runtime {
         Arc::clone(&self.df)
     }
 
+    /// Initializes the query history and caches, and loads the extensions, datasets and models of the app.
+    pub async fn load_components(&self) {
+        let mut futures: Vec<Pin<Box<dyn Future<Output = ()> + '_>>> = vec![
+            Box::pin(async {
+                if let Err(err) = self.init_query_history().await {
+                    tracing::warn!("Creating internal query history table: {err}");
+                };
+            }),
+            Box::pin(self.init_results_cache()),
+            Box::pin(self.init_plan_cache()),
+            Box::pin(self.start_extensions()),
+            Box::pin(self.load_datasets()),
+        ];
+
+        if cfg!(feature = "models") {
+            let mut v: Vec<Pin<Box<dyn Future<Output = ()> + '_>>> = vec![
+                Box::pin(self.load_models()),
+                Box::pin(self.load_llms()),
+                Box::pin(self.load_embeddings()),
+            ];
+
+            futures.append(&mut v);
+        }
+
+        join_all(futures).await;
+    }
+
+    /// Like `load_components`, but fails if the components haven't loaded within `ready_timeout`, i.e. because a
+    /// dataset keeps failing to load. Loading is cancelled once the timeout elapses.
+    pub async fn load_components_with_timeout(&self, ready_timeout: Duration) -> Result<()> {
+        tokio::time::timeout(ready_timeout, self.load_components())
+            .await
+            .map_err(|_| Error::ReadyTimeout { ready_timeout })
+    }
+
     pub async fn start_extensions(&self) {
         let mut extensions = self.extensions.write().await;
         for i in 0..extensions.len() {
@@ -315,27 +387,60 @@ impl Runtime {
         };
 
         let valid_datasets = Self::get_valid_datasets(app, true);
+
+        if app.runtime.dependency_ordered_refresh {
+            match lineage::dependency_order(&valid_datasets) {
+                Ok(levels) => {
+                    for ds in &valid_datasets {
+                        status::update_dataset(&ds.name, status::ComponentStatus::Initializing);
+                    }
+                    for level in levels {
+                        load_concurrently(
+                            level
+                                .iter()
+                                .map(|ds| self.load_dataset_and_wait(ds, &valid_datasets)),
+                            app.runtime.num_of_parallel_loading_at_start_up,
+                        )
+                        .await;
+                    }
+                    return;
+                }
+                Err(err) => {
+                    tracing::error!("Unable to order dataset refreshes by their dependencies, loading datasets without ordering: {err}");
+                }
+            }
+        }
+
         let mut futures = vec![];
         for ds in &valid_datasets {
             status::update_dataset(&ds.name, status::ComponentStatus::Initializing);
             futures.push(self.load_dataset(ds, &valid_datasets));
         }
 
-        let app = self.app.read().await;
-
-        if let Some(app) = app.as_ref() {
-            if let Some(parallel_num) = app.runtime.num_of_parallel_loading_at_start_up {
-                let stream = futures::stream::iter(futures).buffer_unordered(parallel_num);
-                let _ = stream.collect::<Vec<_>>().await;
-                return;
-            }
-        }
-
-        let _ = join_all(futures).await;
+        load_concurrently(futures, app.runtime.num_of_parallel_loading_at_start_up).await;
     }
 
     // Caller must set `status::update_dataset(...` before calling `load_dataset`. This function will set error/ready statuses appropriately.`
     pub async fn load_dataset(&self, ds: &Dataset, all_datasets: &[Dataset]) {
+        self.load_dataset_with_retries(ds, all_datasets, false)
+            .await;
+    }
+
+    /// Loads a dataset like `load_dataset`, but for accelerated datasets only returns once the initial refresh has
+    /// completed, so that datasets reading from it see its data.
+    async fn load_dataset_and_wait(&self, ds: &Dataset, all_datasets: &[Dataset]) {
+        self.load_dataset_with_retries(ds, all_datasets, ds.is_accelerated())
+            .await;
+    }
+
+    /// Retries loading a dataset every second until it is registered. With `wait_for_refresh`, its accelerated table
+    /// is only registered once the initial refresh has completed.
+    async fn load_dataset_with_retries(
+        &self,
+        ds: &Dataset,
+        all_datasets: &[Dataset],
+        wait_for_refresh: bool,
+    ) {
         let spaced_tracer = Arc::clone(&self.spaced_tracer);
 
         loop {
@@ -350,8 +455,17 @@ impl Runtime {
                 }
             };
 
-            if let Ok(()) = self.register_loaded_dataset(ds, connector, None).await {
+            let registered = if wait_for_refresh {
+                self.reload_accelerated_dataset(ds, connector).await
             } else {
+                self.register_loaded_dataset(ds, connector, None).await
+            };
+            if let Err(err) = registered {
+                // `register_loaded_dataset` reports its own errors, but not those creating the accelerated table
+                if wait_for_refresh {
+                    status::update_dataset(&ds.name, status::ComponentStatus::Error);
+                    warn_spaced!(spaced_tracer, "{}{err}", "");
+                }
                 sleep(Duration::from_secs(1)).await;
                 continue;
             }
@@ -991,6 +1105,69 @@ impl Runtime {
         };
     }
 
+    pub async fn init_plan_cache(&self) {
+        let app = self.app.read().await;
+        let Some(app) = app.as_ref() else { return };
+
+        let plan_cache_config = &app.runtime.plan_cache;
+
+        if !plan_cache_config.enabled {
+            return;
+        }
+
+        let max_entries = plan_cache_config
+            .max_entries
+            .unwrap_or(plan_cache::DEFAULT_MAX_ENTRIES);
+        tracing::info!("Initialized plan cache; max entries: {max_entries}");
+        self.datafusion()
+            .set_plan_cache(PlanCache::new(max_entries));
+    }
+
+    /// Sets the session timezone of all queries, preferring `timezone` from the command line over the spicepod.
+    pub async fn init_session_timezone(&self, timezone: Option<&str>) {
+        let app_timezone = self
+            .app
+            .read()
+            .await
+            .as_ref()
+            .and_then(|app| app.runtime.session_timezone.clone());
+        let Some(timezone) = timezone.map(ToString::to_string).or(app_timezone) else {
+            return;
+        };
+
+        match self.datafusion().set_session_timezone(&timezone).await {
+            Ok(()) => tracing::info!("Set session timezone to {timezone}"),
+            Err(e) => tracing::warn!("{e}; timestamps are rendered in UTC"),
+        }
+    }
+
+    /// Writes a JSON summary of the loaded datasets and models to `path`.
+    pub async fn write_startup_report(&self, path: &std::path::Path) {
+        let report = startup_report::StartupReport::new(self).await;
+        match report.write(path) {
+            Ok(()) => tracing::info!("Wrote startup report to {}", path.display()),
+            Err(e) => tracing::warn!("{e}"),
+        }
+    }
+
+    /// Sets the parallelism of all queries, preferring `parallelism` from the command line over the spicepod.
+    pub async fn init_query_parallelism(&self, parallelism: Option<usize>) {
+        let app_parallelism = self
+            .app
+            .read()
+            .await
+            .as_ref()
+            .and_then(|app| app.runtime.query_parallelism);
+        let Some(parallelism) = parallelism.or(app_parallelism) else {
+            return;
+        };
+
+        match self.datafusion().set_query_parallelism(parallelism).await {
+            Ok(()) => tracing::info!("Set query parallelism to {parallelism}"),
+            Err(e) => tracing::warn!("{e}; queries run with the default parallelism"),
+        }
+    }
+
     pub async fn init_query_history(&self) -> Result<()> {
         let query_history_table_reference = TableReference::partial(
             SPICE_RUNTIME_SCHEMA,
@@ -1006,6 +1183,24 @@ impl Runtime {
     }
 }
 
+/// Runs the dataset loading `futures` concurrently, at most `parallel_num` at a time if set.
+async fn load_concurrently<F: Future<Output = ()>>(
+    futures: impl IntoIterator<Item = F>,
+    parallel_num: Option<usize>,
+) {
+    match parallel_num {
+        Some(parallel_num) => {
+            futures::stream::iter(futures)
+                .buffer_unordered(parallel_num)
+                .collect::<Vec<_>>()
+                .await;
+        }
+        None => {
+            join_all(futures).await;
+        }
+    }
+}
+
 fn verify_dependent_tables(ds: &Dataset, existing_tables: &[TableReference]) -> bool {
     if !ds.is_view() {
         return true;
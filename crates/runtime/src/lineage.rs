@@ -0,0 +1,241 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Dataset lineage derived from the tables referenced by view definitions and acceleration `refresh_sql`.
+
+use datafusion::{
+    error::DataFusionError,
+    execution::context::SessionContext,
+    sql::{parser::DFParser, TableReference},
+};
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::component::dataset::{self, Dataset};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to load view SQL for dataset {dataset}: {source}"))]
+    UnableToLoadViewSql {
+        dataset: String,
+        source: dataset::Error,
+    },
+
+    #[snafu(display("Unable to parse SQL for dataset {dataset}: {source}"))]
+    UnableToParseSql {
+        dataset: String,
+        source: DataFusionError,
+    },
+
+    #[snafu(display("Datasets {datasets} depend on each other in a cycle. Remove one of the references from their refresh_sql or view definitions."))]
+    DependencyCycle { datasets: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A dependency of `dataset` on the table `depends_on`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineageEdge {
+    pub dataset: String,
+    pub depends_on: String,
+}
+
+/// Returns the tables the dataset's view definition and `refresh_sql` read from, excluding the dataset itself, sorted
+/// by name.
+pub fn dataset_dependencies(dataset: &Dataset) -> Result<Vec<TableReference>> {
+    let dataset_name = dataset.name.to_string();
+
+    let mut sqls = vec![];
+    if let Some(view_sql) = dataset.view_sql() {
+        sqls.push(view_sql.context(UnableToLoadViewSqlSnafu {
+            dataset: dataset_name.clone(),
+        })?);
+    }
+    if let Some(refresh_sql) = dataset.refresh_sql() {
+        sqls.push(refresh_sql);
+    }
+
+    let state = SessionContext::new().state();
+    let mut dependencies: Vec<TableReference> = vec![];
+    for sql in sqls {
+        let statements = DFParser::parse_sql(&sql)
+            .map_err(DataFusionError::from)
+            .context(UnableToParseSqlSnafu {
+                dataset: dataset_name.clone(),
+            })?;
+
+        for statement in &statements {
+            let references =
+                state
+                    .resolve_table_references(statement)
+                    .context(UnableToParseSqlSnafu {
+                        dataset: dataset_name.clone(),
+                    })?;
+
+            for reference in references {
+                if reference.resolved_eq(&dataset.name) || dependencies.contains(&reference) {
+                    continue;
+                }
+                dependencies.push(reference);
+            }
+        }
+    }
+
+    // Table references are resolved in no particular order
+    dependencies.sort_by_key(ToString::to_string);
+
+    Ok(dependencies)
+}
+
+/// Builds the dependency edges between all datasets. Datasets whose SQL can't be analyzed are skipped.
+#[must_use]
+pub fn lineage(datasets: &[Dataset]) -> Vec<LineageEdge> {
+    datasets
+        .iter()
+        .flat_map(|dataset| match dataset_dependencies(dataset) {
+            Ok(dependencies) => dependencies
+                .into_iter()
+                .map(|depends_on| LineageEdge {
+                    dataset: dataset.name.to_string(),
+                    depends_on: depends_on.to_string(),
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Unable to determine lineage: {e}");
+                vec![]
+            }
+        })
+        .collect()
+}
+
+/// Groups datasets into levels that only depend on datasets in earlier levels, so upstream datasets can be
+/// refreshed before the datasets that read from them. Dependencies on tables that aren't datasets are ignored.
+pub fn dependency_order(datasets: &[Dataset]) -> Result<Vec<Vec<Dataset>>> {
+    let mut pending = datasets
+        .iter()
+        .map(|dataset| {
+            let dependencies = dataset_dependencies(dataset)?
+                .into_iter()
+                .filter(|dependency| {
+                    datasets
+                        .iter()
+                        .any(|other| other.name.resolved_eq(dependency))
+                })
+                .collect::<Vec<_>>();
+            Ok((dataset.clone(), dependencies))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut levels: Vec<Vec<Dataset>> = vec![];
+    while !pending.is_empty() {
+        let (ready, blocked): (Vec<_>, Vec<_>) =
+            pending.into_iter().partition(|(_, dependencies)| {
+                dependencies.iter().all(|dependency| {
+                    levels
+                        .iter()
+                        .flatten()
+                        .any(|loaded| loaded.name.resolved_eq(dependency))
+                })
+            });
+
+        if ready.is_empty() {
+            return DependencyCycleSnafu {
+                datasets: blocked
+                    .iter()
+                    .map(|(dataset, _)| dataset.name.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }
+            .fail();
+        }
+
+        levels.push(ready.into_iter().map(|(dataset, _)| dataset).collect());
+        pending = blocked;
+    }
+
+    Ok(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::dataset::acceleration::Acceleration;
+
+    #[test]
+    fn test_refresh_sql_joining_datasets_reports_dependencies() {
+        let mut dataset =
+            Dataset::try_new("localhost".to_string(), "orders_enriched").expect("a valid dataset");
+        dataset.acceleration = Some(Acceleration {
+            refresh_sql: Some(
+                "SELECT o.* FROM orders_enriched o \
+                 JOIN orders ON o.id = orders.id \
+                 JOIN customers c ON orders.customer_id = c.id"
+                    .to_string(),
+            ),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            lineage(&[dataset]),
+            vec![
+                LineageEdge {
+                    dataset: "orders_enriched".to_string(),
+                    depends_on: "customers".to_string(),
+                },
+                LineageEdge {
+                    dataset: "orders_enriched".to_string(),
+                    depends_on: "orders".to_string(),
+                },
+            ]
+        );
+    }
+
+    fn accelerated_dataset(name: &str, refresh_sql: Option<&str>) -> Dataset {
+        let mut dataset = Dataset::try_new("localhost".to_string(), name).expect("a valid dataset");
+        dataset.acceleration = Some(Acceleration {
+            refresh_sql: refresh_sql.map(ToString::to_string),
+            ..Default::default()
+        });
+        dataset
+    }
+
+    #[test]
+    fn test_dependency_order_refreshes_upstream_first() {
+        let a = accelerated_dataset("a", Some("SELECT * FROM a JOIN b ON a.id = b.id"));
+        let b = accelerated_dataset("b", None);
+
+        let levels = dependency_order(&[a, b]).expect("no dependency cycle");
+        let names = levels
+            .iter()
+            .map(|level| {
+                level
+                    .iter()
+                    .map(|dataset| dataset.name.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![vec!["b".to_string()], vec!["a".to_string()]]);
+    }
+
+    #[test]
+    fn test_dependency_order_detects_cycles() {
+        let a = accelerated_dataset("a", Some("SELECT * FROM a JOIN b ON a.id = b.id"));
+        let b = accelerated_dataset("b", Some("SELECT * FROM b JOIN a ON a.id = b.id"));
+
+        let err = dependency_order(&[a, b]).expect_err("cycle should be detected");
+        assert!(matches!(err, Error::DependencyCycle { ref datasets } if datasets == "a, b"));
+    }
+}
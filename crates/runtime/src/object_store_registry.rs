@@ -14,11 +14,13 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use datafusion::{
     error::DataFusionError,
     execution::{
+        disk_manager::DiskManagerConfig,
+        memory_pool::FairSpillPool,
         object_store::{DefaultObjectStoreRegistry, ObjectStoreRegistry},
         runtime_env::{RuntimeConfig, RuntimeEnv},
     },
@@ -172,11 +174,22 @@ impl ObjectStoreRegistry for SpiceObjectStoreRegistry {
 // This method uses unwrap_or_default, however it should never fail on the initialization. See
 // RuntimeEnv::default()
 pub(crate) fn default_runtime_env() -> Arc<RuntimeEnv> {
-    Arc::new(
-        RuntimeEnv::new(
-            RuntimeConfig::default()
-                .with_object_store_registry(Arc::new(SpiceObjectStoreRegistry::default())),
-        )
-        .unwrap_or_default(),
-    )
+    runtime_env(None, None)
+}
+
+/// Like [`default_runtime_env`], but when `max_memory` is set, sorts, joins and aggregations share a pool of
+/// `max_memory` bytes. Operators that can spill to disk do so when the pool is exhausted, others fail the query.
+///
+/// Spill files are written to `temp_dir`, or the OS temp directory if it isn't set.
+pub(crate) fn runtime_env(max_memory: Option<usize>, temp_dir: Option<PathBuf>) -> Arc<RuntimeEnv> {
+    let mut config = RuntimeConfig::default()
+        .with_object_store_registry(Arc::new(SpiceObjectStoreRegistry::default()));
+    if let Some(max_memory) = max_memory {
+        config = config.with_memory_pool(Arc::new(FairSpillPool::new(max_memory)));
+    }
+    if let Some(temp_dir) = temp_dir {
+        config = config.with_disk_manager(DiskManagerConfig::NewSpecified(vec![temp_dir]));
+    }
+
+    Arc::new(RuntimeEnv::new(config).unwrap_or_default())
 }
@@ -0,0 +1,169 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! A summary of the components loaded at startup, written once loading completes to help debugging deployments.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::component::dataset::acceleration::RefreshMode;
+use crate::component::dataset::Dataset;
+use crate::status::ComponentStatus;
+use crate::Runtime;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unable to serialize the startup report: {source}"))]
+    UnableToSerializeReport { source: serde_json::Error },
+
+    #[snafu(display("Unable to write the startup report to {path}: {source}"))]
+    UnableToWriteReport {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartupReport {
+    pub app: Option<String>,
+    pub datasets: Vec<DatasetReport>,
+    pub models: Vec<ModelReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetReport {
+    pub name: String,
+    pub from: String,
+
+    /// The data connector of the dataset, unknown if its definition is invalid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acceleration: Option<AccelerationReport>,
+    pub status: ComponentStatus,
+
+    /// Why the dataset isn't loaded, if its definition is invalid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccelerationReport {
+    pub engine: String,
+    pub mode: String,
+    pub refresh_mode: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelReport {
+    pub name: String,
+    pub from: String,
+    pub status: ComponentStatus,
+}
+
+impl StartupReport {
+    /// Reports the datasets and models of the runtime's app, and whether they have been loaded.
+    pub async fn new(rt: &Runtime) -> Self {
+        let app_lock = rt.app.read().await;
+        let Some(app) = app_lock.as_ref() else {
+            return Self {
+                app: None,
+                datasets: vec![],
+                models: vec![],
+            };
+        };
+
+        let df = rt.datafusion();
+        let datasets = Runtime::datasets_iter(app)
+            .zip(&app.datasets)
+            .map(|(dataset, spicepod_dataset)| match dataset {
+                Ok(dataset) => {
+                    let status = if df.table_exists(dataset.name.clone()) {
+                        ComponentStatus::Ready
+                    } else {
+                        ComponentStatus::Error
+                    };
+                    DatasetReport {
+                        name: dataset.name.to_string(),
+                        from: dataset.from.clone(),
+                        source: Some(dataset.source()),
+                        acceleration: acceleration_report(&dataset),
+                        status,
+                        error: None,
+                    }
+                }
+                Err(err) => DatasetReport {
+                    name: spicepod_dataset.name.clone(),
+                    from: spicepod_dataset.from.clone(),
+                    source: None,
+                    acceleration: None,
+                    status: ComponentStatus::Error,
+                    error: Some(err.to_string()),
+                },
+            })
+            .collect();
+
+        let loaded_models = rt.models.read().await;
+        let models = app
+            .models
+            .iter()
+            .map(|model| ModelReport {
+                name: model.name.clone(),
+                from: model.from.clone(),
+                status: if loaded_models.contains_key(&model.name) {
+                    ComponentStatus::Ready
+                } else {
+                    ComponentStatus::Error
+                },
+            })
+            .collect();
+
+        Self {
+            app: Some(app.name.clone()),
+            datasets,
+            models,
+        }
+    }
+
+    /// Writes the report to `path` as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let report = serde_json::to_vec_pretty(self).context(UnableToSerializeReportSnafu)?;
+        std::fs::write(path, report).context(UnableToWriteReportSnafu {
+            path: path.display().to_string(),
+        })
+    }
+}
+
+fn acceleration_report(dataset: &Dataset) -> Option<AccelerationReport> {
+    let acceleration = dataset.acceleration.as_ref().filter(|a| a.enabled)?;
+    Some(AccelerationReport {
+        engine: acceleration.engine.to_string(),
+        mode: acceleration.mode.to_string(),
+        refresh_mode: match acceleration.refresh_mode {
+            RefreshMode::Full => "full".to_string(),
+            RefreshMode::Append => "append".to_string(),
+        },
+    })
+}
@@ -0,0 +1,148 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::{collections::HashMap, io::Write, path::Path, sync::Arc};
+
+use app::AppBuilder;
+use arrow::{
+    array::{Int64Array, RecordBatch},
+    datatypes::{DataType, Field, Schema},
+};
+use datafusion::{assert_batches_eq, parquet::arrow::ArrowWriter};
+use runtime::Runtime;
+use spicepod::component::{dataset::Dataset, params::Params};
+
+use crate::init_tracing;
+
+fn write_parquet(path: &Path, ids: Vec<i64>) -> Result<(), anyhow::Error> {
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    let batch = RecordBatch::try_new(Arc::clone(&schema), vec![Arc::new(Int64Array::from(ids))])?;
+
+    let mut writer = ArrowWriter::try_new(std::fs::File::create(path)?, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn parquet_files_matching_glob() -> Result<(), anyhow::Error> {
+    let _tracing = init_tracing(None);
+
+    let dir = std::env::temp_dir().join(format!("spice_file_glob_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("nested"))?;
+    write_parquet(&dir.join("part-0.parquet"), vec![1, 2])?;
+    write_parquet(&dir.join("part-1.parquet"), vec![3])?;
+    // Not matched by `*.parquet`, as `*` doesn't cross directories
+    write_parquet(&dir.join("nested").join("part-2.parquet"), vec![4])?;
+    std::fs::write(dir.join("README.md"), "not a parquet file")?;
+
+    let app = AppBuilder::new("file_glob")
+        .with_dataset(Dataset::new(
+            format!("file:{}/*.parquet", dir.display()),
+            "parts".to_string(),
+        ))
+        .build();
+
+    let rt = Runtime::new(Some(app), Arc::new(vec![])).await;
+    rt.load_datasets().await;
+
+    let results = rt
+        .datafusion()
+        .ctx
+        .sql("SELECT id FROM parts ORDER BY id")
+        .await?
+        .collect()
+        .await?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert_batches_eq!(
+        &["+----+", "| id |", "+----+", "| 1  |", "| 2  |", "| 3  |", "+----+",],
+        &results
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn csv_files_with_options_and_compression() -> Result<(), anyhow::Error> {
+    let _tracing = init_tracing(None);
+
+    let dir = std::env::temp_dir().join(format!("spice_file_csv_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("scores.txt"), "1|alice|9.5\n2|bob|7\n")?;
+    let mut gzipped = flate2::write::GzEncoder::new(
+        std::fs::File::create(dir.join("scores.csv.gz"))?,
+        flate2::Compression::default(),
+    );
+    gzipped.write_all(b"id,name,score\n1,alice,9.5\n2,bob,7\n")?;
+    gzipped.finish()?;
+
+    // Headerless, pipe delimited and without a recognized extension
+    let mut delimited = Dataset::new(
+        format!("file:{}/scores.txt", dir.display()),
+        "delimited".to_string(),
+    );
+    delimited.params = Some(Params::from_string_map(HashMap::from([
+        ("file_format".to_string(), "csv".to_string()),
+        ("file_extension".to_string(), ".txt".to_string()),
+        ("has_header".to_string(), "false".to_string()),
+        ("delimiter".to_string(), "|".to_string()),
+    ])));
+    // Compression inferred from the `.csv.gz` extension
+    let compressed = Dataset::new(
+        format!("file:{}/scores.csv.gz", dir.display()),
+        "compressed".to_string(),
+    );
+
+    let app = AppBuilder::new("file_csv")
+        .with_dataset(delimited)
+        .with_dataset(compressed)
+        .build();
+
+    let rt = Runtime::new(Some(app), Arc::new(vec![])).await;
+    rt.load_datasets().await;
+
+    let delimited = rt
+        .datafusion()
+        .ctx
+        .sql(
+            "SELECT column_1 AS id, column_2 AS name, column_3 AS score FROM delimited ORDER BY id",
+        )
+        .await?
+        .collect()
+        .await?;
+    let compressed = rt
+        .datafusion()
+        .ctx
+        .sql("SELECT id, name, score FROM compressed ORDER BY id")
+        .await?
+        .collect()
+        .await?;
+    std::fs::remove_dir_all(&dir)?;
+
+    let expected = [
+        "+----+-------+-------+",
+        "| id | name  | score |",
+        "+----+-------+-------+",
+        "| 1  | alice | 9.5   |",
+        "| 2  | bob   | 7.0   |",
+        "+----+-------+-------+",
+    ];
+    assert_batches_eq!(expected, &delimited);
+    assert_batches_eq!(expected, &compressed);
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::{sync::Arc, time::Duration};
+
+use arrow::{
+    array::{Int64Array, RecordBatch},
+    datatypes::{DataType, Field, Schema},
+};
+use arrow_flight::{FlightClient, Ticket};
+use clap::Parser;
+use datafusion::datasource::MemTable;
+use futures::StreamExt;
+use runtime::{config::Config, podswatcher::PodsWatcher, Runtime};
+use tonic::transport::Channel;
+
+use crate::init_tracing;
+
+const FLIGHT_BIND_ADDRESS: &str = "127.0.0.1:50063";
+const NUM_BATCHES: i64 = 10;
+const ROWS_PER_BATCH: i64 = 10_000;
+
+async fn connect() -> Result<FlightClient, String> {
+    let endpoint =
+        Channel::from_shared(format!("http://{FLIGHT_BIND_ADDRESS}")).map_err(|e| e.to_string())?;
+
+    // The Flight server is started in the background, retry until it accepts connections
+    for _ in 0..50 {
+        if let Ok(channel) = endpoint.connect().await {
+            return Ok(FlightClient::new(channel));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    Err(format!("Unable to connect to {FLIGHT_BIND_ADDRESS}"))
+}
+
+fn numbers_table() -> Result<MemTable, String> {
+    let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+    let batches = (0..NUM_BATCHES)
+        .map(|batch| {
+            let start = batch * ROWS_PER_BATCH;
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![Arc::new(Int64Array::from_iter_values(
+                    start..start + ROWS_PER_BATCH,
+                ))],
+            )
+            .map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    MemTable::try_new(schema, vec![batches]).map_err(|e| e.to_string())
+}
+
+#[tokio::test]
+async fn do_get_streams_sql_ticket_results() -> Result<(), String> {
+    let _tracing = init_tracing(None);
+
+    let mut rt = Runtime::new(None, Arc::new(vec![])).await;
+    let pods_dir = std::env::temp_dir().join("spiced_flight_do_get_test");
+    std::fs::create_dir_all(&pods_dir).map_err(|e| e.to_string())?;
+    rt.with_pods_watcher(PodsWatcher::new(pods_dir));
+
+    rt.df
+        .ctx
+        .register_table("numbers", Arc::new(numbers_table()?))
+        .map_err(|e| e.to_string())?;
+
+    let config = Config::parse_from([
+        "spiced",
+        "--http",
+        "127.0.0.1:3011",
+        "--flight",
+        FLIGHT_BIND_ADDRESS,
+        "--open_telemetry",
+        "127.0.0.1:50064",
+    ]);
+    let rt = Arc::new(rt);
+    let server_rt = Arc::clone(&rt);
+    tokio::spawn(async move { server_rt.start_servers(config, None).await });
+
+    let mut client = connect().await?;
+    let mut stream = client
+        .do_get(Ticket::new("SELECT n FROM numbers"))
+        .await
+        .map_err(|e| format!("DoGet failed: {e}"))?;
+
+    // Pull one batch at a time, like a client reading at its own pace
+    let (mut num_batches, mut num_rows) = (0, 0);
+    while let Some(batch) = stream.next().await {
+        let batch = batch.map_err(|e| format!("Failed to read batch: {e}"))?;
+        num_batches += 1;
+        num_rows += batch.num_rows();
+    }
+
+    assert!(
+        num_batches > 1,
+        "Expected the result to be streamed in multiple batches, got {num_batches}"
+    );
+    assert_eq!(
+        num_rows,
+        usize::try_from(NUM_BATCHES * ROWS_PER_BATCH).map_err(|e| e.to_string())?
+    );
+
+    Ok(())
+}
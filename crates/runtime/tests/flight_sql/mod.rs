@@ -0,0 +1,148 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::{sync::Arc, time::Duration};
+
+use app::AppBuilder;
+use arrow::array::{RecordBatch, StringArray};
+use arrow_flight::sql::{client::FlightSqlServiceClient, CommandGetTables};
+use clap::Parser;
+use futures::TryStreamExt;
+use runtime::{config::Config, podswatcher::PodsWatcher, Runtime};
+use spicepod::component::{dataset::Dataset, params::Params};
+use tonic::transport::Channel;
+
+use crate::init_tracing;
+
+const FLIGHT_BIND_ADDRESS: &str = "127.0.0.1:50061";
+
+fn make_sink_dataset(name: &str) -> Dataset {
+    let mut dataset = Dataset::new("sink".to_string(), name.to_string());
+    dataset.params = Some(Params::from_string_map(
+        vec![(
+            "schema".to_string(),
+            "CREATE TABLE t (id BIGINT, name TEXT)".to_string(),
+        )]
+        .into_iter()
+        .collect(),
+    ));
+
+    dataset
+}
+
+async fn connect() -> Result<FlightSqlServiceClient<Channel>, String> {
+    let endpoint =
+        Channel::from_shared(format!("http://{FLIGHT_BIND_ADDRESS}")).map_err(|e| e.to_string())?;
+
+    // The Flight server is started in the background, retry until it accepts connections
+    for _ in 0..50 {
+        if let Ok(channel) = endpoint.connect().await {
+            return Ok(FlightSqlServiceClient::new(channel));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    Err(format!("Unable to connect to {FLIGHT_BIND_ADDRESS}"))
+}
+
+fn string_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a StringArray, String> {
+    batch
+        .column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| format!("Missing string column {name}"))
+}
+
+#[tokio::test]
+async fn flight_sql_get_tables_returns_datasets() -> Result<(), String> {
+    let _tracing = init_tracing(None);
+
+    let app = AppBuilder::new("flight_sql_test")
+        .with_dataset(make_sink_dataset("flight_sql_get_tables"))
+        .build();
+
+    let mut rt = Runtime::new(Some(app), Arc::new(vec![])).await;
+    // Keeps the servers running for the duration of the test
+    let pods_dir = std::env::temp_dir().join("spiced_flight_sql_test");
+    std::fs::create_dir_all(&pods_dir).map_err(|e| e.to_string())?;
+    rt.with_pods_watcher(PodsWatcher::new(pods_dir));
+
+    rt.load_datasets().await;
+
+    let config = Config::parse_from([
+        "spiced",
+        "--http",
+        "127.0.0.1:3010",
+        "--flight",
+        FLIGHT_BIND_ADDRESS,
+        "--open_telemetry",
+        "127.0.0.1:50062",
+    ]);
+    let rt = Arc::new(rt);
+    let server_rt = Arc::clone(&rt);
+    tokio::spawn(async move { server_rt.start_servers(config, None).await });
+
+    let mut client = connect().await?;
+    let flight_info = client
+        .get_tables(CommandGetTables {
+            catalog: None,
+            db_schema_filter_pattern: None,
+            table_name_filter_pattern: None,
+            table_types: vec![],
+            include_schema: false,
+        })
+        .await
+        .map_err(|e| format!("GetTables failed: {e}"))?;
+
+    let mut batches = Vec::new();
+    for endpoint in flight_info.endpoint {
+        let ticket = endpoint.ticket.ok_or("Missing ticket")?;
+        let stream = client
+            .do_get(ticket)
+            .await
+            .map_err(|e| format!("DoGet failed: {e}"))?;
+        batches.extend(
+            stream
+                .try_collect::<Vec<RecordBatch>>()
+                .await
+                .map_err(|e| format!("Failed to collect GetTables results: {e}"))?,
+        );
+    }
+
+    let mut tables = Vec::new();
+    for batch in &batches {
+        let catalogs = string_column(batch, "catalog_name")?;
+        let schemas = string_column(batch, "db_schema_name")?;
+        let names = string_column(batch, "table_name")?;
+        for row in 0..batch.num_rows() {
+            tables.push((
+                catalogs.value(row).to_string(),
+                schemas.value(row).to_string(),
+                names.value(row).to_string(),
+            ));
+        }
+    }
+
+    assert!(
+        tables.contains(&(
+            "spice".to_string(),
+            "public".to_string(),
+            "flight_sql_get_tables".to_string()
+        )),
+        "GetTables did not return the registered dataset: {tables:?}"
+    );
+
+    Ok(())
+}
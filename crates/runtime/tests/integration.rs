@@ -28,8 +28,13 @@ use tracing_subscriber::EnvFilter;
 mod docker;
 // Run all tests in the `federation` module
 mod federation;
+mod file;
+mod flight;
+mod flight_sql;
+mod ready_timeout;
 mod refresh_sql;
 mod results_cache;
+mod startup_report;
 
 /// Modifies the runtime configuration of `DataFusion` to make test results reproducible across all machines.
 ///
@@ -0,0 +1,59 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::{sync::Arc, time::Duration};
+
+use app::AppBuilder;
+use runtime::Runtime;
+use spicepod::component::dataset::Dataset;
+
+use crate::init_tracing;
+
+#[tokio::test]
+async fn ready_timeout_fails_when_dataset_does_not_load() -> Result<(), anyhow::Error> {
+    let _tracing = init_tracing(None);
+
+    // The connector doesn't exist, so the dataset is retried until the timeout elapses
+    let app = AppBuilder::new("ready_timeout")
+        .with_dataset(Dataset::new(
+            "not_a_connector:table".to_string(),
+            "never_ready".to_string(),
+        ))
+        .build();
+
+    let rt = Runtime::new(Some(app), Arc::new(vec![])).await;
+
+    let ready_timeout = Duration::from_millis(500);
+    let Err(err) = rt.load_components_with_timeout(ready_timeout).await else {
+        panic!("expected the ready timeout to elapse");
+    };
+    assert!(matches!(err, runtime::Error::ReadyTimeout { ready_timeout: t } if t == ready_timeout));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ready_timeout_succeeds_when_components_load() -> Result<(), anyhow::Error> {
+    let _tracing = init_tracing(None);
+
+    let app = AppBuilder::new("ready_timeout_no_datasets").build();
+    let rt = Runtime::new(Some(app), Arc::new(vec![])).await;
+
+    rt.load_components_with_timeout(Duration::from_secs(10))
+        .await?;
+
+    Ok(())
+}
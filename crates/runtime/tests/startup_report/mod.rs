@@ -0,0 +1,84 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::sync::Arc;
+
+use app::AppBuilder;
+use runtime::{status::ComponentStatus, Runtime};
+use spicepod::component::dataset::{acceleration::Acceleration, Dataset};
+
+use crate::init_tracing;
+
+#[tokio::test]
+async fn startup_report_lists_loaded_datasets() -> Result<(), anyhow::Error> {
+    let _tracing = init_tracing(None);
+
+    let dir = std::env::temp_dir().join(format!("spice_startup_report_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("scores.csv"), "id,score\n1,9.5\n")?;
+
+    let federated = Dataset::new(
+        format!("file:{}/scores.csv", dir.display()),
+        "scores".to_string(),
+    );
+    let mut accelerated = Dataset::new(
+        format!("file:{}/scores.csv", dir.display()),
+        "accelerated_scores".to_string(),
+    );
+    accelerated.acceleration = Some(Acceleration::default());
+    // Dataset names can't include a catalog
+    let invalid = Dataset::new("sink".to_string(), "catalog.schema.table".to_string());
+
+    let app = AppBuilder::new("startup_report")
+        .with_dataset(federated)
+        .with_dataset(accelerated)
+        .with_dataset(invalid)
+        .build();
+
+    let rt = Runtime::new(Some(app), Arc::new(vec![])).await;
+    rt.load_datasets().await;
+
+    let path = dir.join("startup_report.json");
+    rt.write_startup_report(&path).await;
+    let report: runtime::startup_report::StartupReport =
+        serde_json::from_slice(&std::fs::read(&path)?)?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert_eq!(report.app.as_deref(), Some("startup_report"));
+    assert_eq!(report.datasets.len(), 3);
+
+    let scores = &report.datasets[0];
+    assert_eq!(scores.name, "scores");
+    assert_eq!(scores.source.as_deref(), Some("file"));
+    assert_eq!(scores.status, ComponentStatus::Ready);
+    assert!(scores.acceleration.is_none());
+
+    let accelerated = &report.datasets[1];
+    assert_eq!(accelerated.status, ComponentStatus::Ready);
+    let acceleration = accelerated
+        .acceleration
+        .as_ref()
+        .expect("accelerated dataset reports its acceleration");
+    assert_eq!(acceleration.engine, "arrow");
+    assert_eq!(acceleration.refresh_mode, "full");
+
+    let invalid = &report.datasets[2];
+    assert_eq!(invalid.name, "catalog.schema.table");
+    assert_eq!(invalid.status, ComponentStatus::Error);
+    assert!(invalid.error.is_some());
+
+    Ok(())
+}
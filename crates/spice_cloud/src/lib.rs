@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use datafusion::{datasource::TableProvider, sql::TableReference};
@@ -14,7 +14,7 @@ use runtime::{
         Dataset, Mode, TimeFormat,
     },
     dataaccelerator::{self, create_accelerator_table},
-    dataconnector::{create_new_connector, DataConnectorError},
+    dataconnector::{create_new_connector, spiceai, DataConnectorError},
     extension::{Extension, ExtensionFactory, ExtensionManifest, Result},
     spice_metrics::get_metrics_table_reference,
     Runtime,
@@ -89,7 +89,7 @@ impl SpiceExtension {
 
     async fn connect(&self, runtime: &Runtime) -> Result<SpiceCloudConnectResponse, Error> {
         let api_key = self.get_spice_api_key(runtime).await?;
-        let client = reqwest::Client::new();
+        let client = util::http::client().context(UnableToConnectToSpiceCloudSnafu)?;
         let response = client
             .post(format!("{}/v1/connect", self.spice_http_url()))
             .json(&json!({}))
@@ -224,10 +224,93 @@ impl ExtensionFactory for SpiceExtensionFactory {
     }
 }
 
+/// How often creating a spice.ai table provider is attempted before a transient failure is reported.
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_attempts: usize,
+    initial_backoff: Duration,
+}
+
+const PROVIDER_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 5,
+    initial_backoff: Duration::from_millis(500),
+};
+
+/// Runs `operation` until it succeeds, fails with an error that retrying won't fix, or runs out of attempts.
+/// The delay between attempts doubles after each failure.
+async fn with_retries<T, F, Fut>(policy: RetryPolicy, mut operation: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.max_attempts && is_transient(&err) => {
+                tracing::warn!(
+                    "Attempt {attempt} of {} to connect to spice.ai failed, retrying in {backoff:?}: {err}",
+                    policy.max_attempts
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Missing credentials and rejected API keys won't resolve themselves, everything else (i.e. network errors) might.
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::UnableToCreateDataConnector { .. }
+        | Error::UnableToCreateSourceTableProvider { .. } => !is_auth_error(err),
+        _ => false,
+    }
+}
+
+fn is_auth_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(status) = err.downcast_ref::<tonic::Status>() {
+            if matches!(
+                status.code(),
+                tonic::Code::Unauthenticated | tonic::Code::PermissionDenied
+            ) {
+                return true;
+            }
+        }
+        if matches!(
+            err.downcast_ref::<flight_client::Error>(),
+            Some(flight_client::Error::Unauthorized {} | flight_client::Error::PermissionDenied {})
+        ) || matches!(
+            err.downcast_ref::<spiceai::Error>(),
+            Some(spiceai::Error::MissingRequiredSecrets)
+        ) {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
 async fn get_spiceai_table_provider(
     name: &str,
     cloud_dataset_path: &str,
     secret: Option<Secret>,
+) -> Result<Arc<dyn TableProvider>, Error> {
+    with_retries(PROVIDER_RETRY_POLICY, || {
+        try_get_spiceai_table_provider(name, cloud_dataset_path, secret.clone())
+    })
+    .await
+}
+
+async fn try_get_spiceai_table_provider(
+    name: &str,
+    cloud_dataset_path: &str,
+    secret: Option<Secret>,
 ) -> Result<Arc<dyn TableProvider>, Error> {
     let mut dataset = Dataset::try_new(cloud_dataset_path.to_string(), name)
         .boxed()
@@ -296,3 +379,67 @@ struct SpiceCloudConnectResponse {
     app_name: String,
     metrics_dataset_name: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const TEST_RETRY_POLICY: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(1),
+    };
+
+    fn connection_error() -> Error {
+        Error::UnableToCreateDataConnector {
+            source: "connection reset by peer".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_is_retried() {
+        let attempts = AtomicUsize::new(0);
+        let result = with_retries(TEST_RETRY_POLICY, || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(connection_error())
+            } else {
+                Ok("provider")
+            }
+        })
+        .await;
+
+        assert_eq!(result.expect("succeeds after a retry"), "provider");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_bounded() {
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), Error> = with_retries(TEST_RETRY_POLICY, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(connection_error())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            attempts.load(Ordering::SeqCst),
+            TEST_RETRY_POLICY.max_attempts
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure_is_not_retried() {
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), Error> = with_retries(TEST_RETRY_POLICY, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::UnableToCreateDataConnector {
+                source: Box::new(flight_client::Error::Unauthorized {}),
+            })
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -34,6 +34,8 @@ pub enum TimeFormat {
     UnixMillis,
     #[serde(rename = "ISO8601")]
     ISO8601,
+    /// A chrono `strftime` pattern for timestamps stored as strings, i.e. `%m/%d/%Y %H:%M`.
+    Custom(String),
 }
 
 impl std::fmt::Display for TimeFormat {
@@ -78,6 +80,11 @@ pub struct Dataset {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub acceleration: Option<acceleration::Acceleration>,
 
+    /// How long query results reading this dataset are cached, overriding the `item_ttl` of the results cache.
+    /// `0s` disables caching results of this dataset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results_cache_ttl: Option<String>,
+
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(rename = "dependsOn", default)]
     pub depends_on: Vec<String>,
@@ -98,6 +105,7 @@ impl Dataset {
             time_column: None,
             time_format: None,
             acceleration: None,
+            results_cache_ttl: None,
             depends_on: Vec::default(),
         }
     }
@@ -117,6 +125,7 @@ impl WithDependsOn<Dataset> for Dataset {
             time_column: self.time_column.clone(),
             time_format: self.time_format.clone(),
             acceleration: self.acceleration.clone(),
+            results_cache_ttl: self.results_cache_ttl.clone(),
             depends_on: depends_on.to_vec(),
         }
     }
@@ -173,6 +182,19 @@ pub mod acceleration {
         }
     }
 
+    /// How `refresh_sql` references to tables other than the dataset itself are reported.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(rename_all = "snake_case")]
+    pub enum RefreshSqlReferenceCheck {
+        /// Log a warning when loading the dataset. This is the default.
+        #[default]
+        Warn,
+        /// Fail to load the dataset.
+        Error,
+        /// Allow references to other tables, i.e. for datasets derived from other datasets.
+        Off,
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
     pub struct Acceleration {
         #[serde(default = "default_true")]
@@ -196,6 +218,19 @@ pub mod acceleration {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub refresh_data_window: Option<String>,
 
+        /// Limits how far back the first `append` refresh into an empty accelerator loads data.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub refresh_initial_backfill_window: Option<String>,
+
+        /// Drop identical rows that arrive within the same `append` refresh before inserting them.
+        #[serde(default, skip_serializing_if = "is_false")]
+        pub refresh_append_dedup_rows: bool,
+
+        /// Re-fetch rows whose timestamp equals the latest one in the accelerator on `append` refreshes.
+        /// Requires `refresh_append_dedup_rows` to avoid inserting those rows again.
+        #[serde(default, skip_serializing_if = "is_false")]
+        pub refresh_append_boundary_inclusive: bool,
+
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub params: Option<Params>,
 
@@ -213,6 +248,22 @@ pub mod acceleration {
 
         #[serde(default)]
         pub on_zero_results: ZeroResultsAction,
+
+        #[serde(default)]
+        pub refresh_sql_reference_check: RefreshSqlReferenceCheck,
+
+        /// Accelerate a pre-aggregated rollup of the source instead of its raw rows.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub rollup: Option<Rollup>,
+    }
+
+    /// Groups the source rows by `group_by` and stores one row per group with the `aggregations`, i.e.
+    /// `sum(amount) AS total_amount`, computed on every refresh.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Rollup {
+        pub group_by: Vec<String>,
+
+        pub aggregations: Vec<String>,
     }
 
     #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -234,12 +285,17 @@ pub mod acceleration {
                 refresh_check_interval: None,
                 refresh_sql: None,
                 refresh_data_window: None,
+                refresh_initial_backfill_window: None,
+                refresh_append_dedup_rows: false,
+                refresh_append_boundary_inclusive: false,
                 params: None,
                 engine_secret: None,
                 retention_period: None,
                 retention_check_interval: None,
                 retention_check_enabled: false,
                 on_zero_results: ZeroResultsAction::ReturnEmpty,
+                refresh_sql_reference_check: RefreshSqlReferenceCheck::Warn,
+                rollup: None,
             }
         }
     }
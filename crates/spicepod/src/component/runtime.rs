@@ -20,7 +20,47 @@ use serde::{Deserialize, Serialize};
 pub struct Runtime {
     #[serde(default)]
     pub results_cache: ResultsCache,
+
+    /// Reuses the logical plans of repeated identical queries instead of re-planning them.
+    #[serde(default)]
+    pub plan_cache: PlanCache,
     pub num_of_parallel_loading_at_start_up: Option<usize>,
+
+    /// Maximum number of refreshes that may write to the same file-mode accelerator file at once.
+    /// Defaults to 1, serializing refresh writes of datasets that share an accelerator file.
+    pub num_of_parallel_refreshes_per_accelerator_file: Option<usize>,
+
+    /// Load accelerated datasets in the order of their dependencies, so a dataset whose `refresh_sql` or view
+    /// reads from other datasets is only refreshed once those have completed their initial refresh.
+    #[serde(default)]
+    pub dependency_ordered_refresh: bool,
+
+    /// Overrides the `User-Agent` sent by connectors on outbound HTTP requests, which defaults to `spiced/<version>`.
+    pub user_agent: Option<String>,
+
+    /// Routes outbound connector HTTP requests through a proxy, taking precedence over the `HTTP_PROXY`, `HTTPS_PROXY`
+    /// and `NO_PROXY` environment variables.
+    pub http_proxy: Option<HttpProxy>,
+
+    /// Time zone that timestamps in query results are rendered in, as an offset like `+05:00` or a name like
+    /// `America/New_York`. Only the rendering changes, not the instant a timestamp refers to. Defaults to UTC.
+    pub session_timezone: Option<String>,
+
+    /// Number of partitions that scans, joins and aggregations of a query are split into to run in parallel.
+    /// Defaults to the number of CPUs; lower it to leave CPUs to services running on the same host.
+    pub query_parallelism: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HttpProxy {
+    pub url: String,
+
+    /// Hosts, domains and IP ranges that bypass the proxy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+
+    pub username: Option<String>,
+    pub password: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -30,6 +70,19 @@ pub struct ResultsCache {
     pub cache_max_size: Option<String>,
     pub item_ttl: Option<String>,
     pub eviction_policy: Option<String>,
+
+    /// Request headers whose values are added to the cache key of HTTP queries, so that e.g. tenants don't share cached results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cache_key_headers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PlanCache {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Maximum number of cached plans. Defaults to 1024.
+    pub max_entries: Option<u64>,
 }
 
 const fn default_true() -> bool {
@@ -43,6 +96,7 @@ impl Default for ResultsCache {
             cache_max_size: None,
             item_ttl: None,
             eviction_policy: None,
+            cache_key_headers: Vec::new(),
         }
     }
 }
@@ -14,6 +14,9 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+pub mod http;
+pub mod rate_limit;
+
 use std::{
     cmp,
     time::{Duration, SystemTime, SystemTimeError},
@@ -0,0 +1,201 @@
+/*
+Copyright 2024 The Spice.ai OSS Authors
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+     https://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Token-bucket rate limiting of the requests connectors send to external APIs.
+
+use std::{collections::HashMap, time::Duration};
+
+use snafu::prelude::*;
+use tokio::{sync::Mutex, time::Instant};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(
+        "Invalid value for rate_limit_requests_per_second: {value}. Expected a number of at least 1/86400, i.e. one request per day"
+    ))]
+    InvalidRequestsPerSecond { value: String },
+
+    #[snafu(display("Invalid value for rate_limit_burst: {value}. Expected a positive integer"))]
+    InvalidBurst { value: String },
+
+    #[snafu(display("rate_limit_burst requires rate_limit_requests_per_second to be set"))]
+    BurstWithoutRate,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Slower rates would make requests wait for days, and tiny rates overflow the wait duration.
+const MIN_REQUESTS_PER_SECOND: f64 = 1.0 / 86_400.0;
+
+/// Limits requests to a sustained rate, while allowing up to `burst` requests at once after a period of inactivity.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests have reserved tokens that haven't been refilled yet.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter that starts with a full bucket of `burst` tokens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` isn't positive or `burst` is zero.
+    #[must_use]
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second > 0.0 && burst > 0,
+            "rate limits must be positive"
+        );
+        Self {
+            requests_per_second,
+            burst: f64::from(burst),
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Reads the `rate_limit_requests_per_second` and `rate_limit_burst` connector params.
+    ///
+    /// Returns `None` if no rate limit is configured. The burst defaults to 1, so requests are evenly spaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rate is below one request per day, the burst isn't a positive integer, or if only the
+    /// burst is set.
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Option<Self>> {
+        let burst = params
+            .get("rate_limit_burst")
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|burst| *burst > 0)
+                    .context(InvalidBurstSnafu { value })
+            })
+            .transpose()?;
+
+        let Some(value) = params.get("rate_limit_requests_per_second") else {
+            ensure!(burst.is_none(), BurstWithoutRateSnafu);
+            return Ok(None);
+        };
+        let requests_per_second = value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|rate| rate.is_finite() && *rate >= MIN_REQUESTS_PER_SECOND)
+            .context(InvalidRequestsPerSecondSnafu { value })?;
+
+        Ok(Some(Self::new(requests_per_second, burst.unwrap_or(1))))
+    }
+
+    /// Waits until a request may be sent.
+    ///
+    /// Each caller reserves a token before waiting, so concurrent callers are spaced out rather than all sending once a
+    /// token becomes available.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().await;
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.burst);
+            bucket.refilled_at = now;
+            bucket.tokens -= 1.0;
+
+            // Saturates rather than panics for rates so low that the wait doesn't fit a duration
+            (bucket.tokens < 0.0).then(|| {
+                Duration::try_from_secs_f64(-bucket.tokens / self.requests_per_second)
+                    .unwrap_or(Duration::MAX)
+            })
+        };
+
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Paused time advances to the next timer as soon as the runtime is idle, so the timing is exact
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_are_throttled() {
+        let limiter = RateLimiter::new(20.0, 2);
+
+        let start = Instant::now();
+        for _ in 0..6 {
+            limiter.acquire().await;
+        }
+
+        // The burst of 2 is sent at once, the remaining 4 requests are spaced 50ms apart
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(199), "{elapsed:?}");
+        assert!(elapsed <= Duration::from_millis(201), "{elapsed:?}");
+    }
+
+    #[test]
+    fn test_from_params() {
+        let params = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert!(matches!(RateLimiter::from_params(&params(&[])), Ok(None)));
+
+        let limiter = RateLimiter::from_params(&params(&[
+            ("rate_limit_requests_per_second", "0.5"),
+            ("rate_limit_burst", "10"),
+        ]))
+        .expect("valid rate limit params")
+        .expect("rate limit configured");
+        assert!((limiter.requests_per_second - 0.5).abs() < f64::EPSILON);
+        assert!((limiter.burst - 10.0).abs() < f64::EPSILON);
+
+        for rate in ["0", "1e-310", "inf"] {
+            assert!(matches!(
+                RateLimiter::from_params(&params(&[("rate_limit_requests_per_second", rate)])),
+                Err(Error::InvalidRequestsPerSecond { .. })
+            ));
+        }
+        assert!(matches!(
+            RateLimiter::from_params(&params(&[("rate_limit_burst", "5")])),
+            Err(Error::BurstWithoutRate)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tiny_rate_does_not_panic() {
+        let limiter = RateLimiter::new(1e-310, 1);
+        limiter.acquire().await;
+        // The second request would wait longer than a duration can hold
+        let wait = tokio::time::timeout(Duration::from_secs(3600), limiter.acquire()).await;
+        assert!(wait.is_err(), "the request keeps waiting");
+    }
+}